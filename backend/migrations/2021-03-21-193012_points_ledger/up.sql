@@ -0,0 +1,18 @@
+-- Your SQL goes here
+
+CREATE TABLE points_ledger (
+  id SERIAL PRIMARY KEY,
+  user_id INT NOT NULL,
+  source_type TEXT NOT NULL,
+  source_id INT,
+  amount INT NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  CONSTRAINT user_id_fk FOREIGN KEY(user_id) REFERENCES users(id) ON UPDATE CASCADE ON DELETE CASCADE
+);
+
+CREATE INDEX points_ledger_user_id_idx ON points_ledger (user_id, created_at);
+
+-- Open the ledger with each user's current balance so that the
+-- ledger and users.bspts agree from the start
+INSERT INTO points_ledger (user_id, source_type, amount)
+SELECT id, 'Adjustment', bspts FROM users WHERE bspts <> 0;
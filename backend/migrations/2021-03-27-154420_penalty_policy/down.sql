@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE tasks
+DROP COLUMN penalty_policy,
+DROP COLUMN penalty_pts,
+DROP COLUMN misses;
\ No newline at end of file
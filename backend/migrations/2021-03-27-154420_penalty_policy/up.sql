@@ -0,0 +1,6 @@
+-- Your SQL goes here
+
+ALTER TABLE tasks
+ADD COLUMN penalty_policy TEXT NOT NULL DEFAULT 'None',
+ADD COLUMN penalty_pts INT NOT NULL DEFAULT 0,
+ADD COLUMN misses INT NOT NULL DEFAULT 0;
\ No newline at end of file
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE rollovers;
+
+ALTER TABLE users
+DROP COLUMN last_rollover;
\ No newline at end of file
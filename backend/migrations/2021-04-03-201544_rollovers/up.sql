@@ -0,0 +1,15 @@
+-- Your SQL goes here
+
+ALTER TABLE users
+ADD COLUMN last_rollover DATE;
+
+CREATE TABLE rollovers (
+  id SERIAL PRIMARY KEY,
+  user_id INT NOT NULL,
+  rolled_over_on DATE NOT NULL,
+  tasks_reset INT NOT NULL,
+  tasks_missed INT NOT NULL,
+  pts_lost INT NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  CONSTRAINT user_id_fk FOREIGN KEY(user_id) REFERENCES users(id) ON UPDATE CASCADE ON DELETE CASCADE
+);
\ No newline at end of file
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+
+ALTER TABLE users
+ADD COLUMN time_zone TEXT NOT NULL DEFAULT 'UTC';
\ No newline at end of file
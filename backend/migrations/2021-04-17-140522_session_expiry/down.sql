@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX sessions_user_id_idx;
+
+ALTER TABLE sessions
+DROP COLUMN created_at,
+DROP COLUMN last_seen,
+DROP COLUMN expires_at;
\ No newline at end of file
@@ -0,0 +1,8 @@
+-- Your SQL goes here
+
+ALTER TABLE sessions
+ADD COLUMN created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+ADD COLUMN last_seen TIMESTAMP NOT NULL DEFAULT NOW(),
+ADD COLUMN expires_at TIMESTAMP NOT NULL DEFAULT NOW() + INTERVAL '30 days';
+
+CREATE INDEX sessions_user_id_idx ON sessions(user_id);
\ No newline at end of file
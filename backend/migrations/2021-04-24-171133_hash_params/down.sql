@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE users
+DROP COLUMN hash_version,
+DROP COLUMN hash_algorithm,
+DROP COLUMN hash_iterations,
+DROP COLUMN hash_memory_kib;
\ No newline at end of file
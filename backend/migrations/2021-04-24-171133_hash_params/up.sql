@@ -0,0 +1,8 @@
+-- Your SQL goes here
+
+-- Existing credentials are all the original PBKDF2 format
+ALTER TABLE users
+ADD COLUMN hash_version INT NOT NULL DEFAULT 1,
+ADD COLUMN hash_algorithm TEXT NOT NULL DEFAULT 'pbkdf2-sha256',
+ADD COLUMN hash_iterations INT NOT NULL DEFAULT 100000,
+ADD COLUMN hash_memory_kib INT NOT NULL DEFAULT 0;
\ No newline at end of file
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX users_calendar_token_hash_idx;
+
+ALTER TABLE users
+DROP COLUMN calendar_token_hash;
\ No newline at end of file
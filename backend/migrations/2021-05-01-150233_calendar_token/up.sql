@@ -0,0 +1,6 @@
+-- Your SQL goes here
+
+ALTER TABLE users
+ADD COLUMN calendar_token_hash BYTEA;
+
+CREATE UNIQUE INDEX users_calendar_token_hash_idx ON users(calendar_token_hash);
\ No newline at end of file
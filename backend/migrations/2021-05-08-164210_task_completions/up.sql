@@ -0,0 +1,16 @@
+-- Your SQL goes here
+
+CREATE TABLE task_completions (
+  id SERIAL PRIMARY KEY,
+  task_id INT NOT NULL,
+  user_id INT NOT NULL,
+  completed_on DATE NOT NULL,
+  bspts INT NOT NULL,
+  on_time BOOLEAN NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  CONSTRAINT task_id_fk FOREIGN KEY(task_id) REFERENCES tasks(id) ON UPDATE CASCADE ON DELETE CASCADE,
+  CONSTRAINT user_id_fk FOREIGN KEY(user_id) REFERENCES users(id) ON UPDATE CASCADE ON DELETE CASCADE
+);
+
+CREATE INDEX task_completions_user_date_idx ON task_completions(user_id, completed_on);
+CREATE INDEX task_completions_task_idx ON task_completions(task_id);
\ No newline at end of file
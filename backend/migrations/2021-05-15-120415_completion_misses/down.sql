@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE task_completions
+DROP COLUMN misses;
\ No newline at end of file
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+
+ALTER TABLE task_completions
+ADD COLUMN misses INT NOT NULL DEFAULT 0;
\ No newline at end of file
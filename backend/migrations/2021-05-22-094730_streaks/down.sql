@@ -0,0 +1,11 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE task_completions
+DROP COLUMN bonus,
+DROP COLUMN streak;
+
+ALTER TABLE tasks
+DROP COLUMN streak_bonus_amount,
+DROP COLUMN streak_bonus_policy,
+DROP COLUMN best_streak,
+DROP COLUMN streak;
\ No newline at end of file
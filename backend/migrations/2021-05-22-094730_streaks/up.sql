@@ -0,0 +1,11 @@
+-- Your SQL goes here
+
+ALTER TABLE tasks
+ADD COLUMN streak INT NOT NULL DEFAULT 0,
+ADD COLUMN best_streak INT NOT NULL DEFAULT 0,
+ADD COLUMN streak_bonus_policy TEXT NOT NULL DEFAULT 'None',
+ADD COLUMN streak_bonus_amount INT NOT NULL DEFAULT 0;
+
+ALTER TABLE task_completions
+ADD COLUMN streak INT NOT NULL DEFAULT 0,
+ADD COLUMN bonus INT NOT NULL DEFAULT 0;
\ No newline at end of file
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE rewards
+DROP COLUMN archived_at;
\ No newline at end of file
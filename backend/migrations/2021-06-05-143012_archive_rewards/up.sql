@@ -0,0 +1,4 @@
+-- Your SQL goes here
+
+ALTER TABLE rewards
+ADD COLUMN archived_at TIMESTAMP;
\ No newline at end of file
@@ -0,0 +1,15 @@
+-- Your SQL goes here
+
+CREATE TABLE reward_redemptions (
+  id SERIAL PRIMARY KEY,
+  reward_id INT NOT NULL,
+  user_id INT NOT NULL,
+  redeemed_on DATE NOT NULL,
+  bspts INT NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  CONSTRAINT reward_id_fk FOREIGN KEY(reward_id) REFERENCES rewards(id) ON UPDATE CASCADE ON DELETE CASCADE,
+  CONSTRAINT user_id_fk FOREIGN KEY(user_id) REFERENCES users(id) ON UPDATE CASCADE ON DELETE CASCADE
+);
+
+CREATE INDEX reward_redemptions_user_date_idx ON reward_redemptions(user_id, redeemed_on);
+CREATE INDEX reward_redemptions_reward_idx ON reward_redemptions(reward_id);
\ No newline at end of file
@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE rewards
+DROP COLUMN cooldown_days,
+DROP COLUMN limit_period,
+DROP COLUMN limit_times,
+DROP COLUMN allow_overdraft;
\ No newline at end of file
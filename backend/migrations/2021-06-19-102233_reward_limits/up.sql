@@ -0,0 +1,7 @@
+-- Your SQL goes here
+
+ALTER TABLE rewards
+ADD COLUMN cooldown_days INT NOT NULL DEFAULT 0,
+ADD COLUMN limit_period TEXT NOT NULL DEFAULT 'None',
+ADD COLUMN limit_times INT NOT NULL DEFAULT 0,
+ADD COLUMN allow_overdraft BOOLEAN NOT NULL DEFAULT FALSE;
\ No newline at end of file
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE rewards
+DROP COLUMN price_increment,
+DROP COLUMN price_decay;
\ No newline at end of file
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+
+ALTER TABLE rewards
+ADD COLUMN price_increment INT NOT NULL DEFAULT 0,
+ADD COLUMN price_decay INT NOT NULL DEFAULT 0;
\ No newline at end of file
@@ -0,0 +1,13 @@
+-- Your SQL goes here
+
+CREATE TABLE goals (
+  id SERIAL PRIMARY KEY,
+  user_id INT NOT NULL,
+  reward_id INT NOT NULL UNIQUE,
+  saved INT NOT NULL DEFAULT 0,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  CONSTRAINT reward_id_fk FOREIGN KEY(reward_id) REFERENCES rewards(id) ON UPDATE CASCADE ON DELETE CASCADE,
+  CONSTRAINT user_id_fk FOREIGN KEY(user_id) REFERENCES users(id) ON UPDATE CASCADE ON DELETE CASCADE
+);
+
+CREATE INDEX goals_user_idx ON goals(user_id);
\ No newline at end of file
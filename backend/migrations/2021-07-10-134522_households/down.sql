@@ -0,0 +1,30 @@
+-- This file should undo anything in `up.sql`
+
+-- Only the owner of a reward could save for it before households, so the points
+-- other members saved for shared rewards are given back before their goals are removed
+INSERT INTO points_ledger (user_id, source_type, source_id, amount)
+SELECT goals.user_id, 'GoalRelease', goals.id, goals.saved
+FROM goals JOIN rewards ON rewards.id = goals.reward_id
+WHERE goals.user_id <> rewards.user_id AND goals.saved <> 0;
+
+UPDATE users SET bspts = users.bspts + released.saved
+FROM (
+  SELECT goals.user_id, SUM(goals.saved) AS saved
+  FROM goals JOIN rewards ON rewards.id = goals.reward_id
+  WHERE goals.user_id <> rewards.user_id
+  GROUP BY goals.user_id
+) AS released
+WHERE users.id = released.user_id;
+
+DELETE FROM goals USING rewards
+WHERE rewards.id = goals.reward_id AND goals.user_id <> rewards.user_id;
+
+ALTER TABLE goals
+DROP CONSTRAINT goals_user_reward_key,
+ADD CONSTRAINT goals_reward_id_key UNIQUE (reward_id);
+
+ALTER TABLE rewards DROP COLUMN household_id;
+ALTER TABLE tasks DROP COLUMN household_id;
+ALTER TABLE users DROP COLUMN household_id;
+
+DROP TABLE households;
\ No newline at end of file
@@ -0,0 +1,29 @@
+-- Your SQL goes here
+
+CREATE TABLE households (
+  id SERIAL PRIMARY KEY,
+  name TEXT NOT NULL,
+  invite_code TEXT NOT NULL UNIQUE,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE users
+ADD COLUMN household_id INT,
+ADD CONSTRAINT household_id_fk FOREIGN KEY(household_id) REFERENCES households(id) ON UPDATE CASCADE ON DELETE SET NULL;
+
+ALTER TABLE tasks
+ADD COLUMN household_id INT,
+ADD CONSTRAINT household_id_fk FOREIGN KEY(household_id) REFERENCES households(id) ON UPDATE CASCADE ON DELETE SET NULL;
+
+ALTER TABLE rewards
+ADD COLUMN household_id INT,
+ADD CONSTRAINT household_id_fk FOREIGN KEY(household_id) REFERENCES households(id) ON UPDATE CASCADE ON DELETE SET NULL;
+
+CREATE INDEX users_household_idx ON users(household_id);
+CREATE INDEX tasks_household_idx ON tasks(household_id);
+CREATE INDEX rewards_household_idx ON rewards(household_id);
+
+-- Each member of a household can save for a shared reward
+ALTER TABLE goals
+DROP CONSTRAINT goals_reward_id_key,
+ADD CONSTRAINT goals_user_reward_key UNIQUE (user_id, reward_id);
\ No newline at end of file
@@ -1,37 +1,77 @@
-use actix_web::{error, web::Json, http::StatusCode};
+use actix_web::{web::Json, http::StatusCode, HttpResponse, ResponseError};
+use data::error::{ApiError, FieldError};
+use std::fmt;
 
-pub type Error = error::Error;
+pub type Error = actix_web::Error;
 pub type Result<T> = actix_web::Result<T>;
 pub type Rsp<T> = Result<Json<T>>;
 
+/// Sends an ApiError back as JSON, with the status code that goes with it
+#[derive(Debug)]
+pub struct ErrorResponse(pub ApiError);
+
+impl fmt::Display for ErrorResponse {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl ResponseError for ErrorResponse {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::from_u16(self.0.status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        // The client only gets told something went wrong, so keep the details here
+        if status.is_server_error() {
+            println!("Error handling request: {}", self.0);
+        }
+        HttpResponse::build(status).json(&self.0)
+    }
+}
+
+/// Sends an ApiError that was made elsewhere, e.g. by validating a request
+pub fn api_error(error: ApiError) -> Error {
+    ErrorResponse(error).into()
+}
+
 pub fn not_found(msg: String) -> Error {
-    let error = error::InternalError::new(
-        msg,
-        StatusCode::NOT_FOUND
-    );
-    error.into()
+    ErrorResponse(ApiError::NotFound(msg)).into()
 }
 
 pub fn unauthorized(msg: String) -> Error {
-    let error = error::InternalError::new(
-        msg,
-        StatusCode::UNAUTHORIZED
-    );
-    error.into()
+    ErrorResponse(ApiError::Unauthorized(msg)).into()
+}
+
+pub fn forbidden(msg: String) -> Error {
+    ErrorResponse(ApiError::Forbidden(msg)).into()
+}
+
+pub fn validation(msg: String, fields: Vec<FieldError>) -> Error {
+    ErrorResponse(ApiError::Validation{message: msg, fields}).into()
 }
 
 pub fn conflict(msg: String) -> Error {
-    let error = error::InternalError::new(
-        msg,
-        StatusCode::CONFLICT
-    );
-    error.into()
+    ErrorResponse(ApiError::Conflict(msg)).into()
 }
 
 pub fn bad_request(msg: String) -> Error {
-    let error = error::InternalError::new(
-        msg,
-        StatusCode::BAD_REQUEST
-    );
-    error.into()
+    ErrorResponse(ApiError::BadRequest(msg)).into()
+}
+
+pub fn internal(msg: String) -> Error {
+    ErrorResponse(ApiError::Internal(msg)).into()
+}
+
+pub fn cooling_down(msg: String) -> Error {
+    ErrorResponse(ApiError::CoolingDown(msg)).into()
+}
+
+pub fn limit_reached(msg: String) -> Error {
+    ErrorResponse(ApiError::LimitReached(msg)).into()
+}
+
+pub fn insufficient_points(msg: String) -> Error {
+    ErrorResponse(ApiError::InsufficientPoints(msg)).into()
 }
\ No newline at end of file
@@ -10,6 +10,7 @@ mod models;
 mod schema;
 pub mod route;
 pub mod error;
+pub mod scheduler;
 
 use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
 use diesel::pg::PgConnection;
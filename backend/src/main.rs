@@ -2,12 +2,13 @@ extern crate diesel;
 extern crate dotenv;
 extern crate chrono;
 
+use actix::Actor;
 use actix_web::{App, HttpServer};
 use actix_files as fs;
 use dotenv::dotenv;
 use std::env;
 use actix_session::{CookieSession};
-use backend_lib::{self, route, get_connection_pool, run_db_migration};
+use backend_lib::{self, route, scheduler::RolloverScheduler, get_connection_pool, run_db_migration};
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -15,19 +16,27 @@ async fn main() -> std::io::Result<()> {
 
     let pool = get_connection_pool();
     run_db_migration(&pool);
+    RolloverScheduler::new(pool.clone()).start();
 
     let api_url = env::var("API_URL").expect("API_URL must be set");
     let cookie_key =  env::var("COOKIE_KEY").expect("COOKIE_KEY must be set");
     HttpServer::new(move || {
         App::new()
             .data(pool.clone())
+            .app_data(route::json_config())
+            .app_data(route::path_config())
             .wrap(
                 CookieSession::signed(&cookie_key.as_bytes())
                       .secure(false)
             )
             .configure(route::task::configure)
             .configure(route::reward::configure)
+            .configure(route::goal::configure)
+            .configure(route::household::configure)
             .configure(route::user::configure)
+            .configure(route::ledger::configure)
+            .configure(route::session::configure)
+            .configure(route::calendar::configure)
             .service(fs::Files::new("/", "./site").index_file("index.html"))
     })
     .bind(api_url)?
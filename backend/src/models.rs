@@ -1,4 +1,4 @@
-use chrono::{NaiveDate};
+use chrono::{NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 use crate::schema::*;
 
@@ -10,6 +10,16 @@ pub struct QUser {
     pub password: Vec<u8>,
     pub salt: Vec<u8>,
     pub bspts: i32,
+    pub last_rollover: Option<NaiveDate>,
+    pub time_zone: String,
+    pub hash_version: i32,
+    pub hash_algorithm: String,
+    pub hash_iterations: i32,
+    pub hash_memory_kib: i32,
+    /// A hash of the secret token that lets calendar apps read the user's tasks
+    pub calendar_token_hash: Option<Vec<u8>>,
+    /// The household the user belongs to, if any
+    pub household_id: Option<i32>,
 }
 
 #[derive(Insertable)]
@@ -18,6 +28,10 @@ pub struct InsertableUser<'a> {
     pub uname: &'a str,
     pub password: Vec<u8>,
     pub salt: Vec<u8>,
+    pub hash_version: i32,
+    pub hash_algorithm: &'a str,
+    pub hash_iterations: i32,
+    pub hash_memory_kib: i32,
 }
 
 #[derive(Identifiable, Queryable, Associations, Deserialize, Serialize, Clone, Debug)]
@@ -26,6 +40,9 @@ pub struct InsertableUser<'a> {
 pub struct QSession {
     pub id: i32,
     pub user_id: i32,
+    pub created_at: NaiveDateTime,
+    pub last_seen: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
 }
 
 #[derive(Insertable)]
@@ -37,6 +54,7 @@ pub struct InsertableSession {
 #[derive(Identifiable, Queryable, Associations, AsChangeset, Deserialize, Serialize, Clone, Debug)]
 #[belongs_to(QUser, foreign_key = "user_id")]
 #[table_name="tasks"]
+#[changeset_options(treat_none_as_null = "true")]
 pub struct QTask {
     pub id: i32,
     pub name: String,
@@ -50,6 +68,17 @@ pub struct QTask {
     pub user_id: i32,
     pub icon: String,
     pub pts_lost: i32,
+    pub penalty_policy: String,
+    pub penalty_pts: i32,
+    pub misses: i32,
+    pub streak: i32,
+    pub best_streak: i32,
+    pub streak_bonus_policy: String,
+    pub streak_bonus_amount: i32,
+    /// When a one-off task was archived, none for tasks still in use
+    pub archived_at: Option<NaiveDateTime>,
+    /// The household the task is shared with, none if only its owner can see it
+    pub household_id: Option<i32>,
 }
 
 #[derive(Insertable)]
@@ -64,11 +93,17 @@ pub struct InsertableTask<'a> {
     pub time_unit: &'a str,
     pub by_when: i32,
     pub icon: String,
+    pub penalty_policy: &'a str,
+    pub penalty_pts: i32,
+    pub streak_bonus_policy: &'a str,
+    pub streak_bonus_amount: i32,
+    pub household_id: Option<i32>,
 }
 
 #[derive(Identifiable, Queryable, Associations, AsChangeset, Deserialize, Serialize, Clone, Debug)]
 #[belongs_to(QUser, foreign_key = "user_id")]
 #[table_name="rewards"]
+#[changeset_options(treat_none_as_null = "true")]
 pub struct QReward {
     pub id: i32,
     pub user_id: i32,
@@ -76,6 +111,16 @@ pub struct QReward {
     pub description: String,
     pub bspts: i32,
     pub icon: String,
+    /// When the reward was archived, none for rewards still in use
+    pub archived_at: Option<NaiveDateTime>,
+    pub cooldown_days: i32,
+    pub limit_period: String,
+    pub limit_times: i32,
+    pub allow_overdraft: bool,
+    pub price_increment: i32,
+    pub price_decay: i32,
+    /// The household the reward is shared with, none if only its owner can see it
+    pub household_id: Option<i32>,
 }
 
 #[derive(Insertable)]
@@ -86,4 +131,147 @@ pub struct InsertableReward<'a> {
     pub description: &'a str,
     pub bspts: i32,
     pub icon: String,
+    pub cooldown_days: i32,
+    pub limit_period: &'static str,
+    pub limit_times: i32,
+    pub allow_overdraft: bool,
+    pub price_increment: i32,
+    pub price_decay: i32,
+    pub household_id: Option<i32>,
+}
+
+#[derive(Identifiable, Queryable, Associations, Deserialize, Serialize, Clone, Debug)]
+#[belongs_to(QUser, foreign_key = "user_id")]
+#[table_name="points_ledger"]
+pub struct QLedgerEntry {
+    pub id: i32,
+    pub user_id: i32,
+    pub source_type: String,
+    pub source_id: Option<i32>,
+    pub amount: i32,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[table_name="points_ledger"]
+pub struct InsertableLedgerEntry {
+    pub user_id: i32,
+    pub source_type: String,
+    pub source_id: Option<i32>,
+    pub amount: i32,
+}
+
+#[derive(Identifiable, Queryable, Associations, Deserialize, Serialize, Clone, Debug)]
+#[belongs_to(QUser, foreign_key = "user_id")]
+#[table_name="rollovers"]
+pub struct QRollover {
+    pub id: i32,
+    pub user_id: i32,
+    pub rolled_over_on: NaiveDate,
+    pub tasks_reset: i32,
+    pub tasks_missed: i32,
+    pub pts_lost: i32,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[table_name="rollovers"]
+pub struct InsertableRollover {
+    pub user_id: i32,
+    pub rolled_over_on: NaiveDate,
+    pub tasks_reset: i32,
+    pub tasks_missed: i32,
+    pub pts_lost: i32,
+}
+
+#[derive(Identifiable, Queryable, Associations, Deserialize, Serialize, Clone, Debug)]
+#[belongs_to(QUser, foreign_key = "user_id")]
+#[belongs_to(QTask, foreign_key = "task_id")]
+#[table_name="task_completions"]
+pub struct QTaskCompletion {
+    pub id: i32,
+    pub task_id: i32,
+    pub user_id: i32,
+    pub completed_on: NaiveDate,
+    pub bspts: i32,
+    pub on_time: bool,
+    pub created_at: NaiveDateTime,
+    /// The run of misses that this completion ended
+    pub misses: i32,
+    /// The streak this completion reached
+    pub streak: i32,
+    /// The extra points earned for the streak
+    pub bonus: i32,
+}
+
+#[derive(Insertable)]
+#[table_name="task_completions"]
+pub struct InsertableTaskCompletion {
+    pub task_id: i32,
+    pub user_id: i32,
+    pub completed_on: NaiveDate,
+    pub bspts: i32,
+    pub on_time: bool,
+    pub misses: i32,
+    pub streak: i32,
+    pub bonus: i32,
+}
+
+#[derive(Identifiable, Queryable, Associations, Deserialize, Serialize, Clone, Debug)]
+#[belongs_to(QUser, foreign_key = "user_id")]
+#[belongs_to(QReward, foreign_key = "reward_id")]
+#[table_name="reward_redemptions"]
+pub struct QRewardRedemption {
+    pub id: i32,
+    pub reward_id: i32,
+    pub user_id: i32,
+    pub redeemed_on: NaiveDate,
+    pub bspts: i32,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[table_name="reward_redemptions"]
+pub struct InsertableRewardRedemption {
+    pub reward_id: i32,
+    pub user_id: i32,
+    pub redeemed_on: NaiveDate,
+    pub bspts: i32,
+}
+
+#[derive(Identifiable, Queryable, Associations, AsChangeset, Deserialize, Serialize, Clone, Debug)]
+#[belongs_to(QUser, foreign_key = "user_id")]
+#[belongs_to(QReward, foreign_key = "reward_id")]
+#[table_name="goals"]
+pub struct QGoal {
+    pub id: i32,
+    pub user_id: i32,
+    pub reward_id: i32,
+    /// The points set aside for the reward so far
+    pub saved: i32,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[table_name="goals"]
+pub struct InsertableGoal {
+    pub user_id: i32,
+    pub reward_id: i32,
+}
+
+#[derive(Identifiable, Queryable, Clone, Debug)]
+#[table_name="households"]
+pub struct QHousehold {
+    pub id: i32,
+    pub name: String,
+    /// Given to others so that they can join the household
+    pub invite_code: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[table_name="households"]
+pub struct InsertableHousehold<'a> {
+    pub name: &'a str,
+    pub invite_code: &'a str,
 }
\ No newline at end of file
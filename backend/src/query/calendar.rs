@@ -0,0 +1,126 @@
+use diesel::prelude::*;
+use chrono::{DateTime, Utc, Duration};
+use ring::digest;
+use rand_core::{RngCore, OsRng};
+use data::{task::*, rrule};
+use crate::PgPooledConnection;
+use crate::models::*;
+use crate::error::*;
+
+/// How many random bytes are in a calendar token
+const TOKEN_LEN: usize = 32;
+/// The longest a line of an iCalendar file can be, in bytes, before it must be folded
+const MAX_LINE_LEN: usize = 75;
+
+fn hash_token(token: &str) -> Vec<u8> {
+    digest::digest(&digest::SHA256, token.as_bytes()).as_ref().to_vec()
+}
+
+/// Makes a new calendar token for the user and returns it.
+/// Any token the user had before stops working
+pub fn new_calendar_token(q_user: &QUser, conn: &PgPooledConnection) -> Result<String> {
+    use crate::schema::users::dsl::*;
+
+    let mut token_bytes = [0u8; TOKEN_LEN];
+    OsRng.fill_bytes(&mut token_bytes);
+    let token: String = token_bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    diesel::update(users.find(q_user.id))
+        .set(calendar_token_hash.eq(Some(hash_token(&token))))
+        .execute(conn)
+        .map_err(|_| internal(format!("Error saving calendar token for user {}", q_user.id)))?;
+    Ok(token)
+}
+
+/// Stops the user's calendar token from working
+pub fn remove_calendar_token(q_user: &QUser, conn: &PgPooledConnection) -> Result<()> {
+    use crate::schema::users::dsl::*;
+
+    diesel::update(users.find(q_user.id))
+        .set(calendar_token_hash.eq(None::<Vec<u8>>))
+        .execute(conn)
+        .map(|_| ())
+        .map_err(|_| internal(format!("Error removing calendar token for user {}", q_user.id)))
+}
+
+/// Gets the user that the calendar token belongs to
+pub fn get_calendar_user(token: &str, conn: &PgPooledConnection) -> Result<QUser> {
+    use crate::schema::users::dsl::*;
+
+    users
+        .filter(calendar_token_hash.eq(hash_token(token)))
+        .first::<QUser>(conn)
+        .optional()
+        .map_err(|_| internal("Error getting the user for a calendar".to_string()))?
+        .ok_or_else(|| not_found("There's no calendar with that token".to_string()))
+}
+
+/// Gets the RRULE for the task's frequency, if a calendar app would repeat it on the same days.
+/// Calendar apps skip months that don't have the 29th or 30th, and years without Feb 29th,
+/// where tasks fall back to the end of the month instead
+fn calendar_rrule(frequency: &TaskInterval) -> Option<String> {
+    match frequency {
+        TaskInterval::Months{day_of_month, ..} if (29..LAST_DAY_OF_MONTH).contains(day_of_month) => None,
+        TaskInterval::Years{month: 2, day: 29, ..} => None,
+        TaskInterval::Once{..} => None,
+        _ => Some(rrule::to_rrule(frequency)),
+    }
+}
+
+/// Escapes text so that it can be the value of an iCalendar property
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Adds a line to the iCalendar file, folding it onto more lines if it's too long
+fn push_line(ics: &mut String, line: &str) {
+    let mut line_len = 0;
+    for c in line.chars() {
+        if line_len + c.len_utf8() > MAX_LINE_LEN {
+            // Folded lines start with a space, which counts towards their length
+            ics.push_str("\r\n ");
+            line_len = 1;
+        }
+        ics.push(c);
+        line_len += c.len_utf8();
+    }
+    ics.push_str("\r\n");
+}
+
+/// Writes the tasks as an iCalendar file, with an all day event on each task's next reset.
+/// Each event repeats the way the task does where calendar apps can do the same
+pub fn render_calendar(tasks: &[Task], now: DateTime<Utc>) -> String {
+    let mut ics = String::new();
+    let stamp = now.format("%Y%m%dT%H%M%SZ").to_string();
+    push_line(&mut ics, "BEGIN:VCALENDAR");
+    push_line(&mut ics, "VERSION:2.0");
+    push_line(&mut ics, "PRODID:-//bspts//Tasks//EN");
+    push_line(&mut ics, "CALSCALE:GREGORIAN");
+    push_line(&mut ics, "X-WR-CALNAME:bspts tasks");
+    for task in tasks {
+        let points = format!("Worth {} bs points", task.bspts);
+        let description = if task.description.is_empty() {
+            points
+        } else {
+            format!("{}\n\n{}", task.description, points)
+        };
+        let day_after = task.next_reset + Duration::days(1);
+        push_line(&mut ics, "BEGIN:VEVENT");
+        push_line(&mut ics, &format!("UID:task-{}@bspts", task.id));
+        push_line(&mut ics, &format!("DTSTAMP:{}", stamp));
+        push_line(&mut ics, &format!("DTSTART;VALUE=DATE:{}", task.next_reset.format("%Y%m%d")));
+        push_line(&mut ics, &format!("DTEND;VALUE=DATE:{}", day_after.format("%Y%m%d")));
+        if let Some(rule) = calendar_rrule(&task.frequency) {
+            push_line(&mut ics, &format!("RRULE:{}", rule));
+        }
+        push_line(&mut ics, &format!("SUMMARY:{}", escape_text(&format!("{} ({} pts)", task.name, task.bspts))));
+        push_line(&mut ics, &format!("DESCRIPTION:{}", escape_text(&description)));
+        push_line(&mut ics, "TRANSP:TRANSPARENT");
+        push_line(&mut ics, "END:VEVENT");
+    }
+    push_line(&mut ics, "END:VCALENDAR");
+    ics
+}
\ No newline at end of file
@@ -0,0 +1,224 @@
+use diesel::prelude::*;
+use data::{goal::*, reward::Reward, ledger::LedgerSource, error::FieldError};
+use chrono::{NaiveDate, Utc, Duration};
+use std::collections::HashMap;
+use crate::PgPooledConnection;
+use crate::models::*;
+use crate::error::*;
+use crate::query::{atomically, user, ledger, reward};
+
+/// How many days of earnings the rate used to project goals is averaged over
+pub const EARNING_RATE_DAYS: i64 = 14;
+
+/// The points the user has earned a day on average over the last EARNING_RATE_DAYS
+fn daily_earning_rate(user: &QUser, conn: &PgPooledConnection) -> Result<f64> {
+    let since = Utc::now().naive_utc() - Duration::days(EARNING_RATE_DAYS);
+    let earned = ledger::earned_since(user.id, since, conn)?;
+    Ok(earned as f64 / EARNING_RATE_DAYS as f64)
+}
+
+/// The day a goal will be reached if the user keeps earning at the same rate
+fn project(remaining: i32, daily_rate: f64, today: NaiveDate) -> Option<NaiveDate> {
+    if remaining <= 0 {
+        return Some(today);
+    }
+    if daily_rate <= 0.0 {
+        return None;
+    }
+    let days = (remaining as f64 / daily_rate).ceil() as i64;
+    today.checked_add_signed(Duration::days(days))
+}
+
+fn q_goal_to_goal(q: &QGoal, reward: &Reward, daily_rate: f64, today: NaiveDate) -> Goal {
+    let target = reward.current_price;
+    Goal {
+        id: q.id,
+        reward_id: q.reward_id,
+        reward_name: reward.name.clone(),
+        saved: q.saved,
+        target,
+        daily_rate,
+        projected_on: project(target - q.saved, daily_rate, today),
+        created_at: q.created_at,
+    }
+}
+
+/// Gets the goal with the given id, so long as it belongs to the user.
+/// Goals belonging to other users are reported as not found
+fn get_q_goal(goal_id: i32, user: &QUser, conn: &PgPooledConnection) -> Result<QGoal> {
+    use crate::schema::goals::dsl::*;
+
+    QGoal::belonging_to(user)
+        .filter(id.eq(goal_id))
+        .for_update()
+        .first::<QGoal>(conn)
+        .optional()
+        .map_err(|_| internal(format!("Error querying for goal {}", goal_id)))?
+        .ok_or_else(|| not_found(format!("No goal with id {}", goal_id)))
+}
+
+/// Gets the user's goal saving for the reward, if there is one
+fn get_q_goal_for_reward(for_reward_id: i32, user: &QUser, conn: &PgPooledConnection) -> Result<Option<QGoal>> {
+    use crate::schema::goals::dsl::*;
+
+    QGoal::belonging_to(user)
+        .filter(reward_id.eq(for_reward_id))
+        .for_update()
+        .first::<QGoal>(conn)
+        .optional()
+        .map_err(|_| internal(format!("Error querying for the goal of reward {}", for_reward_id)))
+}
+
+/// Whether the user is saving for the reward
+pub fn is_a_goal(for_reward_id: i32, user: &QUser, conn: &PgPooledConnection) -> Result<bool> {
+    use crate::schema::goals::dsl::*;
+    use diesel::dsl::exists;
+
+    diesel::select(exists(QGoal::belonging_to(user).filter(reward_id.eq(for_reward_id))))
+        .get_result(conn)
+        .map_err(|_| internal(format!("Error querying for the goals of reward {}", for_reward_id)))
+}
+
+/// Fills in the reward's name and price and the projection for a goal
+fn to_goal(q_goal: &QGoal, user: &QUser, conn: &PgPooledConnection, today: NaiveDate) -> Result<Goal> {
+    let reward = reward::get_reward(q_goal.reward_id, user, conn, today)?;
+    let daily_rate = daily_earning_rate(user, conn)?;
+    Ok(q_goal_to_goal(q_goal, &reward, daily_rate, today))
+}
+
+/// Gets all of the user's goals, oldest first
+pub fn get_goals(user: QUser, conn: &PgPooledConnection, today: NaiveDate) -> Result<Vec<Goal>> {
+    use crate::schema::goals::dsl::*;
+
+    let q_goals = QGoal::belonging_to(&user)
+        .order(id.asc())
+        .load::<QGoal>(conn)
+        .map_err(|_| internal(format!("Error loading goals for user {}", user.id)))?;
+    let daily_rate = daily_earning_rate(&user, conn)?;
+    let rewards: HashMap<i32, Reward> = reward::get_rewards(user, conn, today)?
+        .into_iter()
+        .map(|reward| (reward.id, reward))
+        .collect();
+    q_goals.iter().map(|q_goal| {
+        let reward = rewards.get(&q_goal.reward_id)
+            .ok_or_else(|| internal(format!("Goal {} is for a missing reward {}", q_goal.id, q_goal.reward_id)))?;
+        Ok(q_goal_to_goal(q_goal, reward, daily_rate, today))
+    }).collect()
+}
+
+/// Pins the reward as a goal to save points for
+pub fn pin_goal(new_goal: NewGoal, user: &QUser, conn: &PgPooledConnection, today: NaiveDate) -> Result<Goal> {
+    use crate::schema::goals;
+
+    let reward = reward::get_reward(new_goal.reward_id, user, conn, today)?;
+    if reward.archived_at.is_some() {
+        return Err(bad_request(format!("Reward {} is archived", reward.id)));
+    }
+    if get_q_goal_for_reward(reward.id, user, conn)?.is_some() {
+        return Err(conflict(format!("{} is already a goal", reward.name)));
+    }
+    let committed_goal: QGoal = diesel::insert_into(goals::table)
+        .values(InsertableGoal {
+            user_id: user.id,
+            reward_id: reward.id,
+        })
+        .get_result(conn)
+        .map_err(|_| internal(format!("Error saving a goal for reward {}", reward.id)))?;
+    let daily_rate = daily_earning_rate(user, conn)?;
+    Ok(q_goal_to_goal(&committed_goal, &reward, daily_rate, today))
+}
+
+/// Moves points out of the user's total and into the goal
+pub fn deposit(goal_id: i32, pts: i32, user: &QUser, conn: &PgPooledConnection, today: NaiveDate) -> Result<Goal> {
+    use crate::schema::{goals, users};
+
+    atomically(conn, || {
+        let balance: i32 = users::table.find(user.id)
+            .select(users::bspts)
+            .for_update()
+            .first(conn)
+            .map_err(|_| internal(format!("Could not lock user {}", user.id)))?;
+        let mut q_goal = get_q_goal(goal_id, user, conn)?;
+        let goal = to_goal(&q_goal, user, conn, today)?;
+        if pts > goal.remaining() {
+            return Err(validation(
+                "That's more than the goal needs".to_string(),
+                vec![FieldError::new("pts", &format!("Only {} more pts are needed", goal.remaining()))],
+            ));
+        }
+        if pts > balance {
+            return Err(insufficient_points(format!("You only have {} pts to set aside", balance)));
+        }
+
+        user::update_bspts(user.id, -pts, LedgerSource::GoalDeposit, Some(q_goal.id), conn)?;
+        q_goal = diesel::update(goals::table.find(q_goal.id))
+            .set(goals::saved.eq(goals::saved + pts))
+            .get_result(conn)
+            .map_err(|_| internal(format!("Could not save points for goal {}", goal_id)))?;
+        to_goal(&q_goal, user, conn, today)
+    })
+}
+
+/// Gives the points saved for a goal back to the user and removes the goal.
+/// Returns the user's new total
+fn release(q_goal: &QGoal, conn: &PgPooledConnection) -> Result<i32> {
+    use crate::schema::goals::dsl::goals;
+
+    atomically(conn, || {
+        let new_total = user::update_bspts(q_goal.user_id, q_goal.saved, LedgerSource::GoalRelease, Some(q_goal.id), conn)?;
+        diesel::delete(goals.find(q_goal.id))
+            .execute(conn)
+            .map_err(|_| internal(format!("Could not remove goal {}", q_goal.id)))?;
+        Ok(new_total)
+    })
+}
+
+/// Cancels the goal, giving its points back to the user.
+/// Returns the user's new total
+pub fn cancel_goal(goal_id: i32, user: &QUser, conn: &PgPooledConnection) -> Result<i32> {
+    atomically(conn, || {
+        let q_goal = get_q_goal(goal_id, user, conn)?;
+        release(&q_goal, conn)
+    })
+}
+
+/// Gives the points saved for the reward back to the user so they can be spent on it, if it's their goal.
+/// Returns the user's new total, or none if the reward wasn't a goal
+pub fn release_goal_for_reward(for_reward_id: i32, user: &QUser, conn: &PgPooledConnection) -> Result<Option<i32>> {
+    match get_q_goal_for_reward(for_reward_id, user, conn)? {
+        Some(q_goal) => release(&q_goal, conn).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Cancels every goal for the rewards that the given user isn't saving for,
+/// giving the points back to whoever saved them.
+/// Used when rewards stop being shared with the other members of a household or are archived
+pub fn release_goals_of_others(reward_ids: &[i32], owner_id: i32, conn: &PgPooledConnection) -> Result<()> {
+    use crate::schema::goals::dsl::*;
+
+    let q_goals = goals
+        .filter(reward_id.eq_any(reward_ids))
+        .filter(user_id.ne(owner_id))
+        .for_update()
+        .load::<QGoal>(conn)
+        .map_err(|_| internal("Error loading the goals of shared rewards".to_string()))?;
+    atomically(conn, || {
+        q_goals.iter().try_for_each(|q_goal| release(q_goal, conn).map(|_| ()))
+    })
+}
+
+/// Cancels the user's goals for the given rewards, giving the points back to them.
+/// Used when the user can no longer see the rewards, such as after leaving a household
+pub fn release_goals_for_rewards(reward_ids: &[i32], user: &QUser, conn: &PgPooledConnection) -> Result<()> {
+    use crate::schema::goals::dsl::*;
+
+    let q_goals = QGoal::belonging_to(user)
+        .filter(reward_id.eq_any(reward_ids))
+        .for_update()
+        .load::<QGoal>(conn)
+        .map_err(|_| internal(format!("Error loading the goals of user {}", user.id)))?;
+    atomically(conn, || {
+        q_goals.iter().try_for_each(|q_goal| release(q_goal, conn).map(|_| ()))
+    })
+}
\ No newline at end of file
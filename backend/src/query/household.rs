@@ -0,0 +1,171 @@
+use diesel::prelude::*;
+use rand_core::{RngCore, OsRng};
+use data::{household::*, error::FieldError};
+use crate::PgPooledConnection;
+use crate::models::*;
+use crate::error::*;
+use crate::query::{atomically, goal};
+
+/// How many characters are in an invite code
+const INVITE_CODE_LEN: usize = 8;
+/// The characters an invite code is made of, leaving out ones that are easily mixed up like 0 and O
+const INVITE_CODE_CHARS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+fn new_invite_code() -> String {
+    let mut code_bytes = [0u8; INVITE_CODE_LEN];
+    OsRng.fill_bytes(&mut code_bytes);
+    code_bytes.iter()
+        .map(|byte| INVITE_CODE_CHARS[*byte as usize % INVITE_CODE_CHARS.len()] as char)
+        .collect()
+}
+
+/// Gets the household a new task or reward is shared with.
+/// Returns a validation error if it should be shared but the user isn't in a household
+pub fn household_to_share_with(shared: bool, user: &QUser) -> Result<Option<i32>> {
+    if !shared {
+        return Ok(None);
+    }
+    match user.household_id {
+        Some(id) => Ok(Some(id)),
+        None => Err(validation(
+            "You aren't in a household".to_string(),
+            vec![FieldError::new("shared", "Join a household to share with it")],
+        )),
+    }
+}
+
+fn to_household(q_household: QHousehold, conn: &PgPooledConnection) -> Result<Household> {
+    use crate::schema::users::dsl::*;
+
+    let members = users
+        .filter(household_id.eq(q_household.id))
+        .select((uname, bspts))
+        .order(uname.asc())
+        .load::<(String, i32)>(conn)
+        .map_err(|_| internal(format!("Error loading the members of household {}", q_household.id)))?
+        .into_iter()
+        .map(|(member_uname, member_bspts)| HouseholdMember {
+            uname: member_uname,
+            bspts: member_bspts,
+        })
+        .collect();
+    Ok(Household {
+        id: q_household.id,
+        name: q_household.name,
+        invite_code: q_household.invite_code,
+        members,
+    })
+}
+
+/// Gets the household the user is in, if any
+pub fn get_household(user: &QUser, conn: &PgPooledConnection) -> Result<Option<Household>> {
+    use crate::schema::households::dsl::households;
+
+    let for_household_id = match user.household_id {
+        Some(for_household_id) => for_household_id,
+        None => return Ok(None),
+    };
+    let q_household = households.find(for_household_id)
+        .first::<QHousehold>(conn)
+        .map_err(|_| internal(format!("Error querying for household {}", for_household_id)))?;
+    to_household(q_household, conn).map(Some)
+}
+
+/// Puts the user in the household
+fn set_household(user: &QUser, for_household_id: Option<i32>, conn: &PgPooledConnection) -> Result<()> {
+    use crate::schema::users::dsl::*;
+
+    diesel::update(users.find(user.id))
+        .set(household_id.eq(for_household_id))
+        .execute(conn)
+        .map(|_| ())
+        .map_err(|_| internal(format!("Could not change the household of user {}", user.id)))
+}
+
+/// Starts a new household with the user as its only member
+pub fn create_household(new_household: NewHousehold, user: &QUser, conn: &PgPooledConnection) -> Result<Household> {
+    use crate::schema::households;
+
+    if user.household_id.is_some() {
+        return Err(conflict("Leave your household before starting a new one".to_string()));
+    }
+    atomically(conn, || {
+        let invite_code = new_invite_code();
+        let q_household: QHousehold = diesel::insert_into(households::table)
+            .values(InsertableHousehold {
+                name: &new_household.name,
+                invite_code: &invite_code,
+            })
+            .get_result(conn)
+            .map_err(|_| internal(format!("Error saving a household for user {}", user.id)))?;
+        set_household(user, Some(q_household.id), conn)?;
+        to_household(q_household, conn)
+    })
+}
+
+/// Adds the user to the household with the invite code, which isn't case sensitive
+pub fn join_household(join: JoinHousehold, user: &QUser, conn: &PgPooledConnection) -> Result<Household> {
+    use crate::schema::households::dsl::*;
+
+    if user.household_id.is_some() {
+        return Err(conflict("Leave your household before joining another one".to_string()));
+    }
+    let code = join.invite_code.trim().to_uppercase();
+    let q_household = households
+        .filter(invite_code.eq(&code))
+        .first::<QHousehold>(conn)
+        .optional()
+        .map_err(|_| internal("Error querying for a household by invite code".to_string()))?
+        .ok_or_else(|| not_found(format!("No household has the invite code {}", code)))?;
+    set_household(user, Some(q_household.id), conn)?;
+    to_household(q_household, conn)
+}
+
+/// Takes the user out of their household. The tasks and rewards they shared stop being shared,
+/// and any points saved for rewards that the user or the other members can no longer see are given back.
+/// The household is removed once its last member leaves
+pub fn leave_household(user: &QUser, conn: &PgPooledConnection) -> Result<()> {
+    use crate::schema::{households, rewards, tasks, users};
+
+    let left_household_id = user.household_id
+        .ok_or_else(|| bad_request(format!("User {} isn't in a household", user.id)))?;
+    atomically(conn, || {
+        let shared_rewards: Vec<(i32, i32)> = rewards::table
+            .filter(rewards::household_id.eq(left_household_id))
+            .select((rewards::id, rewards::user_id))
+            .load(conn)
+            .map_err(|_| internal(format!("Error loading the rewards of household {}", left_household_id)))?;
+        let reward_ids_where = |owned: bool| -> Vec<i32> {
+            shared_rewards.iter()
+                .filter(|(_, owner_id)| (*owner_id == user.id) == owned)
+                .map(|(id, _)| *id)
+                .collect()
+        };
+        let own_reward_ids = reward_ids_where(true);
+        let others_reward_ids = reward_ids_where(false);
+        goal::release_goals_for_rewards(&others_reward_ids, user, conn)?;
+        goal::release_goals_of_others(&own_reward_ids, user.id, conn)?;
+
+        diesel::update(tasks::table.filter(tasks::user_id.eq(user.id)))
+            .set(tasks::household_id.eq(None::<i32>))
+            .execute(conn)
+            .map_err(|_| internal(format!("Could not stop sharing the tasks of user {}", user.id)))?;
+        diesel::update(rewards::table.filter(rewards::user_id.eq(user.id)))
+            .set(rewards::household_id.eq(None::<i32>))
+            .execute(conn)
+            .map_err(|_| internal(format!("Could not stop sharing the rewards of user {}", user.id)))?;
+        set_household(user, None, conn)?;
+
+        let members_left: i64 = users::table
+            .filter(users::household_id.eq(left_household_id))
+            .count()
+            .get_result(conn)
+            .map_err(|_| internal(format!("Could not count the members of household {}", left_household_id)))?;
+        if members_left == 0 {
+            diesel::delete(households::table.find(left_household_id))
+                .execute(conn)
+                .map_err(|_| internal(format!("Could not remove household {}", left_household_id)))?;
+        }
+        Ok(())
+    })
+}
\ No newline at end of file
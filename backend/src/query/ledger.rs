@@ -0,0 +1,103 @@
+use diesel::prelude::*;
+use data::ledger::*;
+use crate::PgPooledConnection;
+use crate::models::*;
+use crate::error::*;
+use std::str::FromStr;
+use chrono::NaiveDateTime;
+
+fn q_entry_to_entry(q: &QLedgerEntry) -> Result<LedgerEntry> {
+    let source = LedgerSource::from_str(&q.source_type)
+        .map_err(|_| internal(format!("Unknown ledger source {} on entry {}", q.source_type, q.id)))?;
+    Ok(LedgerEntry {
+        id: q.id,
+        source,
+        source_id: q.source_id,
+        amount: q.amount,
+        created_at: q.created_at,
+    })
+}
+
+/// Adds an entry to the user's points ledger.
+/// This only records the change, use `user::update_bspts` to actually change the user's points
+pub fn record(
+    user_id: i32,
+    amount: i32,
+    source: LedgerSource,
+    source_id: Option<i32>,
+    conn: &PgPooledConnection,
+) -> Result<()> {
+    use crate::schema::points_ledger;
+
+    let entry = InsertableLedgerEntry {
+        user_id,
+        source_type: source.to_string(),
+        source_id,
+        amount,
+    };
+
+    diesel::insert_into(points_ledger::table)
+        .values(entry)
+        .execute(conn)
+        .map(|_| ())
+        .map_err(|_| internal(format!("Could not record {} pts for user {}", amount, user_id)))
+}
+
+/// The sum of every entry in the user's ledger
+pub fn balance(for_user_id: i32, conn: &PgPooledConnection) -> Result<i32> {
+    use crate::schema::points_ledger::dsl::*;
+
+    let total: Option<i64> = points_ledger
+        .filter(user_id.eq(for_user_id))
+        .select(diesel::dsl::sum(amount))
+        .first(conn)
+        .map_err(|_| internal(format!("Could not sum the ledger for user {}", for_user_id)))?;
+    Ok(total.unwrap_or(0) as i32)
+}
+
+/// Get a page of the user's ledger, newest entries first
+/// * page: The index of the page to get, starting at 0
+/// * per_page: How many entries are in each page
+pub fn get_ledger_page(user: &QUser, page: i64, per_page: i64, conn: &PgPooledConnection) -> Result<LedgerPage> {
+    use crate::schema::points_ledger::dsl::*;
+
+    let total = QLedgerEntry::belonging_to(user)
+        .count()
+        .get_result::<i64>(conn)
+        .map_err(|_| internal(format!("Could not count the ledger for user {}", user.id)))?;
+    let q_entries = QLedgerEntry::belonging_to(user)
+        .order((created_at.desc(), id.desc()))
+        .limit(per_page)
+        .offset(page * per_page)
+        .load::<QLedgerEntry>(conn)
+        .map_err(|_| internal(format!("Could not load the ledger for user {}", user.id)))?;
+    let entries = q_entries.iter().map(q_entry_to_entry).collect::<Result<Vec<LedgerEntry>>>()?;
+
+    Ok(LedgerPage {
+        entries,
+        page,
+        per_page,
+        total,
+    })
+}
+
+/// The points the user earned or lost from their tasks since the given moment.
+/// Rewards and goals only move points around so they aren't counted
+pub fn earned_since(for_user_id: i32, since: NaiveDateTime, conn: &PgPooledConnection) -> Result<i32> {
+    use crate::schema::points_ledger::dsl::*;
+
+    let task_sources: Vec<String> = [
+        LedgerSource::TaskCompletion,
+        LedgerSource::StreakBonus,
+        LedgerSource::Penalty,
+        LedgerSource::CompletionUndone,
+    ].iter().map(|source| source.to_string()).collect();
+    let total: Option<i64> = points_ledger
+        .filter(user_id.eq(for_user_id))
+        .filter(created_at.ge(since))
+        .filter(source_type.eq_any(task_sources))
+        .select(diesel::dsl::sum(amount))
+        .first(conn)
+        .map_err(|_| internal(format!("Could not sum the recent earnings of user {}", for_user_id)))?;
+    Ok(total.unwrap_or(0) as i32)
+}
\ No newline at end of file
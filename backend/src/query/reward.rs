@@ -1,9 +1,72 @@
 use diesel::prelude::*;
-use data::reward::*;
+use data::{reward::*, ledger::LedgerSource};
+use chrono::{NaiveDate, Utc, Duration};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use diesel::pg::Pg;
 use crate::PgPooledConnection;
 use crate::models::*;
+use crate::schema::rewards;
 use crate::error::*;
+use crate::query::{atomically, user, goal, household};
 
+pub const NO_LIMIT: &str = "None";
+pub const PER_DAY_LIMIT: &str = "PerDay";
+pub const PER_WEEK_LIMIT: &str = "PerWeek";
+
+fn get_limit_from_q_reward(q: &QReward) -> RedemptionLimit {
+    let times = q.limit_times.max(0) as u32;
+    match q.limit_period.as_str() {
+        PER_DAY_LIMIT => RedemptionLimit::PerDay{times},
+        PER_WEEK_LIMIT => RedemptionLimit::PerWeek{times},
+        _ => RedemptionLimit::None,
+    }
+}
+
+/// Splits a redemption limit into the columns it is stored in
+fn get_limit_columns(limit: &RedemptionLimit) -> (&'static str, i32) {
+    let period = match limit {
+        RedemptionLimit::None => NO_LIMIT,
+        RedemptionLimit::PerDay{..} => PER_DAY_LIMIT,
+        RedemptionLimit::PerWeek{..} => PER_WEEK_LIMIT,
+    };
+    (period, limit.times() as i32)
+}
+
+/// The first day a reward taken on the given day can be taken again
+fn cooldown_end(q: &QReward, last_redeemed: NaiveDate) -> NaiveDate {
+    last_redeemed + Duration::days(q.cooldown_days.max(0) as i64)
+}
+
+/// How many days back a redemption can still add to a reward's price, counting today
+fn price_window_days(price_increment: u32, price_decay: u32) -> i64 {
+    if price_increment == 0 {
+        return 0;
+    }
+    // An increment that would never wear off is treated as wearing off a point a day,
+    // and one saved before increments were limited counts as the largest allowed
+    let decay = price_decay.max(1);
+    i64::from(price_increment.min(MAX_PRICE_INCREMENT).div_ceil(decay))
+}
+
+/// The first day that's too long ago for a redemption on it to still add to the price.
+/// Falls back to the earliest date there is for a window that reaches back further than that
+fn price_window_start(today: NaiveDate, window_days: i64) -> NaiveDate {
+    today.checked_sub_signed(Duration::days(window_days)).unwrap_or(NaiveDate::MIN)
+}
+
+/// The price of a reward on the given day. Each time the reward was taken adds price_increment to its
+/// base price, which then goes back down by price_decay each day after
+fn price_on(base: i32, price_increment: u32, price_decay: u32, today: NaiveDate, taken_on: &[NaiveDate]) -> i32 {
+    let added: i64 = taken_on.iter()
+        .filter(|day| **day <= today)
+        .map(|day| {
+            let days_since = (today - *day).num_days();
+            i64::from(price_increment).saturating_sub(i64::from(price_decay).saturating_mul(days_since)).max(0)
+        })
+        .fold(0, i64::saturating_add);
+    i32::try_from(i64::from(base).saturating_add(added)).unwrap_or(i32::MAX)
+}
 
 fn q_reward_to_reward(q: &QReward) -> Reward {
     Reward {
@@ -13,25 +76,108 @@ fn q_reward_to_reward(q: &QReward) -> Reward {
         user_id: q.user_id,
         bspts: q.bspts,
         icon: q.icon.clone().into(),
+        cooldown_days: q.cooldown_days.max(0) as u32,
+        limit: get_limit_from_q_reward(q),
+        allow_overdraft: q.allow_overdraft,
+        price_increment: q.price_increment.max(0) as u32,
+        price_decay: q.price_decay.max(0) as u32,
+        archived_at: q.archived_at,
+        household_id: q.household_id,
+        last_redeemed: None,
+        redeemed_today: 0,
+        available_on: None,
+        current_price: q.bspts,
+    }
+}
+
+/// The rewards the user can see, which are their own and those shared with their household
+fn visible_rewards<'a>(user: &QUser) -> rewards::BoxedQuery<'a, Pg> {
+    use crate::schema::rewards::dsl::*;
+
+    rewards
+        .filter(user_id.eq(user.id).or(household_id.eq(user.household_id)))
+        .into_boxed()
+}
+
+/// Fills in when each of the rewards was last taken, how many times it was taken today,
+/// when its cooldown ends and what it costs today.
+/// A shared reward counts the times any member of the household took it
+fn add_redemption_stats(mut rewards: Vec<Reward>, user: &QUser, today: NaiveDate, conn: &PgPooledConnection) -> Result<Vec<Reward>> {
+    use crate::schema::reward_redemptions::dsl::*;
+
+    let reward_ids: Vec<i32> = rewards.iter().map(|reward| reward.id).collect();
+    // The latest day each reward was taken on
+    let last_redeemed: HashMap<i32, NaiveDate> = reward_redemptions
+        .filter(reward_id.eq_any(&reward_ids))
+        .select((reward_id, redeemed_on))
+        .distinct_on(reward_id)
+        .order((reward_id, redeemed_on.desc()))
+        .load::<(i32, NaiveDate)>(conn)
+        .map_err(|_| internal(format!("Error loading when rewards were taken for user {}", user.id)))?
+        .into_iter()
+        .collect();
+    // Only the redemptions recent enough to add to a price are needed, which always includes today's
+    let window_days = rewards.iter()
+        .map(|reward| price_window_days(reward.price_increment, reward.price_decay))
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let recently_taken: Vec<(i32, NaiveDate)> = reward_redemptions
+        .filter(reward_id.eq_any(&reward_ids))
+        .filter(redeemed_on.gt(price_window_start(today, window_days)))
+        .filter(redeemed_on.le(today))
+        .select((reward_id, redeemed_on))
+        .load(conn)
+        .map_err(|_| internal(format!("Error loading the rewards recently taken by user {}", user.id)))?;
+    for reward in rewards.iter_mut() {
+        let taken_on: Vec<NaiveDate> = recently_taken.iter()
+            .filter(|(taken_id, _)| *taken_id == reward.id)
+            .map(|(_, day)| *day)
+            .collect();
+        reward.last_redeemed = last_redeemed.get(&reward.id).copied();
+        reward.redeemed_today = taken_on.iter().filter(|day| **day == today).count() as i64;
+        reward.current_price = price_on(reward.bspts, reward.price_increment, reward.price_decay, today, &taken_on);
+        reward.available_on = reward.last_redeemed
+            .map(|last| last + Duration::days(reward.cooldown_days as i64))
+            .filter(|available_on| *available_on > today);
     }
+    Ok(rewards)
 }
 
-/// Get all of the rewards for the user
+/// Get all of the rewards for the user, with when they were taken
 /// * user: The user to get the rewards for
-pub fn get_rewards(user: QUser, conn: &PgPooledConnection) -> Vec<Reward> {
-    let q_rewards = QReward::belonging_to(&user)
+pub fn get_rewards(user: QUser, conn: &PgPooledConnection, today: NaiveDate) -> Result<Vec<Reward>> {
+    use crate::schema::rewards::dsl::*;
+
+    let q_rewards = visible_rewards(&user)
+        .filter(archived_at.is_null())
         .load::<QReward>(conn)
-        .expect("Error loading rewards");
-        q_rewards.iter().map(q_reward_to_reward).collect()
+        .map_err(|_| internal(format!("Error loading rewards for user {}", user.id)))?;
+    add_redemption_stats(q_rewards.iter().map(q_reward_to_reward).collect(), &user, today, conn)
 }
 
-fn get_q_reward(reward_id: i32, conn: &PgPooledConnection) -> Result<QReward> {
+/// Get all of the user's archived rewards, most recently archived first
+/// * user: The user to get the rewards for
+pub fn get_archived_rewards(user: QUser, conn: &PgPooledConnection) -> Result<Vec<Reward>> {
     use crate::schema::rewards::dsl::*;
 
-    let mut q_rewards = rewards
+    let q_rewards = visible_rewards(&user)
+        .filter(archived_at.is_not_null())
+        .order((archived_at.desc(), id.desc()))
+        .load::<QReward>(conn)
+        .map_err(|_| internal(format!("Error loading archived rewards for user {}", user.id)))?;
+    Ok(q_rewards.iter().map(q_reward_to_reward).collect())
+}
+
+/// Gets the reward with the given id, so long as the user can see it.
+/// Rewards belonging to other users and not shared with the user's household are reported as not found
+fn get_q_reward(reward_id: i32, user: &QUser, conn: &PgPooledConnection) -> Result<QReward> {
+    use crate::schema::rewards::dsl::*;
+
+    let mut q_rewards = visible_rewards(user)
         .filter(id.eq(reward_id))
         .load::<QReward>(conn)
-        .map_err(|_| bad_request(format!("Error querying for reward {}", reward_id)))?;
+        .map_err(|_| internal(format!("Error querying for reward {}", reward_id)))?;
     // Should be a vec of only one item, return that item
     match q_rewards.pop() {
         Some(q_reward) => Ok(q_reward),
@@ -39,29 +185,47 @@ fn get_q_reward(reward_id: i32, conn: &PgPooledConnection) -> Result<QReward> {
     }
 }
 
-pub fn get_reward(reward_id: i32, conn: &PgPooledConnection) -> Result<Reward> {
-    let q_reward = get_q_reward(reward_id, conn)?;
-    Ok(q_reward_to_reward(&q_reward))
+/// Gets a reward that the user owns, so they can change it.
+/// Members of the household it's shared with can see and take it, but not change it
+fn get_owned_q_reward(reward_id: i32, user: &QUser, conn: &PgPooledConnection) -> Result<QReward> {
+    let q_reward = get_q_reward(reward_id, user, conn)?;
+    if q_reward.user_id != user.id {
+        return Err(forbidden(format!("Only the owner of reward {} can change it", reward_id)));
+    }
+    Ok(q_reward)
 }
 
-/// Add a new reward to the database
-pub fn commit_new_reward(new_reward: NewReward, user: QUser, conn: PgPooledConnection) -> Reward {
-    use crate::schema::rewards;
+pub fn get_reward(reward_id: i32, user: &QUser, conn: &PgPooledConnection, today: NaiveDate) -> Result<Reward> {
+    let q_reward = get_q_reward(reward_id, user, conn)?;
+    let mut rewards = add_redemption_stats(vec![q_reward_to_reward(&q_reward)], user, today, conn)?;
+    rewards.pop().ok_or_else(|| internal(format!("Lost reward {}", reward_id)))
+}
 
+/// Add a new reward to the database
+pub fn commit_new_reward(new_reward: NewReward, user: QUser, conn: PgPooledConnection) -> Result<Reward> {
+    let household_id = household::household_to_share_with(new_reward.shared, &user)?;
+    let (limit_period, limit_times) = get_limit_columns(&new_reward.limit);
     let insert_reward = InsertableReward {
         user_id: user.id,
         name: &new_reward.name,
         description: &new_reward.description,
         bspts: new_reward.bspts,
         icon: new_reward.icon.into(),
+        cooldown_days: new_reward.cooldown_days as i32,
+        limit_period,
+        limit_times,
+        allow_overdraft: new_reward.allow_overdraft,
+        price_increment: new_reward.price_increment as i32,
+        price_decay: new_reward.price_decay as i32,
+        household_id,
     };
     
     let committed_reward: QReward = diesel::insert_into(rewards::table)
         .values(insert_reward)
         .get_result(&conn)
-        .expect("Error saving new reward");
+        .map_err(|_| internal(format!("Error saving new reward for user {}", user.id)))?;
 
-    q_reward_to_reward(&committed_reward)
+    Ok(q_reward_to_reward(&committed_reward))
 }
 
 /// Updates the reward with reward_id to the value q_reward and returns the updated reward
@@ -71,28 +235,246 @@ fn update_q_reward(q_reward: &QReward, conn: &PgPooledConnection) -> Result<QRew
     diesel::update(rewards.find(q_reward.id))
         .set(q_reward)
         .get_result(conn)
-        .map_err(|_| bad_request(format!("Error updating for reward {}", q_reward.id)))
+        .map_err(|_| internal(format!("Error updating for reward {}", q_reward.id)))
 }
 
-/// Add a new reward to the database
-pub fn update_reward(reward_id: i32, new_reward: NewReward, conn: &PgPooledConnection) -> Result<Reward> {
-    let mut q_reward = get_q_reward(reward_id, &conn)?;
+/// Add a new reward to the database.
+/// Only the user who made a reward can change it. When it stops being shared,
+/// the points other members saved for it are given back to them
+pub fn update_reward(reward_id: i32, user: &QUser, new_reward: NewReward, conn: &PgPooledConnection, today: NaiveDate) -> Result<Reward> {
+    let mut q_reward = get_owned_q_reward(reward_id, user, &conn)?;
+    if new_reward.shared != q_reward.household_id.is_some() {
+        q_reward.household_id = household::household_to_share_with(new_reward.shared, user)?;
+    }
 
     q_reward.name = new_reward.name;
     q_reward.description = new_reward.description;
     q_reward.bspts = new_reward.bspts;
     q_reward.icon = new_reward.icon.into();
+    q_reward.cooldown_days = new_reward.cooldown_days as i32;
+    let (limit_period, limit_times) = get_limit_columns(&new_reward.limit);
+    q_reward.limit_period = limit_period.to_string();
+    q_reward.limit_times = limit_times;
+    q_reward.allow_overdraft = new_reward.allow_overdraft;
+    q_reward.price_increment = new_reward.price_increment as i32;
+    q_reward.price_decay = new_reward.price_decay as i32;
 
-    let committed_reward = update_q_reward(&q_reward, conn)?;
+    let committed_reward = atomically(conn, || {
+        if q_reward.household_id.is_none() {
+            goal::release_goals_of_others(&[q_reward.id], user.id, conn)?;
+        }
+        update_q_reward(&q_reward, conn)
+    })?;
 
-    Ok(q_reward_to_reward(&committed_reward))
+    let mut rewards = add_redemption_stats(vec![q_reward_to_reward(&committed_reward)], user, today, conn)?;
+    rewards.pop().ok_or_else(|| internal(format!("Lost reward {}", reward_id)))
+}
+
+/// Works out what the reward costs today from when it was recently taken
+fn current_price(q_reward: &QReward, today: NaiveDate, conn: &PgPooledConnection) -> Result<i32> {
+    use crate::schema::reward_redemptions::dsl::*;
+
+    let increment = u32::try_from(q_reward.price_increment).unwrap_or(0);
+    let decay = u32::try_from(q_reward.price_decay).unwrap_or(0);
+    let window_days = price_window_days(increment, decay);
+    if window_days == 0 {
+        return Ok(q_reward.bspts);
+    }
+    let taken_on: Vec<NaiveDate> = QRewardRedemption::belonging_to(q_reward)
+        .filter(redeemed_on.gt(price_window_start(today, window_days)))
+        .filter(redeemed_on.le(today))
+        .select(redeemed_on)
+        .load(conn)
+        .map_err(|_| internal(format!("Could not load when reward {} was recently taken", q_reward.id)))?;
+    Ok(price_on(q_reward.bspts, increment, decay, today, &taken_on))
 }
 
-pub fn delete_reward(reward_id: i32, conn: &PgPooledConnection) -> Result<()> {
+/// Checks that the reward's cooldown, limit and overdraft rules allow it to be taken today at the given price.
+/// Must be called inside the transaction that records the redemption, after the user's row is locked
+fn check_can_redeem(q_reward: &QReward, price: i32, balance: i32, today: NaiveDate, conn: &PgPooledConnection) -> Result<()> {
+    use crate::schema::reward_redemptions::dsl::*;
+
+    if q_reward.cooldown_days > 0 {
+        let last_redeemed = QRewardRedemption::belonging_to(q_reward)
+            .select(redeemed_on)
+            .order(redeemed_on.desc())
+            .first::<NaiveDate>(conn)
+            .optional()
+            .map_err(|_| internal(format!("Could not check when reward {} was last taken", q_reward.id)))?;
+        if let Some(last_redeemed) = last_redeemed {
+            let available_on = cooldown_end(q_reward, last_redeemed);
+            if available_on > today {
+                return Err(cooling_down(format!(
+                    "{} can't be taken again until {}", q_reward.name, available_on.format("%B %-d")
+                )));
+            }
+        }
+    }
+
+    let limit = get_limit_from_q_reward(q_reward);
+    if let Some(period_start) = limit.period_start(today) {
+        let times_taken: i64 = QRewardRedemption::belonging_to(q_reward)
+            .filter(redeemed_on.ge(period_start))
+            .filter(redeemed_on.le(today))
+            .count()
+            .get_result(conn)
+            .map_err(|_| internal(format!("Could not count the times reward {} was taken", q_reward.id)))?;
+        if times_taken >= limit.times() as i64 {
+            let period = match limit {
+                RedemptionLimit::PerWeek{..} => "week",
+                _ => "day",
+            };
+            return Err(limit_reached(format!(
+                "{} can only be taken {} times a {}", q_reward.name, limit.times(), period
+            )));
+        }
+    }
+
+    if !q_reward.allow_overdraft && balance < price {
+        return Err(insufficient_points(format!(
+            "{} costs {} pts but you only have {}", q_reward.name, price, balance
+        )));
+    }
+    Ok(())
+}
+
+/// Takes the reward, recording it and removing its current price from the user's points.
+/// If the reward is a goal, the points saved for it are given back to spend on it first.
+/// Fails if the reward's cooldown, limit or overdraft rules don't allow it.
+/// Returns the user's new total
+pub fn redeem_reward(reward_id: i32, user: &QUser, conn: &PgPooledConnection, today: NaiveDate) -> Result<i32> {
+    use crate::schema::{reward_redemptions, rewards, users};
+
+    atomically(conn, || {
+        // Lock the user and the reward so that rewards taken at the same time are checked one after another
+        let mut balance: i32 = users::table.find(user.id)
+            .select(users::bspts)
+            .for_update()
+            .first(conn)
+            .map_err(|_| internal(format!("Could not lock user {}", user.id)))?;
+        let q_reward: QReward = rewards::table.find(reward_id)
+            .filter(rewards::user_id.eq(user.id).or(rewards::household_id.eq(user.household_id)))
+            .for_update()
+            .first(conn)
+            .optional()
+            .map_err(|_| internal(format!("Error querying for reward {}", reward_id)))?
+            .ok_or_else(|| not_found(format!("No reward with id {}", reward_id)))?;
+        if q_reward.archived_at.is_some() {
+            return Err(bad_request(format!("Reward {} is archived", reward_id)));
+        }
+        if let Some(new_total) = goal::release_goal_for_reward(q_reward.id, user, conn)? {
+            balance = new_total;
+        }
+        let price = current_price(&q_reward, today, conn)?;
+        check_can_redeem(&q_reward, price, balance, today, conn)?;
+
+        let redemption = InsertableRewardRedemption {
+            reward_id: q_reward.id,
+            user_id: user.id,
+            redeemed_on: today,
+            bspts: price,
+        };
+        diesel::insert_into(reward_redemptions::table)
+            .values(redemption)
+            .execute(conn)
+            .map_err(|_| internal(format!("Could not record taking reward {}", reward_id)))?;
+        user::update_bspts(
+            user.id,
+            -price,
+            LedgerSource::RewardRedemption,
+            Some(q_reward.id),
+            conn,
+        )
+    })
+}
+
+fn q_redemption_to_redemption(reward_name: &str, q: &QRewardRedemption) -> RewardRedemption {
+    RewardRedemption {
+        id: q.id,
+        reward_id: q.reward_id,
+        reward_name: reward_name.to_string(),
+        redeemed_on: q.redeemed_on,
+        bspts: q.bspts,
+    }
+}
+
+/// Gets every time the reward was taken, newest first
+pub fn get_reward_history(for_reward_id: i32, user: &QUser, conn: &PgPooledConnection) -> Result<Vec<RewardRedemption>> {
+    use crate::schema::reward_redemptions::dsl::*;
+
+    let q_reward = get_q_reward(for_reward_id, user, conn)?;
+    let q_redemptions = QRewardRedemption::belonging_to(&q_reward)
+        .order((redeemed_on.desc(), id.desc()))
+        .load::<QRewardRedemption>(conn)
+        .map_err(|_| internal(format!("Could not load the history of reward {}", for_reward_id)))?;
+    Ok(q_redemptions.iter().map(|q| q_redemption_to_redemption(&q_reward.name, q)).collect())
+}
+
+/// Gets every reward the user took from one day to another, including both, newest first
+pub fn get_redemptions(user: &QUser, from: NaiveDate, to: NaiveDate, conn: &PgPooledConnection) -> Result<Vec<RewardRedemption>> {
+    use crate::schema::reward_redemptions::dsl::*;
+
+    let q_redemptions = QRewardRedemption::belonging_to(user)
+        .filter(redeemed_on.ge(from))
+        .filter(redeemed_on.le(to))
+        .order((redeemed_on.desc(), id.desc()))
+        .load::<QRewardRedemption>(conn)
+        .map_err(|_| internal(format!("Could not load the rewards taken by user {}", user.id)))?;
+    // Shared rewards the user took may belong to someone else
+    let reward_ids: Vec<i32> = q_redemptions.iter().map(|q| q.reward_id).collect();
+    let reward_names: HashMap<i32, String> = rewards::table
+        .filter(rewards::id.eq_any(reward_ids))
+        .load::<QReward>(conn)
+        .map_err(|_| internal(format!("Error loading rewards for user {}", user.id)))?
+        .into_iter()
+        .map(|q_reward| (q_reward.id, q_reward.name))
+        .collect();
+    q_redemptions.iter().map(|q| {
+        let reward_name = reward_names.get(&q.reward_id)
+            .ok_or_else(|| internal(format!("Redemption {} is for a missing reward {}", q.id, q.reward_id)))?;
+        Ok(q_redemption_to_redemption(reward_name, q))
+    }).collect()
+}
+
+/// Archives the reward so that it leaves the list of rewards, but keeps the ledger's references to it
+pub fn archive_reward(reward_id: i32, user: &QUser, conn: &PgPooledConnection) -> Result<()> {
+    let mut q_reward = get_owned_q_reward(reward_id, user, conn)?;
+    if q_reward.archived_at.is_some() {
+        return Err(bad_request(format!("Reward {} is already archived", reward_id)));
+    }
+    if goal::is_a_goal(q_reward.id, user, conn)? {
+        return Err(conflict(format!("Cancel the goal for {} before archiving it", q_reward.name)));
+    }
+    atomically(conn, || {
+        // Other members of the household get back what they saved for it
+        goal::release_goals_of_others(&[q_reward.id], user.id, conn)?;
+        q_reward.archived_at = Some(Utc::now().naive_utc());
+        update_q_reward(&q_reward, conn)?;
+        Ok(())
+    })
+}
+
+/// Brings an archived reward back to the list of rewards
+pub fn restore_reward(reward_id: i32, user: &QUser, conn: &PgPooledConnection) -> Result<Reward> {
+    let mut q_reward = get_owned_q_reward(reward_id, user, conn)?;
+    if q_reward.archived_at.is_none() {
+        return Err(bad_request(format!("Reward {} isn't archived", reward_id)));
+    }
+    q_reward.archived_at = None;
+    let restored_reward = update_q_reward(&q_reward, conn)?;
+    Ok(q_reward_to_reward(&restored_reward))
+}
+
+/// Deletes an archived reward for good
+pub fn purge_reward(reward_id: i32, user: &QUser, conn: &PgPooledConnection) -> Result<()> {
     use crate::schema::rewards::dsl::rewards;
 
-    match diesel::delete(rewards.find(reward_id)).execute(conn) {
+    let q_reward = get_owned_q_reward(reward_id, user, conn)?;
+    if q_reward.archived_at.is_none() {
+        return Err(bad_request(format!("Reward {} must be archived before it's deleted", reward_id)));
+    }
+    match diesel::delete(rewards.find(q_reward.id)).execute(conn) {
         Ok(_) => Ok(()),
-        Err(_) => Err(bad_request(format!("Could not delete reward {}", reward_id))),
+        Err(_) => Err(internal(format!("Could not delete reward {}", reward_id))),
     }
 }
\ No newline at end of file
@@ -0,0 +1,80 @@
+use diesel::prelude::*;
+use data::task::Task;
+use chrono::{DateTime, NaiveDate, Utc};
+use crate::PgPooledConnection;
+use crate::models::*;
+use crate::error::*;
+use crate::query::{atomically, task, user::local_date};
+
+/// Marks the user as rolled over on the given day, unless they were already
+/// rolled over on a later day
+fn set_last_rollover(user: &QUser, today: NaiveDate, conn: &PgPooledConnection) -> Result<()> {
+    use crate::schema::users::dsl::*;
+
+    diesel::update(users.find(user.id))
+        .filter(last_rollover.is_null().or(last_rollover.lt(today)))
+        .set(last_rollover.eq(today))
+        .execute(conn)
+        .map(|_| ())
+        .map_err(|_| internal(format!("Could not set the last rollover for user {}", user.id)))
+}
+
+/// Rolls the user's tasks over to the given day. Tasks that were missed are penalized
+/// and tasks that are done but due again are moved back to "todo".
+/// Returns the tasks that were moved back to "todo" along with a record of what was done,
+/// which is only kept if any task was reset or missed
+pub fn roll_over_tasks(user: &QUser, conn: &PgPooledConnection, today: NaiveDate) -> Result<(Vec<Task>, Option<QRollover>)> {
+    use crate::schema::rollovers;
+
+    atomically(conn, || {
+        let (missed_tasks, pts_lost) = task::apply_missed_task_penalties(user, conn, today)?;
+        let reset_tasks = task::move_tasks_to_todo_if_ready(user.clone(), conn, today)?;
+        set_last_rollover(user, today, conn)?;
+        if reset_tasks.is_empty() && missed_tasks.is_empty() {
+            return Ok((reset_tasks, None));
+        }
+
+        let rollover = InsertableRollover {
+            user_id: user.id,
+            rolled_over_on: today,
+            tasks_reset: reset_tasks.len() as i32,
+            tasks_missed: missed_tasks.len() as i32,
+            pts_lost,
+        };
+        let q_rollover: QRollover = diesel::insert_into(rollovers::table)
+            .values(rollover)
+            .get_result(conn)
+            .map_err(|_| internal(format!("Could not record the rollover for user {}", user.id)))?;
+        Ok((reset_tasks, Some(q_rollover)))
+    })
+}
+
+/// Rolls over the tasks of every user that hasn't been rolled over yet on
+/// their own local date at the given moment.
+/// A failure for one user is logged and doesn't stop the others from rolling over.
+/// Returns a record of each user's rollover
+pub fn roll_over_due_users(conn: &PgPooledConnection, now: DateTime<Utc>) -> Result<Vec<QRollover>> {
+    use crate::schema::users::dsl::*;
+
+    // No time zone is more than a day ahead of UTC, so anyone rolled over
+    // after the UTC date is already up to date
+    let utc_today = now.date_naive();
+    let q_users: Vec<QUser> = users
+        .filter(last_rollover.is_null().or(last_rollover.le(utc_today)))
+        .load(conn)
+        .map_err(|_| internal("Could not load the users due for rollover".to_string()))?;
+    let q_rollovers = q_users.iter().filter_map(|q_user| {
+        let today = local_date(q_user, now);
+        if q_user.last_rollover.is_some_and(|last| last >= today) {
+            return None;
+        }
+        match roll_over_tasks(q_user, conn, today) {
+            Ok((_, q_rollover)) => q_rollover,
+            Err(e) => {
+                println!("Could not roll over tasks for user {}: {}", q_user.id, e);
+                None
+            }
+        }
+    }).collect();
+    Ok(q_rollovers)
+}
\ No newline at end of file
@@ -1,11 +1,20 @@
 use crate::models::*;
 use crate::PgPooledConnection;
 use diesel::prelude::*;
+use diesel::dsl::{now, IntervalDsl};
+use data::session::SessionInfo;
 use crate::error::*;
 
-/// Creates a new session and returns its id
-pub fn start_session(q_user: &QUser, conn: &PgPooledConnection) -> QSession {
-    use crate::schema::sessions;
+/// How long a session lasts without being used before the user is logged out
+const SESSION_LIFETIME_DAYS: i32 = 30;
+
+/// Creates a new session and returns its id.
+/// Also clears out any of the user's sessions that have expired
+pub fn start_session(q_user: &QUser, conn: &PgPooledConnection) -> Result<QSession> {
+    use crate::schema::sessions::{self, dsl::*};
+
+    let _ = diesel::delete(QSession::belonging_to(q_user).filter(expires_at.le(now)))
+        .execute(conn);
 
     let insert_session = InsertableSession {
         user_id: q_user.id,
@@ -14,37 +23,98 @@ pub fn start_session(q_user: &QUser, conn: &PgPooledConnection) -> QSession {
     diesel::insert_into(sessions::table)
         .values(insert_session)
         .get_result(conn)
-        .expect("Error saving new post")
+        .map_err(|_| internal(format!("Error saving new session for user {}", q_user.id)))
 }
 
-fn get_session(session_id: i32, conn: &PgPooledConnection) -> Result<QSession> {
+/// Gets the session if it hasn't expired, and pushes back when it will expire
+fn touch_session(session_id: i32, conn: &PgPooledConnection) -> Result<QSession> {
     use crate::schema::sessions::dsl::*;
 
-    let q_sessions = sessions
-        .filter(id.eq(session_id))
-        .load::<QSession>(conn)
-        .expect("Error getting users from session id");
+    let q_sessions = diesel::update(sessions.filter(id.eq(session_id)).filter(expires_at.gt(now)))
+        .set((
+            last_seen.eq(now),
+            expires_at.eq(now + SESSION_LIFETIME_DAYS.days()),
+        ))
+        .get_results::<QSession>(conn)
+        .map_err(|_| internal(format!("Error getting session with id {}", session_id)))?;
 
     match &q_sessions[..] {
         [] => Err(unauthorized(format!("There's no session with id {}", session_id))),
         [q_ses] =>  Ok(q_ses.clone()),
-        _ => Err(conflict(format!("There was an error getting session with id {}", session_id))),
+        _ => Err(internal(format!("There was an error getting session with id {}", session_id))),
     }
 }
 
+/// Gets the user that the session belongs to. Using a session keeps it alive,
+/// while a session that has gone unused for too long is unauthorized
 pub fn get_session_user(session_id: i32, conn: &PgPooledConnection) -> Result<QUser> {
     use crate::schema::users::dsl::*;
 
-    let q_ses = get_session(session_id, &conn)?;
+    let q_ses = touch_session(session_id, &conn)?;
 
     let q_users: Vec<QUser> = users
         .filter(id.eq(q_ses.user_id))
         .load(conn)
-        .expect("Error getting users from session id");
+        .map_err(|_| internal(format!("Error getting user for session with id {}", session_id)))?;
 
     match &q_users[..] {
         [] => Err(not_found(format!("There's no user with id {}", q_ses.user_id))),
         [q_user] =>  Ok(q_user.clone()),
-        _ => Err(conflict(format!("There was an error getting user with id {}", q_ses.user_id))),
+        _ => Err(internal(format!("There was an error getting user with id {}", q_ses.user_id))),
+    }
+}
+
+/// Ends the session so it can no longer be used
+pub fn end_session(session_id: i32, conn: &PgPooledConnection) -> Result<()> {
+    use crate::schema::sessions::dsl::*;
+
+    diesel::delete(sessions.filter(id.eq(session_id)))
+        .execute(conn)
+        .map(|_| ())
+        .map_err(|_| internal(format!("Could not end session {}", session_id)))
+}
+
+/// Gets all of the user's sessions that haven't expired, most recently used first
+/// * current_session_id: The session making the request, which gets marked as current
+pub fn get_sessions(q_user: &QUser, current_session_id: i32, conn: &PgPooledConnection) -> Result<Vec<SessionInfo>> {
+    use crate::schema::sessions::dsl::*;
+
+    let q_sessions: Vec<QSession> = QSession::belonging_to(q_user)
+        .filter(expires_at.gt(now))
+        .order(last_seen.desc())
+        .load(conn)
+        .map_err(|_| internal(format!("Could not get the sessions for user {}", q_user.id)))?;
+    Ok(q_sessions.into_iter().map(|q_ses| SessionInfo {
+        id: q_ses.id,
+        created_at: q_ses.created_at,
+        last_seen: q_ses.last_seen,
+        expires_at: q_ses.expires_at,
+        current: q_ses.id == current_session_id,
+    }).collect())
+}
+
+/// Ends one of the user's sessions, e.g. to log out a lost device.
+/// Sessions that belong to other users are not found
+pub fn revoke_session(q_user: &QUser, session_id: i32, conn: &PgPooledConnection) -> Result<()> {
+    use crate::schema::sessions::dsl::*;
+
+    let deleted = diesel::delete(QSession::belonging_to(q_user).filter(id.eq(session_id)))
+        .execute(conn)
+        .map_err(|_| internal(format!("Could not end session {}", session_id)))?;
+    if deleted == 0 {
+        Err(not_found(format!("There's no session with id {}", session_id)))
+    } else {
+        Ok(())
     }
+}
+
+/// Ends all of the user's sessions except for the one given,
+/// e.g. to log out everywhere else after the password changes
+pub fn end_other_sessions(q_user: &QUser, keep_session_id: i32, conn: &PgPooledConnection) -> Result<()> {
+    use crate::schema::sessions::dsl::*;
+
+    diesel::delete(QSession::belonging_to(q_user).filter(id.ne(keep_session_id)))
+        .execute(conn)
+        .map(|_| ())
+        .map_err(|_| internal(format!("Could not end the other sessions for user {}", q_user.id)))
 }
\ No newline at end of file
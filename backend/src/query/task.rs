@@ -1,49 +1,52 @@
 use diesel::prelude::*;
-use data::task::*;
-use chrono::{NaiveDate, Duration, Datelike};
+use data::{task::*, ledger::LedgerSource, rrule, error::FieldError};
+use chrono::{NaiveDate, Utc};
+use std::collections::HashMap;
+use diesel::pg::Pg;
 use crate::PgPooledConnection;
 use crate::models::*;
+use crate::schema::tasks;
 use crate::error::*;
-use crate::query::{atomically, user};
+use crate::query::{atomically, user, household};
 
 pub const DAYS: &str = "Days";
 pub const WEEKS: &str = "Weeks";
 pub const MONTHS: &str = "Months";
+/// by_when holds the weekdays as bits, with Monday as the lowest bit
+pub const DAYS_OF_WEEK: &str = "DaysOfWeek";
+/// by_when holds the week * 7 + the weekday
+pub const MONTH_WEEKDAY: &str = "MonthWeekday";
+/// by_when holds the month * 100 + the day, so April 15th is 415
+pub const YEARS: &str = "Years";
+/// One-off tasks keep their due date in next_reset
+pub const ONCE: &str = "Once";
 
-/// adds the frequency to the current date to get the next date this will trigger
-fn calc_next_reset(frequency: &TaskInterval, today: NaiveDate) -> NaiveDate {
-    match frequency {
-        TaskInterval::Days{every} => {
-            let duration = Duration::days(*every as i64);
-            today.checked_add_signed(duration).unwrap()
-        },
-        TaskInterval::Weeks{every, weekday} => {
-            let current_day_of_week = today.weekday().num_days_from_monday();
-            let days_to_jump: u32 = if *weekday > current_day_of_week {
-                // The day this task must be done by is later this week
-                weekday - current_day_of_week
-            } else {
-                // This day is on the following week
-                (weekday + 7) - current_day_of_week
-            };
-            // Add on enough weeks to compensate for the every x weeks param
-            let days_to_jump_plus_weeks = if *every > 1 {
-                days_to_jump + (7 * every)
-            } else {
-                days_to_jump
-            };
-            let duration = Duration::days(days_to_jump_plus_weeks as i64);
-            today.checked_add_signed(duration).unwrap()
-        },
-        TaskInterval::Months{every, day_of_month} => {
-            let current_month = today.month0();
-            let new_month_no_mod = current_month + every;
-            // The month 0 indexed (requires 1 indexed for from_ymd fn)
-            let new_month0 = new_month_no_mod % 12;
-            let new_year = today.year() + (new_month_no_mod / 12) as i32;
-            NaiveDate::from_ymd(new_year, new_month0 + 1, *day_of_month)
-        }
-    }
+pub const NO_PENALTY: &str = "None";
+pub const FLAT_PENALTY: &str = "Flat";
+pub const LINEAR_PENALTY: &str = "Linear";
+pub const ESCALATING_PENALTY: &str = "Escalating";
+
+pub const NO_STREAK_BONUS: &str = "None";
+pub const FLAT_STREAK_BONUS: &str = "Flat";
+pub const MULTIPLIER_STREAK_BONUS: &str = "Multiplier";
+
+/// adds the frequency to the current date to get the next date this will trigger.
+/// Returns an error if there is no such date
+fn calc_next_reset(frequency: &TaskInterval, today: NaiveDate) -> Result<NaiveDate> {
+    frequency.next_reset_after(today).ok_or_else(|| {
+        bad_request(format!("The task can't be reset {:?} after {}", frequency, today))
+    })
+}
+
+/// Gets the first date a new task resets on.
+/// Returns a validation error if a one-off task is due before today
+fn calc_first_reset(frequency: &TaskInterval, today: NaiveDate) -> Result<NaiveDate> {
+    frequency.first_reset(today).ok_or_else(|| {
+        validation(
+            "The task's due date has passed".to_string(),
+            vec![FieldError::new("frequency.due", "The due date can't be in the past")],
+        )
+    })
 }
 
 fn get_days_to_next_reset(next_reset: NaiveDate, today: NaiveDate) -> i64 {
@@ -51,92 +54,288 @@ fn get_days_to_next_reset(next_reset: NaiveDate, today: NaiveDate) -> i64 {
     duration.num_days()
 }
 
-fn get_frequency_from_q_task(qt: &QTask) -> TaskInterval {
+fn get_frequency_from_q_task(qt: &QTask) -> Result<TaskInterval> {
     match qt.time_unit.as_str() {
         DAYS => {
-            TaskInterval::Days{every: qt.every as u32}
+            Ok(TaskInterval::Days{every: qt.every as u32})
         },
         WEEKS => {
-            TaskInterval::Weeks{every: qt.every as u32, weekday: qt.by_when as u32}
+            Ok(TaskInterval::Weeks{every: qt.every as u32, weekday: qt.by_when as u32})
         },
         MONTHS => {
-            TaskInterval::Months{every: qt.every as u32, day_of_month: qt.by_when as u32}
+            Ok(TaskInterval::Months{every: qt.every as u32, day_of_month: qt.by_when as u32})
+        },
+        DAYS_OF_WEEK => {
+            let weekdays = (0..7).filter(|weekday| qt.by_when & (1 << weekday) != 0).collect();
+            Ok(TaskInterval::DaysOfWeek{every: qt.every as u32, weekdays})
+        },
+        MONTH_WEEKDAY => {
+            Ok(TaskInterval::MonthWeekday{
+                every: qt.every as u32,
+                week: (qt.by_when / 7) as u32,
+                weekday: (qt.by_when % 7) as u32,
+            })
+        },
+        YEARS => {
+            Ok(TaskInterval::Years{
+                every: qt.every as u32,
+                month: (qt.by_when / 100) as u32,
+                day: (qt.by_when % 100) as u32,
+            })
+        },
+        ONCE => {
+            Ok(TaskInterval::Once{due: qt.next_reset})
         },
         _ => {
-            panic!(format!("Could not create a task interval for {}", qt.time_unit))
+            Err(internal(format!("Task {} has an unknown time unit {}", qt.id, qt.time_unit)))
         }
     }
 }
 
-fn query_task_to_task(today: NaiveDate) -> impl Fn(&QTask) -> Task {
+fn get_penalty_from_q_task(qt: &QTask) -> PenaltyPolicy {
+    let pts = qt.penalty_pts.max(0) as u32;
+    match qt.penalty_policy.as_str() {
+        FLAT_PENALTY => PenaltyPolicy::Flat{pts},
+        LINEAR_PENALTY => PenaltyPolicy::Linear{pts},
+        ESCALATING_PENALTY => PenaltyPolicy::Escalating{pts},
+        _ => PenaltyPolicy::None,
+    }
+}
+
+fn get_streak_bonus_from_q_task(qt: &QTask) -> StreakBonus {
+    let amount = qt.streak_bonus_amount.max(0) as u32;
+    match qt.streak_bonus_policy.as_str() {
+        FLAT_STREAK_BONUS => StreakBonus::Flat{pts: amount},
+        MULTIPLIER_STREAK_BONUS => StreakBonus::Multiplier{percent: amount},
+        _ => StreakBonus::None,
+    }
+}
+
+/// Gets the frequency of a new task, which may be given as an RRULE
+fn get_schedule(new_task: &NewTask) -> Result<TaskInterval> {
+    new_task.schedule().map_err(|e| {
+        validation("The task's rrule isn't valid".to_string(), vec![FieldError::new("rrule", &e.to_string())])
+    })
+}
+
+/// Splits a frequency into the time_unit, every and by_when columns it is stored in
+fn get_frequency_columns(frequency: &TaskInterval) -> (&'static str, i32, i32) {
+    match frequency {
+        TaskInterval::Days{every} => {
+            (DAYS, *every as i32, 0)
+        },
+        TaskInterval::Weeks{every, weekday} => {
+            (WEEKS, *every as i32, *weekday as i32)
+        },
+        TaskInterval::Months{every, day_of_month} => {
+            (MONTHS, *every as i32, *day_of_month as i32)
+        },
+        TaskInterval::DaysOfWeek{every, weekdays} => {
+            let weekday_bits = weekdays.iter().fold(0, |bits, weekday| bits | (1 << weekday));
+            (DAYS_OF_WEEK, *every as i32, weekday_bits)
+        },
+        TaskInterval::MonthWeekday{every, week, weekday} => {
+            (MONTH_WEEKDAY, *every as i32, (week * 7 + weekday) as i32)
+        },
+        TaskInterval::Years{every, month, day} => {
+            (YEARS, *every as i32, (month * 100 + day) as i32)
+        },
+        TaskInterval::Once{..} => {
+            (ONCE, 1, 0)
+        },
+    }
+}
+
+/// Splits a penalty policy into the columns it is stored in
+fn get_penalty_columns(penalty: &PenaltyPolicy) -> (&'static str, i32) {
+    let policy = match penalty {
+        PenaltyPolicy::None => NO_PENALTY,
+        PenaltyPolicy::Flat{pts:_} => FLAT_PENALTY,
+        PenaltyPolicy::Linear{pts:_} => LINEAR_PENALTY,
+        PenaltyPolicy::Escalating{pts:_} => ESCALATING_PENALTY,
+    };
+    (policy, penalty.pts() as i32)
+}
+
+/// Splits a streak bonus into the columns it is stored in
+fn get_streak_bonus_columns(streak_bonus: &StreakBonus) -> (&'static str, i32) {
+    let policy = match streak_bonus {
+        StreakBonus::None => NO_STREAK_BONUS,
+        StreakBonus::Flat{..} => FLAT_STREAK_BONUS,
+        StreakBonus::Multiplier{..} => MULTIPLIER_STREAK_BONUS,
+    };
+    (policy, streak_bonus.amount() as i32)
+}
+
+fn query_task_to_task(today: NaiveDate) -> impl Fn(&QTask) -> Result<Task> {
     move |qt: &QTask| {
-        Task {
+        let frequency = get_frequency_from_q_task(qt)?;
+        Ok(Task {
             id: qt.id,
             name: qt.name.clone(),
             description: qt.description.clone(),
             user_id: qt.user_id,
             bspts: qt.bspts,
             pts_lost: qt.pts_lost,
+            misses: qt.misses,
+            penalty: get_penalty_from_q_task(qt),
+            streak: qt.streak,
+            best_streak: qt.best_streak,
+            streak_bonus: get_streak_bonus_from_q_task(qt),
             is_done: qt.is_done,
             days_to_next_reset: get_days_to_next_reset(qt.next_reset, today),
             next_reset: qt.next_reset,
-            frequency: get_frequency_from_q_task(qt),
+            rrule: rrule::to_rrule(&frequency),
+            frequency,
             icon: qt.icon.clone().into(),
-        }
+            archived_at: qt.archived_at,
+            household_id: qt.household_id,
+        })
     }
 }
 
-fn get_q_tasks(user: QUser, done_tasks: bool, conn: &PgPooledConnection) -> Vec<QTask> {
+/// The tasks the user can see, which are their own and those shared with their household
+fn visible_tasks<'a>(user: &QUser) -> tasks::BoxedQuery<'a, Pg> {
+    use crate::schema::tasks::dsl::*;
+
+    tasks
+        .filter(user_id.eq(user.id).or(household_id.eq(user.household_id)))
+        .into_boxed()
+}
+
+fn get_q_tasks(user: QUser, done_tasks: bool, conn: &PgPooledConnection) -> Result<Vec<QTask>> {
     use crate::schema::tasks::dsl::*;
 
-    QTask::belonging_to(&user)
-        .filter(is_done.eq(done_tasks)).load(conn)
-        .expect("Error loading tasks")
+    visible_tasks(&user)
+        .filter(is_done.eq(done_tasks))
+        .filter(archived_at.is_null())
+        .load(conn)
+        .map_err(|_| internal(format!("Error loading tasks for user {}", user.id)))
 }
 
 /// Get all of the tasks for the user that are not yet complete
 /// * user: The user to get the tasks for
-pub fn get_todo_tasks(user: QUser, conn: &PgPooledConnection, today: NaiveDate) -> Vec<Task> {
-    let q_tasks = get_q_tasks(user, false, conn);
+pub fn get_todo_tasks(user: QUser, conn: &PgPooledConnection, today: NaiveDate) -> Result<Vec<Task>> {
+    let q_tasks = get_q_tasks(user, false, conn)?;
     q_tasks.iter().map(query_task_to_task(today)).collect()
 }
 
 /// Get all of the tasks for the user that are completed
 /// * user: The user to get the tasks for
-pub fn get_done_tasks(user: QUser, conn: &PgPooledConnection, today: NaiveDate) -> Vec<Task> {
-    let q_tasks = get_q_tasks(user, true, conn);
+pub fn get_done_tasks(user: QUser, conn: &PgPooledConnection, today: NaiveDate) -> Result<Vec<Task>> {
+    let q_tasks = get_q_tasks(user, true, conn)?;
+    q_tasks.iter().map(query_task_to_task(today)).collect()
+}
+
+/// Get all of the user's archived tasks, most recently archived first.
+/// These are the tasks that were deleted and the one-off tasks that were done
+/// * user: The user to get the tasks for
+pub fn get_archived_tasks(user: QUser, conn: &PgPooledConnection, today: NaiveDate) -> Result<Vec<Task>> {
+    use crate::schema::tasks::dsl::*;
+
+    let q_tasks: Vec<QTask> = visible_tasks(&user)
+        .filter(archived_at.is_not_null())
+        .order((archived_at.desc(), id.desc()))
+        .load(conn)
+        .map_err(|_| internal(format!("Error loading archived tasks for user {}", user.id)))?;
     q_tasks.iter().map(query_task_to_task(today)).collect()
 }
 
 /// Checks all of the user's "done" tasks and moves them back to 
 /// "todo" if it's their time. Returns the list of tasks that were
-/// moved to "todo" by this action
-pub fn move_tasks_to_todo_if_ready(user: QUser, conn: &PgPooledConnection, today: NaiveDate) -> Vec<Task>  {
-    let mut q_tasks = get_q_tasks(user, true, conn);
-    q_tasks.iter_mut().filter_map(|q_task| {
-        println!("compare {} to {}", q_task.next_reset, today);
+/// moved to "todo" by this action. A one-off task that's done never comes back
+pub fn move_tasks_to_todo_if_ready(user: QUser, conn: &PgPooledConnection, today: NaiveDate) -> Result<Vec<Task>> {
+    let q_tasks = get_q_tasks(user, true, conn)?;
+    let mut reset_tasks = vec![];
+    for mut q_task in q_tasks {
         if q_task.next_reset > today {
-            return None
+            continue;
         };
-        let frequency = get_frequency_from_q_task(&q_task);
-        let new_reset = calc_next_reset(&frequency, today);
-        q_task.next_reset = new_reset;
-        q_task.is_done = false;
-        match update_q_task(q_task, conn) {
-            Ok(updated_q_task) => Some(query_task_to_task(today)(&updated_q_task)),
-            _ => None,
+        let frequency = get_frequency_from_q_task(&q_task)?;
+        if !frequency.repeats() {
+            continue;
         }
-        
-    }).collect()
+        q_task.next_reset = calc_next_reset(&frequency, today)?;
+        q_task.is_done = false;
+        let updated_q_task = update_q_task(&q_task, conn)?;
+        reset_tasks.push(query_task_to_task(today)(&updated_q_task)?);
+    }
+    Ok(reset_tasks)
+}
+
+/// Checks all of the user's "todo" tasks and applies the penalty for any that
+/// were not completed before their reset date. The task then moves on to its next
+/// reset date, and the points lost are taken from the user.
+/// One-off tasks stay on their due date and are only penalized once.
+/// Returns the list of tasks that were penalized by this action
+/// and the total number of points lost.
+/// A task shared with a household is only ever penalized for the user who made it
+pub fn apply_missed_task_penalties(user: &QUser, conn: &PgPooledConnection, today: NaiveDate) -> Result<(Vec<Task>, i32)> {
+    use crate::schema::tasks::dsl::*;
+
+    atomically(conn, || {
+        // Lock the missed tasks so that a rollover running at the same time waits, then
+        // finds them already moved on rather than penalizing the same miss again
+        let q_tasks: Vec<QTask> = QTask::belonging_to(user)
+            .filter(is_done.eq(false))
+            .filter(next_reset.lt(today))
+            .filter(archived_at.is_null())
+            // One-off tasks that have already been missed stay past-due
+            .filter(time_unit.ne(ONCE).or(misses.eq(0)))
+            .for_update()
+            .load(conn)
+            .map_err(|_| internal(format!("Error loading missed tasks for user {}", user.id)))?;
+        let mut total_lost: i32 = 0;
+        let missed_tasks = q_tasks.into_iter().map(|mut q_task| {
+            let frequency = get_frequency_from_q_task(&q_task)?;
+            let penalty = get_penalty_from_q_task(&q_task);
+            let mut lost: i32 = 0;
+            if !frequency.repeats() {
+                q_task.misses = 1;
+                q_task.streak = 0;
+                lost = penalty.penalty_for_miss(1);
+            }
+            // Count a miss for every reset date that has passed
+            while frequency.repeats() && q_task.next_reset < today {
+                q_task.misses += 1;
+                // A period passed without the task being done, so the streak is broken
+                q_task.streak = 0;
+                lost = lost.saturating_add(penalty.penalty_for_miss(q_task.misses as u32));
+                let new_reset = calc_next_reset(&frequency, q_task.next_reset)?;
+                q_task.next_reset = if new_reset > q_task.next_reset {
+                    new_reset
+                } else {
+                    // The frequency doesn't move the date forward, so skip straight to today
+                    today
+                };
+            }
+            q_task.pts_lost = q_task.pts_lost.saturating_add(lost);
+            total_lost = total_lost.saturating_add(lost);
+            let updated_q_task = update_q_task(&q_task, conn)?;
+            if lost > 0 {
+                user::update_bspts(
+                    user.id,
+                    -lost,
+                    LedgerSource::Penalty,
+                    Some(updated_q_task.id),
+                    conn,
+                )?;
+            }
+            query_task_to_task(today)(&updated_q_task)
+        }).collect::<Result<Vec<Task>>>()?;
+        Ok((missed_tasks, total_lost))
+    })
 }
 
-fn get_q_task(task_id: i32, conn: &PgPooledConnection) -> Result<QTask> {
+/// Gets the task with the given id, so long as the user can see it.
+/// Tasks belonging to other users and not shared with the user's household are reported as not found
+fn get_q_task(task_id: i32, user: &QUser, conn: &PgPooledConnection) -> Result<QTask> {
     use crate::schema::tasks::dsl::*;
 
-    let mut q_tasks = tasks
+    let mut q_tasks = visible_tasks(user)
         .filter(id.eq(task_id))
         .load::<QTask>(conn)
-        .map_err(|_| bad_request(format!("Error querying for task {}", task_id)))?;
+        .map_err(|_| internal(format!("Error querying for task {}", task_id)))?;
     // Should be a vec of only one item, return that item
     match q_tasks.pop() {
         Some(q_task) => Ok(q_task),
@@ -144,27 +343,43 @@ fn get_q_task(task_id: i32, conn: &PgPooledConnection) -> Result<QTask> {
     }
 }
 
-pub fn get_task(task_id: i32, conn: &PgPooledConnection, today: NaiveDate) -> Result<Task> {
-    let q_task = get_q_task(task_id, conn)?;
-    Ok(query_task_to_task(today)(&q_task))
+/// Gets a task that the user owns, so they can change it.
+/// Members of the household it's shared with can see and complete it, but not change it
+fn get_owned_q_task(task_id: i32, user: &QUser, conn: &PgPooledConnection) -> Result<QTask> {
+    let q_task = get_q_task(task_id, user, conn)?;
+    if q_task.user_id != user.id {
+        return Err(forbidden(format!("Only the owner of task {} can change it", task_id)));
+    }
+    Ok(q_task)
 }
 
-/// Add a new task to the database
-pub fn commit_new_task(new_task: NewTask, user: QUser, conn: PgPooledConnection, today: NaiveDate) -> Task {
-    use crate::schema::tasks;
+/// Gets the task and locks it until the transaction ends,
+/// so that changes made to it at the same time happen one after another
+fn lock_q_task(task_id: i32, user: &QUser, conn: &PgPooledConnection) -> Result<QTask> {
+    use crate::schema::tasks::dsl::*;
 
-    let next_reset = calc_next_reset(&new_task.frequency, today);
-    let (time_unit, every, by_when) = match new_task.frequency {
-        TaskInterval::Days{every} => {
-            (DAYS, every as i32, 0)
-        },
-        TaskInterval::Weeks{every, weekday} => {
-            (WEEKS, every as i32, weekday as i32)
-        },
-        TaskInterval::Months{every, day_of_month} => {
-            (MONTHS, every as i32, day_of_month as i32)
-        }
-    };
+    tasks.find(task_id)
+        .filter(user_id.eq(user.id).or(household_id.eq(user.household_id)))
+        .for_update()
+        .first::<QTask>(conn)
+        .optional()
+        .map_err(|_| internal(format!("Error querying for task {}", task_id)))?
+        .ok_or_else(|| not_found(format!("No task with id {} could be found", task_id)))
+}
+
+pub fn get_task(task_id: i32, user: &QUser, conn: &PgPooledConnection, today: NaiveDate) -> Result<Task> {
+    let q_task = get_q_task(task_id, user, conn)?;
+    query_task_to_task(today)(&q_task)
+}
+
+/// Add a new task to the database
+pub fn commit_new_task(new_task: NewTask, user: QUser, conn: PgPooledConnection, today: NaiveDate) -> Result<Task> {
+    let household_id = household::household_to_share_with(new_task.shared, &user)?;
+    let frequency = get_schedule(&new_task)?;
+    let next_reset = calc_first_reset(&frequency, today)?;
+    let (time_unit, every, by_when) = get_frequency_columns(&frequency);
+    let (penalty_policy, penalty_pts) = get_penalty_columns(&new_task.penalty);
+    let (streak_bonus_policy, streak_bonus_amount) = get_streak_bonus_columns(&new_task.streak_bonus);
     let full_task = InsertableTask {
         user_id: user.id,
         name: &new_task.name,
@@ -175,12 +390,17 @@ pub fn commit_new_task(new_task: NewTask, user: QUser, conn: PgPooledConnection,
         time_unit,
         by_when,
         icon: new_task.icon.into(),
+        penalty_policy,
+        penalty_pts,
+        streak_bonus_policy,
+        streak_bonus_amount,
+        household_id,
     };
     
     let committed_task: QTask = diesel::insert_into(tasks::table)
         .values(full_task)
         .get_result(&conn)
-        .expect("Error saving new task");
+        .map_err(|_| internal(format!("Error saving new task for user {}", user.id)))?;
 
     query_task_to_task(today)(&committed_task)
 }
@@ -192,24 +412,26 @@ fn update_q_task(q_task: &QTask, conn: &PgPooledConnection) -> Result<QTask> {
     diesel::update(tasks.find(q_task.id))
         .set(q_task)
         .get_result(conn)
-        .map_err(|_| bad_request(format!("Error updating for task {}", q_task.id)))
+        .map_err(|_| internal(format!("Error updating for task {}", q_task.id)))
 }
 
-/// Add a new task to the database
-pub fn update_task(task_id: i32, new_task: NewTask, conn: &PgPooledConnection, today: NaiveDate) -> Result<Task> {
-    let mut q_task = get_q_task(task_id, &conn)?;
+/// Add a new task to the database.
+/// Only the user who made a task can change it
+pub fn update_task(task_id: i32, user: &QUser, new_task: NewTask, conn: &PgPooledConnection, today: NaiveDate) -> Result<Task> {
+    let mut q_task = get_owned_q_task(task_id, user, &conn)?;
+    if new_task.shared != q_task.household_id.is_some() {
+        q_task.household_id = household::household_to_share_with(new_task.shared, user)?;
+    }
 
-    let (time_unit, every, by_when) = match new_task.frequency {
-        TaskInterval::Days{every} => {
-            (DAYS, every as i32, 0)
-        },
-        TaskInterval::Weeks{every, weekday} => {
-            (WEEKS, every as i32, weekday as i32)
-        },
-        TaskInterval::Months{every, day_of_month} => {
-            (MONTHS, every as i32, day_of_month as i32)
+    let frequency = get_schedule(&new_task)?;
+    if let TaskInterval::Once{due} = frequency {
+        // Only a new due date has to be in the future, so past-due tasks can still be edited
+        let was_once = q_task.time_unit == ONCE;
+        if !was_once || due != q_task.next_reset {
+            q_task.next_reset = calc_first_reset(&frequency, today)?;
         }
-    };
+    }
+    let (time_unit, every, by_when) = get_frequency_columns(&frequency);
 
     q_task.name = new_task.name;
     q_task.description = new_task.description;
@@ -218,35 +440,233 @@ pub fn update_task(task_id: i32, new_task: NewTask, conn: &PgPooledConnection, t
     q_task.time_unit = time_unit.to_string();
     q_task.by_when = by_when;
     q_task.icon = new_task.icon.into();
+    let (penalty_policy, penalty_pts) = get_penalty_columns(&new_task.penalty);
+    q_task.penalty_policy = penalty_policy.to_string();
+    q_task.penalty_pts = penalty_pts;
+    let (streak_bonus_policy, streak_bonus_amount) = get_streak_bonus_columns(&new_task.streak_bonus);
+    q_task.streak_bonus_policy = streak_bonus_policy.to_string();
+    q_task.streak_bonus_amount = streak_bonus_amount;
 
     let committed_task = update_q_task(&q_task, conn)?;
 
-    Ok(query_task_to_task(today)(&committed_task))
+    query_task_to_task(today)(&committed_task)
 }
 
-pub fn delete_task(task_id: i32, conn: &PgPooledConnection) -> Result<()> {
+/// Archives the task so that it leaves the todo and done lists, but keeps its history
+pub fn archive_task(task_id: i32, user: &QUser, conn: &PgPooledConnection) -> Result<()> {
+    let mut q_task = get_owned_q_task(task_id, user, conn)?;
+    if q_task.archived_at.is_some() {
+        return Err(bad_request(format!("Task {} is already archived", task_id)));
+    }
+    q_task.archived_at = Some(Utc::now().naive_utc());
+    update_q_task(&q_task, conn)?;
+    Ok(())
+}
+
+/// Brings an archived task back. A one-off task comes back as it was, so one that was done
+/// stays done, and a task that would have reset while it was archived picks up from today without any misses
+pub fn restore_task(task_id: i32, user: &QUser, conn: &PgPooledConnection, today: NaiveDate) -> Result<Task> {
+    let mut q_task = get_owned_q_task(task_id, user, conn)?;
+    if q_task.archived_at.is_none() {
+        return Err(bad_request(format!("Task {} isn't archived", task_id)));
+    }
+    let frequency = get_frequency_from_q_task(&q_task)?;
+    q_task.archived_at = None;
+    if frequency.repeats() && q_task.next_reset < today {
+        q_task.next_reset = calc_next_reset(&frequency, today)?;
+        q_task.is_done = false;
+    }
+    let restored_q_task = update_q_task(&q_task, conn)?;
+    query_task_to_task(today)(&restored_q_task)
+}
+
+/// Deletes an archived task for good, along with its completion history
+pub fn purge_task(task_id: i32, user: &QUser, conn: &PgPooledConnection) -> Result<()> {
     use crate::schema::tasks::dsl::tasks;
 
-    match diesel::delete(tasks.find(task_id)).execute(conn) {
+    let q_task = get_owned_q_task(task_id, user, conn)?;
+    if q_task.archived_at.is_none() {
+        return Err(bad_request(format!("Task {} must be archived before it's deleted", task_id)));
+    }
+    match diesel::delete(tasks.find(q_task.id)).execute(conn) {
         Ok(_) => Ok(()),
-        Err(_) => Err(bad_request(format!("Could not delete task {}", task_id))),
+        Err(_) => Err(internal(format!("Could not delete task {}", task_id))),
     }
 }
 
-/// marks the task as complete and returns the number of points that the user has after completion
-pub fn complete_task(task_id: i32, conn: &PgPooledConnection, today: NaiveDate) -> Result<Task> {
+/// marks the task as complete, adding to its streak, and gives the user its points
+/// and any streak bonus. The points of a shared task go to whichever member did it. One-off tasks are archived, and can still be done once they're
+/// past-due. Returns the completed task
+pub fn complete_task(task_id: i32, user: &QUser, conn: &PgPooledConnection, today: NaiveDate) -> Result<Task> {
     println!("Completing task {}", task_id);
-    let mut q_task = get_q_task(task_id, conn)?;
-    if q_task.is_done {
-        return Err(bad_request(format!("Task {} was already completed", q_task.id)));
-    }
-    if q_task.next_reset < today {
-        return Err(bad_request(format!("Task {} is past-due and cannot be completed", q_task.id)));
-    }
     atomically(conn, || {
+        // Checked under the lock so that the task can't be completed twice at once
+        let mut q_task = lock_q_task(task_id, user, conn)?;
+        if q_task.archived_at.is_some() {
+            return Err(bad_request(format!("Task {} is archived", q_task.id)));
+        }
+        if q_task.is_done {
+            return Err(bad_request(format!("Task {} was already completed", q_task.id)));
+        }
+        let repeats = get_frequency_from_q_task(&q_task)?.repeats();
+        if repeats && q_task.next_reset < today {
+            return Err(bad_request(format!("Task {} is past-due and cannot be completed", q_task.id)));
+        }
+        // Misses reset the streak, so this always carries it on
+        q_task.streak = q_task.streak.saturating_add(1);
+        q_task.best_streak = q_task.best_streak.max(q_task.streak);
+        let bonus = get_streak_bonus_from_q_task(&q_task).bonus_for_streak(q_task.streak as u32, q_task.bspts);
+        record_completion(&q_task, user, today, bonus, conn)?;
         q_task.is_done = true;
+        // Doing the task ends the run of misses
+        q_task.misses = 0;
+        if !repeats {
+            q_task.archived_at = Some(Utc::now().naive_utc());
+        }
+        let updated_q_task = update_q_task(&q_task, conn)?;
+        user::update_bspts(
+            user.id,
+            updated_q_task.bspts,
+            LedgerSource::TaskCompletion,
+            Some(updated_q_task.id),
+            conn,
+        )?;
+        if bonus > 0 {
+            user::update_bspts(
+                user.id,
+                bonus,
+                LedgerSource::StreakBonus,
+                Some(updated_q_task.id),
+                conn,
+            )?;
+        }
+        query_task_to_task(today)(&updated_q_task)
+    })
+}
+
+/// Undoes the last completion of the task, so long as it hasn't reset since.
+/// Only whoever completed it can undo it, and the points and bonus it earned are taken back from them,
+/// and its streak and run of misses are restored
+pub fn uncomplete_task(for_task_id: i32, user: &QUser, conn: &PgPooledConnection, today: NaiveDate) -> Result<Task> {
+    use crate::schema::task_completions::dsl::*;
+
+    atomically(conn, || {
+        // Checked under the lock so that the same completion can't be undone twice at once
+        let mut q_task = lock_q_task(for_task_id, user, conn)?;
+        if !q_task.is_done {
+            return Err(bad_request(format!("Task {} hasn't been completed", q_task.id)));
+        }
+        // One-off tasks never reset, so they can always be brought back
+        let repeats = get_frequency_from_q_task(&q_task)?.repeats();
+        if repeats && q_task.next_reset < today {
+            return Err(bad_request(format!("Task {} has reset since it was completed", q_task.id)));
+        }
+        if repeats && q_task.archived_at.is_some() {
+            return Err(bad_request(format!("Task {} is archived", q_task.id)));
+        }
+        let q_completion = QTaskCompletion::belonging_to(&q_task)
+            .order((created_at.desc(), id.desc()))
+            .first::<QTaskCompletion>(conn)
+            .optional()
+            .map_err(|_| internal(format!("Could not load the history of task {}", q_task.id)))?
+            .ok_or_else(|| bad_request(format!("Task {} has no completion to undo", q_task.id)))?;
+        if q_completion.user_id != user.id {
+            return Err(forbidden(format!("Task {} was completed by someone else", q_task.id)));
+        }
+        diesel::delete(task_completions.find(q_completion.id))
+            .execute(conn)
+            .map_err(|_| internal(format!("Could not delete completion {}", q_completion.id)))?;
+        // The best streak is the longest of the completions that are left
+        let remaining_best: Option<i32> = QTaskCompletion::belonging_to(&q_task)
+            .select(diesel::dsl::max(streak))
+            .first(conn)
+            .map_err(|_| internal(format!("Could not load the history of task {}", q_task.id)))?;
+        q_task.is_done = false;
+        q_task.archived_at = None;
+        q_task.misses = q_completion.misses;
+        q_task.streak = (q_completion.streak - 1).max(0);
+        q_task.best_streak = remaining_best.unwrap_or(0).max(q_task.streak);
         let updated_q_task = update_q_task(&q_task, conn)?;
-        user::update_bspts(updated_q_task.user_id, updated_q_task.bspts, conn)?;
-        Ok(query_task_to_task(today)(&updated_q_task))
+        user::update_bspts(
+            q_completion.user_id,
+            -q_completion.bspts.saturating_add(q_completion.bonus),
+            LedgerSource::CompletionUndone,
+            Some(updated_q_task.id),
+            conn,
+        )?;
+        query_task_to_task(today)(&updated_q_task)
     })
+}
+
+/// Records that the user completed the task today, with the points and streak bonus it earned.
+/// It's late if it was missed since it was last completed
+fn record_completion(q_task: &QTask, user: &QUser, today: NaiveDate, bonus: i32, conn: &PgPooledConnection) -> Result<()> {
+    use crate::schema::task_completions;
+
+    let completion = InsertableTaskCompletion {
+        task_id: q_task.id,
+        user_id: user.id,
+        completed_on: today,
+        bspts: q_task.bspts,
+        on_time: q_task.misses == 0,
+        misses: q_task.misses,
+        streak: q_task.streak,
+        bonus,
+    };
+    diesel::insert_into(task_completions::table)
+        .values(completion)
+        .execute(conn)
+        .map(|_| ())
+        .map_err(|_| internal(format!("Could not record the completion of task {}", q_task.id)))
+}
+
+fn q_completion_to_completion(task_name: &str, q: &QTaskCompletion) -> TaskCompletion {
+    TaskCompletion {
+        id: q.id,
+        task_id: q.task_id,
+        task_name: task_name.to_string(),
+        completed_on: q.completed_on,
+        bspts: q.bspts,
+        bonus: q.bonus,
+        streak: q.streak,
+        on_time: q.on_time,
+    }
+}
+
+/// Gets every time the task was completed, newest first
+pub fn get_task_history(for_task_id: i32, user: &QUser, conn: &PgPooledConnection) -> Result<Vec<TaskCompletion>> {
+    use crate::schema::task_completions::dsl::*;
+
+    let q_task = get_q_task(for_task_id, user, conn)?;
+    let q_completions = QTaskCompletion::belonging_to(&q_task)
+        .order((completed_on.desc(), id.desc()))
+        .load::<QTaskCompletion>(conn)
+        .map_err(|_| internal(format!("Could not load the history of task {}", for_task_id)))?;
+    Ok(q_completions.iter().map(|q| q_completion_to_completion(&q_task.name, q)).collect())
+}
+
+/// Gets every task the user completed from one day to another, including both, newest first
+pub fn get_history(user: &QUser, from: NaiveDate, to: NaiveDate, conn: &PgPooledConnection) -> Result<Vec<TaskCompletion>> {
+    use crate::schema::task_completions::dsl::*;
+
+    let q_completions = QTaskCompletion::belonging_to(user)
+        .filter(completed_on.ge(from))
+        .filter(completed_on.le(to))
+        .order((completed_on.desc(), id.desc()))
+        .load::<QTaskCompletion>(conn)
+        .map_err(|_| internal(format!("Could not load the task history for user {}", user.id)))?;
+    // Shared tasks the user did may belong to someone else
+    let task_ids: Vec<i32> = q_completions.iter().map(|q| q.task_id).collect();
+    let task_names: HashMap<i32, String> = tasks::table
+        .filter(tasks::id.eq_any(task_ids))
+        .load::<QTask>(conn)
+        .map_err(|_| internal(format!("Error loading tasks for user {}", user.id)))?
+        .into_iter()
+        .map(|q_task| (q_task.id, q_task.name))
+        .collect();
+    q_completions.iter().map(|q| {
+        let task_name = task_names.get(&q.task_id)
+            .ok_or_else(|| internal(format!("Completion {} is for a missing task {}", q.id, q.task_id)))?;
+        Ok(q_completion_to_completion(task_name, q))
+    }).collect()
 }
\ No newline at end of file
@@ -1,72 +1,119 @@
-use ring::{digest, pbkdf2};
+use ring::{constant_time, pbkdf2};
 use rand_core::{RngCore, OsRng};
 use std::num::NonZeroU32;
-use data::user::*;
+use argon2::{Algorithm, Argon2, Params, Version};
+use data::{user::*, ledger::LedgerSource, error::FieldError, validate::Validate};
 use crate::models;
 use crate::PgPooledConnection;
 use diesel::RunQueryDsl;
 use crate::diesel::ExpressionMethods;
 use crate::error::*;
+use crate::query::{atomically, ledger, household};
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
 
 static PBKDF2_ALG: pbkdf2::Algorithm = pbkdf2::PBKDF2_HMAC_SHA256;
-const CREDENTIAL_LEN: usize = digest::SHA256_OUTPUT_LEN;
-pub type Credential = [u8; CREDENTIAL_LEN];
 
-/// Takes a user name and password and returns the hashed pw and a random salt
-fn generate_creds(password: &str) -> (Vec<u8>, Vec<u8>) {
-    // Generate a salt for this user
-    let mut salt = [0u8, 16];
-    OsRng.fill_bytes(&mut salt);
-    let mut encrypted_password: Credential = [0u8; CREDENTIAL_LEN];
-    pbkdf2::derive(
-        PBKDF2_ALG,
-        NonZeroU32::new(100_000).unwrap(),
-        &salt,
-        password.as_bytes(),
-        &mut encrypted_password
-    );
-    (encrypted_password.to_vec(), salt.to_vec())
-}
+/// The names of the algorithms a password can be hashed with, as stored in the database
+const PBKDF2_SHA256: &str = "pbkdf2-sha256";
+const ARGON2ID: &str = "argon2id";
 
-/// checks that a given password is valid for a given user
-fn check_password(password: &str, user: &models::QUser) -> bool {
-    let stored_hash: &Vec<u8> = &user.password;
-    let mut generated_hash: Credential = [0u8; CREDENTIAL_LEN];
-    pbkdf2::derive(
-        PBKDF2_ALG,
-        NonZeroU32::new(100_000).unwrap(),
-        &user.salt,
-        password.as_bytes(),
-        &mut generated_hash
-    );
-    stored_hash == &generated_hash.to_vec()
+/// The format of the credentials that new passwords are saved in.
+/// * 1: PBKDF2 with a two byte salt, from before the hash parameters were recorded
+/// * 2: Argon2id with a 16 byte salt, with its parameters recorded next to the hash
+const CURRENT_HASH_VERSION: i32 = 2;
+const ARGON2_ITERATIONS: u32 = 2;
+const ARGON2_MEMORY_KIB: u32 = 19 * 1024;
+const SALT_LEN: usize = 16;
+const HASH_LEN: usize = 32;
+
+/// A hashed password along with everything needed to check a password against it
+pub struct Credential {
+    pub hash: Vec<u8>,
+    pub salt: Vec<u8>,
+    pub version: i32,
+    pub algorithm: &'static str,
+    pub iterations: i32,
+    pub memory_kib: i32,
 }
 
-/// Returns the user with the given username
-fn get_q_user_by_id(user_id: i32, conn: &PgPooledConnection) -> Result<models::QUser> {
-    use crate::schema::users::dsl::*;
-    use diesel::query_dsl::filter_dsl::FilterDsl;
+/// Gets an Argon2id hasher with the given cost, or None if the cost isn't valid
+fn argon2_hasher(iterations: u32, memory_kib: u32) -> Option<Argon2<'static>> {
+    let params = Params::new(memory_kib, iterations, 1, Some(HASH_LEN)).ok()?;
+    Some(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+}
 
-    let q_users: Vec<models::QUser> = users
-        .filter(id.eq(user_id))
-        .load::<models::QUser>(conn)
-        .expect("Error getting users");
+/// Takes a password and returns it hashed in the current format with a random salt
+fn generate_creds(password: &str) -> Result<Credential> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let mut hash = [0u8; HASH_LEN];
+    argon2_hasher(ARGON2_ITERATIONS, ARGON2_MEMORY_KIB)
+        .ok_or_else(|| internal("Invalid Argon2 parameters".to_string()))?
+        .hash_password_into(password.as_bytes(), &salt, &mut hash)
+        .map_err(|e| internal(format!("Error hashing password: {}", e)))?;
+    Ok(Credential {
+        hash: hash.to_vec(),
+        salt: salt.to_vec(),
+        version: CURRENT_HASH_VERSION,
+        algorithm: ARGON2ID,
+        iterations: ARGON2_ITERATIONS as i32,
+        memory_kib: ARGON2_MEMORY_KIB as i32,
+    })
+}
 
-    match &q_users[..] {
-        [] => Err(not_found(format!("Found no user with id {}", user_id))),
-        [q_user] => Ok(q_user.clone()),
-        _ => Err(conflict("Ambiguous user id".to_string())),
+/// checks that a given password is valid for a given user,
+/// using whichever algorithm and parameters their password was saved with
+fn check_password(password: &str, user: &models::QUser) -> bool {
+    match user.hash_algorithm.as_str() {
+        PBKDF2_SHA256 => {
+            let iterations = match NonZeroU32::new(user.hash_iterations as u32) {
+                Some(iterations) => iterations,
+                None => return false,
+            };
+            pbkdf2::verify(PBKDF2_ALG, iterations, &user.salt, password.as_bytes(), &user.password).is_ok()
+        }
+        ARGON2ID => {
+            let hasher = match argon2_hasher(user.hash_iterations as u32, user.hash_memory_kib as u32) {
+                Some(hasher) => hasher,
+                None => return false,
+            };
+            let mut generated_hash = vec![0u8; user.password.len()];
+            hasher.hash_password_into(password.as_bytes(), &user.salt, &mut generated_hash).is_ok()
+                && constant_time::verify_slices_are_equal(&generated_hash, &user.password).is_ok()
+        }
+        _ => false,
     }
 }
 
-fn update_q_user(q_user: models::QUser, conn: &PgPooledConnection) -> Result<models::QUser> {
-    use crate::schema::users::dsl::*;
+/// Whether the user's password was saved in an older format or with
+/// a lower cost than new passwords are, and so should be rehashed
+fn needs_rehash(user: &models::QUser) -> bool {
+    user.hash_version < CURRENT_HASH_VERSION
+        || user.hash_algorithm != ARGON2ID
+        || user.hash_iterations < ARGON2_ITERATIONS as i32
+        || user.hash_memory_kib < ARGON2_MEMORY_KIB as i32
+        || user.salt.len() < SALT_LEN
+}
+
+/// Saves the user's password in the current format. Returns the updated user
+fn rehash_password(q_user: models::QUser, password: &str, conn: &PgPooledConnection) -> Result<models::QUser> {
+    use crate::schema::users;
     use crate::diesel::query_dsl::filter_dsl::FindDsl;
 
-    diesel::update(users.find(q_user.id))
-        .set(&q_user)
+    let creds = generate_creds(password)?;
+    // Only the hash is written so that changes made to the user since they were loaded are kept
+    diesel::update(users::table.find(q_user.id))
+        .set((
+            users::password.eq(creds.hash),
+            users::salt.eq(creds.salt),
+            users::hash_version.eq(creds.version),
+            users::hash_algorithm.eq(creds.algorithm.to_string()),
+            users::hash_iterations.eq(creds.iterations),
+            users::hash_memory_kib.eq(creds.memory_kib),
+        ))
         .get_result(conn)
-        .map_err(|_| bad_request(format!("Error updating for user {}", q_user.id)))
+        .map_err(|_| internal(format!("Error saving the password of user {}", q_user.id)))
 }
 
 /// Returns the user if they are allowed to log in with that password, or an error otherwise
@@ -77,15 +124,23 @@ pub fn login_user(user: NewUser, conn: &PgPooledConnection) -> Result<models::QU
     let q_users: Vec<models::QUser> = users
         .filter(uname.eq(&user.uname))
         .load::<models::QUser>(conn)
-        .expect("Error getting users");
+        .map_err(|_| internal(format!("Error getting user with name {}", &user.uname)))?;
 
     match &q_users[..] {
         [] => Err(not_found(format!("There's no user with name {}", &user.uname))),
         [q_user] =>  {
-            if check_password(&user.password, q_user) {
-                Ok(q_user.clone())
-            } else {
+            if !check_password(&user.password, q_user) {
                 Err(unauthorized("Incorrect password".to_string()))
+            } else if needs_rehash(q_user) {
+                // The password is known to be right, so take the chance to
+                // save it in the current format. Failing that isn't a reason
+                // to stop the user from logging in
+                rehash_password(q_user.clone(), &user.password, conn).or_else(|e| {
+                    println!("Could not rehash the password for user {}: {}", q_user.id, e);
+                    Ok(q_user.clone())
+                })
+            } else {
+                Ok(q_user.clone())
             }
         },
         _ => Err(conflict("Ambiguous user name".to_string())),
@@ -96,11 +151,15 @@ pub fn login_user(user: NewUser, conn: &PgPooledConnection) -> Result<models::QU
 pub fn save_new_user(user: &NewUser, conn: &PgPooledConnection) -> Result<models::QUser> {
     use crate::schema::users;
 
-    let creds = generate_creds(&user.password);
+    let creds = generate_creds(&user.password)?;
     let insert = models::InsertableUser {
         uname: &user.uname,
-        password: creds.0,
-        salt: creds.1
+        password: creds.hash,
+        salt: creds.salt,
+        hash_version: creds.version,
+        hash_algorithm: creds.algorithm,
+        hash_iterations: creds.iterations,
+        hash_memory_kib: creds.memory_kib,
     };
     
     let save_user_result = diesel::insert_into(users::table)
@@ -110,11 +169,111 @@ pub fn save_new_user(user: &NewUser, conn: &PgPooledConnection) -> Result<models
     save_user_result.map_err(|_| conflict("There's already a user with that username".to_string()))
 }
 
-/// Adds the provided number of points to the user's total
+/// Adds the provided number of points to the user's total and records
+/// the change in the points ledger.
 /// Returns their total points after the addition
-pub fn update_bspts(user_id: i32, pts: i32, conn: &PgPooledConnection) -> Result<i32> {
-    let mut q_user = get_q_user_by_id(user_id, conn)?;
-    q_user.bspts += pts;
-    let updated_q_user = update_q_user(q_user, conn)?;
-    Ok(updated_q_user.bspts)
+/// * source: What caused the points to change
+/// * source_id: The id of the task, reward, etc. that caused the change
+pub fn update_bspts(
+    user_id: i32,
+    pts: i32,
+    source: LedgerSource,
+    source_id: Option<i32>,
+    conn: &PgPooledConnection,
+) -> Result<i32> {
+    use crate::schema::users::dsl::*;
+    use crate::diesel::query_dsl::filter_dsl::FindDsl;
+
+    atomically(conn, || {
+        ledger::record(user_id, pts, source, source_id, conn)?;
+        // Increment in the database rather than reading and writing back
+        // so that concurrent updates can't clobber each other
+        diesel::update(users.find(user_id))
+            .set(bspts.eq(bspts + pts))
+            .returning(bspts)
+            .get_result::<i32>(conn)
+            .map_err(|_| not_found(format!("Found no user with id {}", user_id)))
+    })
+}
+
+/// Sets the user's total points to the sum of their ledger.
+/// Returns their total points after reconciliation
+pub fn reconcile_bspts(user_id: i32, conn: &PgPooledConnection) -> Result<i32> {
+    use crate::schema::users::dsl::*;
+    use diesel::QueryDsl;
+
+    atomically(conn, || {
+        // Lock the user so that points changing at the same time wait for the total to be fixed
+        let user_bspts: i32 = users.find(user_id)
+            .select(bspts)
+            .for_update()
+            .first(conn)
+            .map_err(|_| not_found(format!("Found no user with id {}", user_id)))?;
+        let ledger_total = ledger::balance(user_id, conn)?;
+        if user_bspts == ledger_total {
+            return Ok(user_bspts);
+        }
+        println!("Reconciling user {} from {} to {} pts", user_id, user_bspts, ledger_total);
+        diesel::update(users.find(user_id))
+            .set(bspts.eq(ledger_total))
+            .returning(bspts)
+            .get_result::<i32>(conn)
+            .map_err(|_| internal(format!("Error reconciling the points of user {}", user_id)))
+    })
+}
+
+/// Gets the date it is for the user at the given moment, in their time zone.
+/// Falls back to UTC if their time zone isn't one that's recognized
+pub fn local_date(q_user: &models::QUser, now: DateTime<Utc>) -> NaiveDate {
+    let tz: Tz = q_user.time_zone.parse().unwrap_or(Tz::UTC);
+    now.with_timezone(&tz).date_naive()
+}
+
+/// Sets the user's time zone, which must be an IANA name like America/Denver.
+/// Returns the updated user
+pub fn set_time_zone(q_user: models::QUser, time_zone: &str, conn: &PgPooledConnection) -> Result<models::QUser> {
+    if time_zone.parse::<Tz>().is_err() {
+        return Err(validation(
+            "The time zone isn't valid".to_string(),
+            vec![FieldError::new("time_zone", &format!("{} is not a known time zone", time_zone))],
+        ));
+    }
+    use crate::schema::users;
+    use crate::diesel::query_dsl::filter_dsl::FindDsl;
+
+    diesel::update(users::table.find(q_user.id))
+        .set(users::time_zone.eq(time_zone))
+        .get_result(conn)
+        .map_err(|_| internal(format!("Error setting the time zone of user {}", q_user.id)))
+}
+
+/// Changes the user's password, as long as the current password they gave is right.
+/// Returns the updated user
+pub fn change_password(q_user: models::QUser, change: &PasswordChange, conn: &PgPooledConnection) -> Result<models::QUser> {
+    if !check_password(&change.current_password, &q_user) {
+        return Err(forbidden("Incorrect password".to_string()));
+    }
+    change.validate().map_err(api_error)?;
+    rehash_password(q_user, &change.new_password, conn)
+}
+
+/// Deletes the user, as long as the password they gave is right.
+/// Everything that belongs to them is deleted along with them
+pub fn delete_user(q_user: &models::QUser, confirm_password: &str, conn: &PgPooledConnection) -> Result<()> {
+    use crate::schema::users::dsl::*;
+    use crate::diesel::query_dsl::filter_dsl::FindDsl;
+
+    if !check_password(confirm_password, q_user) {
+        return Err(forbidden("Incorrect password".to_string()));
+    }
+    atomically(conn, || {
+        // Leaving first gives back the points other members saved for the user's shared rewards
+        if q_user.household_id.is_some() {
+            household::leave_household(q_user, conn)?;
+        }
+        diesel::delete(users.find(q_user.id))
+            .execute(conn)
+            .map(|_| ())
+            .map_err(|_| internal(format!("Could not delete user {}", q_user.id)))
+    })
 }
\ No newline at end of file
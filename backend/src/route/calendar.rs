@@ -0,0 +1,55 @@
+use actix_web::{
+    get,
+    post,
+    delete,
+    web::{self, Data, Json, ServiceConfig},
+    HttpResponse,
+};
+use chrono::Utc;
+use data::user::CalendarToken;
+use actix_session::{Session};
+use crate::{
+    PgPool,
+    route::*,
+    error::*,
+    query::{calendar::*, task::get_todo_tasks, user::local_date},
+};
+
+/// Makes a new secret token for calendar apps to read the user's tasks with.
+/// Any old token stops working
+#[post("/calendar")]
+async fn new_token(data: Data<PgPool>, ses: Session) -> Rsp<CalendarToken> {
+    with_auth(ses, data, |user, conn| {
+        let token = new_calendar_token(&user, &conn)?;
+        Ok(Json(CalendarToken {token}))
+    })
+}
+
+/// Stops calendar apps from reading the user's tasks
+#[delete("/calendar")]
+async fn remove_token(data: Data<PgPool>, ses: Session) -> Rsp<()> {
+    with_auth(ses, data, |user, conn| {
+        remove_calendar_token(&user, &conn)?;
+        Ok(Json(()))
+    })
+}
+
+/// The user's todo tasks as an iCalendar feed. Calendar apps can't log in,
+/// so the secret token in the path is used in place of a session
+#[get("/calendar/{token}.ics")]
+async fn feed(web::Path(token): web::Path<String>, data: Data<PgPool>) -> Result<HttpResponse> {
+    let conn = get_conn(&data)?;
+    let user = get_calendar_user(&token, &conn)?;
+    let now = Utc::now();
+    let today = local_date(&user, now);
+    let tasks = get_todo_tasks(user, &conn, today)?;
+    Ok(HttpResponse::Ok()
+        .content_type("text/calendar; charset=utf-8")
+        .body(render_calendar(&tasks, now)))
+}
+
+pub fn configure(config: &mut ServiceConfig) {
+    config.service(new_token);
+    config.service(remove_token);
+    config.service(feed);
+}
\ No newline at end of file
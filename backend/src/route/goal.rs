@@ -0,0 +1,72 @@
+use actix_web::{
+    get,
+    delete,
+    post,
+    web::{self, Data, Json, ServiceConfig},
+    HttpRequest,
+};
+use data::goal::*;
+use crate::query::goal::*;
+use actix_session::{Session};
+use crate::PgPool;
+use crate::route::*;
+use crate::error::*;
+
+/// Gets the rewards the user is saving for, with how far along they are
+#[get("/goal")]
+async fn get_all(req: HttpRequest, data: Data<PgPool>, ses: Session) -> Rsp<Vec<Goal>> {
+    with_auth(ses, data, |user, conn| {
+        let today = get_date(req, &user)?;
+        let goals = get_goals(user, &conn, today)?;
+        Ok(Json(goals))
+    })
+}
+
+/// Pins a reward as a goal to save points for
+#[post("/goal")]
+async fn new(payload: Json<NewGoal>, req: HttpRequest, data: Data<PgPool>, ses: Session) -> Rsp<Goal> {
+    with_auth(ses, data, |user, conn| {
+        let Json(new_goal) = payload;
+        let today = get_date(req, &user)?;
+        let goal = pin_goal(new_goal, &user, &conn, today)?;
+        Ok(Json(goal))
+    })
+}
+
+/// Sets aside some of the user's points for the goal
+#[post("/goal/{id}/deposit")]
+async fn deposit_to(
+    web::Path(id): web::Path<i32>,
+    payload: Json<GoalDeposit>,
+    req: HttpRequest,
+    data: Data<PgPool>,
+    ses: Session
+) -> Rsp<Goal> {
+    with_auth(ses, data, |user, conn| {
+        let Json(goal_deposit) = payload;
+        check_valid(&goal_deposit)?;
+        let today = get_date(req, &user)?;
+        let goal = deposit(id, goal_deposit.pts, &user, &conn, today)?;
+        Ok(Json(goal))
+    })
+}
+
+/// Cancels the goal, giving the points saved for it back. Returns the user's new total
+#[delete("/goal/{id}")]
+async fn cancel(
+    web::Path(id): web::Path<i32>,
+    data: Data<PgPool>,
+    ses: Session
+) -> Rsp<i32> {
+    with_auth(ses, data, |user, conn| {
+        let new_pts = cancel_goal(id, &user, &conn)?;
+        Ok(Json(new_pts))
+    })
+}
+
+pub fn configure(config: &mut ServiceConfig) {
+    config.service(get_all);
+    config.service(new);
+    config.service(deposit_to);
+    config.service(cancel);
+}
\ No newline at end of file
@@ -0,0 +1,58 @@
+use actix_web::{
+    get,
+    post,
+    web::{Data, Json, ServiceConfig},
+};
+use data::household::*;
+use crate::query::household::*;
+use actix_session::{Session};
+use crate::PgPool;
+use crate::route::*;
+use crate::error::*;
+
+/// Gets the household the user is in, with its members
+#[get("/household")]
+async fn get_current(data: Data<PgPool>, ses: Session) -> Rsp<Option<Household>> {
+    with_auth(ses, data, |user, conn| {
+        let household = get_household(&user, &conn)?;
+        Ok(Json(household))
+    })
+}
+
+/// Starts a new household with the user in it
+#[post("/household")]
+async fn new(payload: Json<NewHousehold>, data: Data<PgPool>, ses: Session) -> Rsp<Household> {
+    with_auth(ses, data, |user, conn| {
+        let Json(new_household) = payload;
+        check_valid(&new_household)?;
+        let household = create_household(new_household, &user, &conn)?;
+        Ok(Json(household))
+    })
+}
+
+/// Joins the household with the invite code
+#[post("/household/join")]
+async fn join(payload: Json<JoinHousehold>, data: Data<PgPool>, ses: Session) -> Rsp<Household> {
+    with_auth(ses, data, |user, conn| {
+        let Json(join) = payload;
+        check_valid(&join)?;
+        let household = join_household(join, &user, &conn)?;
+        Ok(Json(household))
+    })
+}
+
+/// Leaves the user's household, which stops sharing their tasks and rewards with it
+#[post("/household/leave")]
+async fn leave(data: Data<PgPool>, ses: Session) -> Rsp<()> {
+    with_auth(ses, data, |user, conn| {
+        leave_household(&user, &conn)?;
+        Ok(Json(()))
+    })
+}
+
+pub fn configure(config: &mut ServiceConfig) {
+    config.service(get_current);
+    config.service(new);
+    config.service(join);
+    config.service(leave);
+}
\ No newline at end of file
@@ -0,0 +1,45 @@
+use actix_web::{
+    get,
+    web::{Data, Json, Query, ServiceConfig}
+};
+use serde::Deserialize;
+use data::{ledger::*, error::FieldError};
+use crate::query::ledger::*;
+use actix_session::{Session};
+use crate::PgPool;
+use crate::route::*;
+use crate::error::*;
+
+const DEFAULT_PER_PAGE: i64 = 20;
+const MAX_PER_PAGE: i64 = 100;
+
+#[derive(Deserialize)]
+pub struct PageParams {
+    page: Option<i64>,
+    per_page: Option<i64>,
+}
+
+/// Gets a page of the history of where the user's points came from and went
+#[get("/ledger")]
+async fn get_page(params: Query<PageParams>, data: Data<PgPool>, ses: Session) -> Rsp<LedgerPage> {
+    with_auth(ses, data, |user, conn| {
+        let page = params.page.unwrap_or(0);
+        let per_page = params.per_page.unwrap_or(DEFAULT_PER_PAGE);
+        let mut field_errors = vec![];
+        if page < 0 {
+            field_errors.push(FieldError::new("page", "Must be at least 0"));
+        }
+        if !(1..=MAX_PER_PAGE).contains(&per_page) {
+            field_errors.push(FieldError::new("per_page", &format!("Must be between 1 and {}", MAX_PER_PAGE)));
+        }
+        if !field_errors.is_empty() {
+            return Err(validation("The page asked for isn't valid".to_string(), field_errors));
+        }
+        let ledger_page = get_ledger_page(&user, page, per_page, &conn)?;
+        Ok(Json(ledger_page))
+    })
+}
+
+pub fn configure(config: &mut ServiceConfig) {
+    config.service(get_page);
+}
\ No newline at end of file
@@ -1,52 +1,125 @@
 pub mod task;
 pub mod user;
 pub mod reward;
+pub mod goal;
+pub mod ledger;
+pub mod session;
+pub mod calendar;
+pub mod household;
 
-use actix_web::{error, http::StatusCode, web::Data, HttpRequest};
+use actix_web::{web::{Data, JsonConfig, PathConfig}, HttpRequest};
 use actix_session::{Session};
+use serde::Deserialize;
+use data::{validate::Validate, error::FieldError};
 use crate::{
     PgPool, PgPooledConnection,
     models,
-    query::session::get_session_user,
+    query::{session::get_session_user, user::local_date},
     error::*,
 };
-use chrono::NaiveDate;
+use chrono::{NaiveDate, Utc, Duration};
 
 const SESSION_ID_KEY: &str = "session_id";
+/// How many days of history are sent when the start isn't given
+const DEFAULT_HISTORY_DAYS: i64 = 30;
+/// The most days of history that can be asked for at once
+const MAX_HISTORY_DAYS: i64 = 366;
 
+/// The days to get the history of something for, including both
+#[derive(Deserialize)]
+pub struct HistoryParams {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+}
+
+/// Sends bodies that can't be read as the expected JSON back as an ApiError
+pub fn json_config() -> JsonConfig {
+    JsonConfig::default().error_handler(|err, _| {
+        bad_request(format!("The request body isn't valid: {}", err))
+    })
+}
+
+/// Sends paths that can't be read, e.g. an id that isn't a number, back as an ApiError
+pub fn path_config() -> PathConfig {
+    PathConfig::default().error_handler(|err, _| {
+        not_found(format!("The path isn't valid: {}", err))
+    })
+}
+
+/// Checks the fields of a request, returning a validation error listing any that are wrong
+pub fn check_valid<T: Validate>(payload: &T) -> Result<()> {
+    payload.validate().map_err(api_error)
+}
+
+/// Gets a connection from the pool, or an internal error if none is available
+pub fn get_conn(data: &Data<PgPool>) -> Result<PgPooledConnection> {
+    data.get_ref().get()
+        .map_err(|e| internal(format!("Failed to get database connection: {}", e)))
+}
+
+/// Runs the request as the user signed in to the session, or returns an unauthorized error.
+/// The user is loaded fresh for each request, so the household it's in decides which shared
+/// tasks and rewards it can see
 pub fn with_auth<T, F>(ses: Session, data: Data<PgPool>, run: F)-> Rsp<T>
 where
     F: FnOnce(models::QUser, PgPooledConnection) -> Rsp<T>
 {
-    let pool = data.get_ref().clone();
-    let conn = pool.get().expect("Failed to get database connection");
-    let session_cookie = ses.get::<i32>(SESSION_ID_KEY);
-    match session_cookie {
-        Ok(Some(session_id)) => {
-            let user = get_session_user(session_id, &conn)?;
-            run(user, conn)
-        }
-        _ => {
-            let error = error::InternalError::new("Could not get session", StatusCode::UNAUTHORIZED);
-            Err(error.into())
-        }
+    let conn = get_conn(&data)?;
+    let session_id = get_session_id(&ses)?;
+    let user = get_session_user(session_id, &conn)?;
+    run(user, conn)
+}
+
+/// Gets the id of the session that the request was made with
+pub fn get_session_id(ses: &Session) -> Result<i32> {
+    match ses.get::<i32>(SESSION_ID_KEY) {
+        Ok(Some(session_id)) => Ok(session_id),
+        _ => Err(unauthorized("Could not get session".to_string())),
     }
 }
 
-// Get the date sent in by the client
-pub fn get_date(req: HttpRequest) -> NaiveDate {
+/// Gets the date it is for the user. By default this is worked out from the user's
+/// time zone, but a client can override it by sending the year, month and day headers.
+/// Returns a bad request error if the override headers are incomplete or not a real date
+pub fn get_date(req: HttpRequest, user: &models::QUser) -> Result<NaiveDate> {
+    let headers = req.headers();
+    let date_headers = ["year", "month", "day"];
+    if date_headers.iter().all(|name| !headers.contains_key(*name)) {
+        return Ok(local_date(user, Utc::now()));
+    }
+    let get_header = |name: &str| -> Option<&str> {
+        headers.get(name)?.to_str().ok()
+    };
     let try_make_date = || -> Option<NaiveDate> {
-        let headers = req.headers();
-        let year_str = headers.get("year")?.to_str().ok()?;
-        let year = year_str.parse::<i32>().ok()?;
-        let month_str = headers.get("month")?.to_str().ok()?;
-        let month = month_str.parse::<u32>().ok()?;
-        let day_str = headers.get("day")?.to_str().ok()?;
-        let day = day_str.parse::<u32>().ok()?;
+        let year = get_header("year")?.parse::<i32>().ok()?;
+        let month = get_header("month")?.parse::<u32>().ok()?;
+        let day = get_header("day")?.parse::<u32>().ok()?;
         NaiveDate::from_ymd_opt(year, month, day)
     };
-    match try_make_date() {
-        Some(date) => date,
-        None => panic!("Could not get date from the headers"),
+    try_make_date().ok_or_else(|| {
+        bad_request("The year, month and day headers must all be set to a valid date".to_string())
+    })
+}
+
+/// Works out the days that history was asked for. Defaults to the last 30 days up to today.
+/// Returns a validation error if the range is backwards or too long
+pub fn get_history_days(params: &HistoryParams, req: HttpRequest, user: &models::QUser) -> Result<(NaiveDate, NaiveDate)> {
+    let to = match params.to {
+        Some(to) => to,
+        None => get_date(req, user)?,
+    };
+    let from = match params.from {
+        Some(from) => from,
+        None => to.checked_sub_signed(Duration::days(DEFAULT_HISTORY_DAYS - 1)).unwrap_or(NaiveDate::MIN),
+    };
+    let mut field_errors = vec![];
+    if from > to {
+        field_errors.push(FieldError::new("from", "Must not be after to"));
+    } else if (to - from).num_days() >= MAX_HISTORY_DAYS {
+        field_errors.push(FieldError::new("from", &format!("Can't be more than {} days before to", MAX_HISTORY_DAYS)));
+    }
+    if !field_errors.is_empty() {
+        return Err(validation("The dates asked for aren't valid".to_string(), field_errors));
     }
+    Ok((from, to))
 }
\ No newline at end of file
@@ -3,27 +3,65 @@ use actix_web::{
     delete,
     post,
     put,
-    web::{self, Data, Json, ServiceConfig}
+    web::{self, Data, Json, Query, ServiceConfig},
+    HttpRequest,
 };
 use data::reward::*;
-use crate::query::{self, reward::*};
+use crate::query::{reward::*, user::local_date};
+use chrono::Utc;
 use actix_session::{Session};
 use crate::PgPool;
 use crate::route::*;
 use crate::error::*;
 
 #[get("/reward")]
-async fn get_all(data: Data<PgPool>, ses: Session) -> Rsp<Vec<Reward>> {
+async fn get_all(req: HttpRequest, data: Data<PgPool>, ses: Session) -> Rsp<Vec<Reward>> {
     with_auth(ses, data, |user, conn| {
-        let rewards = get_rewards(user, &conn);
+        let today = get_date(req, &user)?;
+        let rewards = get_rewards(user, &conn, today)?;
         Ok(Json(rewards))
     })
 }
 
+/// Gets the rewards that were deleted, so they can be restored or purged
+#[get("/reward/archived")]
+async fn get_archived(data: Data<PgPool>, ses: Session) -> Rsp<Vec<Reward>> {
+    with_auth(ses, data, |user, conn| {
+        let rewards = get_archived_rewards(user, &conn)?;
+        Ok(Json(rewards))
+    })
+}
+
+/// Gets every time the reward was taken, newest first
+#[get("/reward/{id}/history")]
+async fn get_history_by_id(web::Path(id): web::Path<i32>, data: Data<PgPool>, ses: Session) -> Rsp<Vec<RewardRedemption>> {
+    with_auth(ses, data, |user, conn| {
+        let history = get_reward_history(id, &user, &conn)?;
+        Ok(Json(history))
+    })
+}
+
+/// Gets the rewards taken from one day to another, including both, newest first.
+/// Defaults to the last 30 days up to today
+#[get("/redemptions")]
+async fn get_redemption_range(
+    params: Query<HistoryParams>,
+    req: HttpRequest,
+    data: Data<PgPool>,
+    ses: Session
+) -> Rsp<Vec<RewardRedemption>> {
+    with_auth(ses, data, |user, conn| {
+        let (from, to) = get_history_days(&params, req, &user)?;
+        let redemptions = get_redemptions(&user, from, to, &conn)?;
+        Ok(Json(redemptions))
+    })
+}
+
 #[get("/reward/{id}")]
-async fn get_by_id(web::Path(id): web::Path<i32>, data: Data<PgPool>, ses: Session) -> Rsp<Reward> {
-    with_auth(ses, data, |_, conn| {
-        let reward = get_reward(id, &conn)?;
+async fn get_by_id(web::Path(id): web::Path<i32>, req: HttpRequest, data: Data<PgPool>, ses: Session) -> Rsp<Reward> {
+    with_auth(ses, data, |user, conn| {
+        let today = get_date(req, &user)?;
+        let reward = get_reward(id, &user, &conn, today)?;
         Ok(Json(reward))
     })
 }
@@ -32,13 +70,16 @@ async fn get_by_id(web::Path(id): web::Path<i32>, data: Data<PgPool>, ses: Sessi
 async fn new(payload: Json<NewReward>, data: Data<PgPool>, ses: Session) -> Rsp<Reward> {
     with_auth(ses, data, |user, conn| {
         let Json(new_reward) = payload;
-        let committed_reward = commit_new_reward(new_reward, user, conn);
+        check_valid(&new_reward)?;
+        let committed_reward = commit_new_reward(new_reward, user, conn)?;
         Ok(Json(committed_reward))
     })
 }
 
-/// Takes the id of a reward and removes points from the user's
-/// total equal to the reward's cost.
+/// Takes the id of a reward, records that it was taken, and removes points
+/// from the user's total equal to the reward's cost. Returns the new total.
+/// Cooldowns and limits are checked against the server's date in the user's time zone,
+/// so they can't be dodged by sending another date
 #[post("/reward/do/{id}")]
 async fn did_it(
     web::Path(id): web::Path<i32>,
@@ -46,9 +87,8 @@ async fn did_it(
     ses: Session
 ) -> Rsp<i32> {
     with_auth(ses, data, |user, conn| {
-        let reward = get_reward(id, &conn)?;
-        let cost = -reward.bspts;
-        let new_pts = query::user::update_bspts(user.id, cost, &conn)?;
+        let today = local_date(&user, Utc::now());
+        let new_pts = redeem_reward(id, &user, &conn, today)?;
         Ok(Json(new_pts))
     })
 }
@@ -57,33 +97,67 @@ async fn did_it(
 async fn update(
     web::Path(id): web::Path<i32>,
     payload: Json<NewReward>,
+    req: HttpRequest,
     data: Data<PgPool>,
     ses: Session
 ) -> Rsp<Reward> {
-    with_auth(ses, data, |_, conn| {
+    with_auth(ses, data, |user, conn| {
         let Json(reward_updates) = payload;
-        let updated_reward = update_reward(id, reward_updates, &conn)?;
+        check_valid(&reward_updates)?;
+        let today = get_date(req, &user)?;
+        let updated_reward = update_reward(id, &user, reward_updates, &conn, today)?;
         Ok(Json(updated_reward))
     })
 }
 
+/// Archives the reward, which can be restored or purged later
 #[delete("/reward/{id}")]
 async fn delete(
     web::Path(id): web::Path<i32>,
     data: Data<PgPool>,
     ses: Session
 ) -> Rsp<()> {
-    with_auth(ses, data, |_, conn| {
-        delete_reward(id, &conn)?;
+    with_auth(ses, data, |user, conn| {
+        archive_reward(id, &user, &conn)?;
+        Ok(Json(()))
+    })
+}
+
+#[post("/reward/restore/{id}")]
+async fn restore(
+    web::Path(id): web::Path<i32>,
+    data: Data<PgPool>,
+    ses: Session
+) -> Rsp<Reward> {
+    with_auth(ses, data, |user, conn| {
+        let reward = restore_reward(id, &user, &conn)?;
+        Ok(Json(reward))
+    })
+}
+
+/// Deletes an archived reward for good
+#[delete("/reward/purge/{id}")]
+async fn purge(
+    web::Path(id): web::Path<i32>,
+    data: Data<PgPool>,
+    ses: Session
+) -> Rsp<()> {
+    with_auth(ses, data, |user, conn| {
+        purge_reward(id, &user, &conn)?;
         Ok(Json(()))
     })
 }
 
 pub fn configure(config: &mut ServiceConfig) {
     config.service(get_all);
+    config.service(get_archived);
+    config.service(get_history_by_id);
+    config.service(get_redemption_range);
     config.service(get_by_id);
     config.service(new);
     config.service(did_it);
     config.service(update);
     config.service(delete);
+    config.service(restore);
+    config.service(purge);
 }
\ No newline at end of file
@@ -0,0 +1,51 @@
+use actix_web::{
+    get,
+    post,
+    delete,
+    web::{self, Data, Json, ServiceConfig}
+};
+use data::session::SessionInfo;
+use actix_session::{Session};
+use crate::{
+    PgPool,
+    route::*,
+    error::*,
+    query::session::*,
+};
+
+/// Ends the session the request was made with. Logging out when
+/// already logged out does nothing
+#[post("/logout")]
+async fn logout(data: Data<PgPool>, ses: Session) -> Rsp<()> {
+    if let Ok(session_id) = get_session_id(&ses) {
+        let conn = get_conn(&data)?;
+        end_session(session_id, &conn)?;
+    }
+    ses.purge();
+    Ok(Json(()))
+}
+
+/// Lists everywhere the user is logged in
+#[get("/sessions")]
+async fn get_all(data: Data<PgPool>, ses: Session) -> Rsp<Vec<SessionInfo>> {
+    let current_session_id = get_session_id(&ses)?;
+    with_auth(ses, data, |user, conn| {
+        let sessions = get_sessions(&user, current_session_id, &conn)?;
+        Ok(Json(sessions))
+    })
+}
+
+/// Logs the user out of one of their sessions
+#[delete("/sessions/{id}")]
+async fn revoke(web::Path(id): web::Path<i32>, data: Data<PgPool>, ses: Session) -> Rsp<()> {
+    with_auth(ses, data, |user, conn| {
+        revoke_session(&user, id, &conn)?;
+        Ok(Json(()))
+    })
+}
+
+pub fn configure(config: &mut ServiceConfig) {
+    config.service(logout);
+    config.service(get_all);
+    config.service(revoke);
+}
\ No newline at end of file
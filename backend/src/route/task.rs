@@ -3,11 +3,12 @@ use actix_web::{
     delete,
     post,
     put,
-    web::{self, Data, Json, ServiceConfig},
+    web::{self, Data, Json, Query, ServiceConfig},
     HttpRequest,
 };
 use data::task::*;
-use crate::query::task::*;
+use crate::query::{task::*, rollover::roll_over_tasks, user::local_date};
+use chrono::Utc;
 use actix_session::{Session};
 use crate::PgPool;
 use crate::route::*;
@@ -16,8 +17,8 @@ use crate::error::*;
 #[get("/task/todo")]
 async fn get_todo(req: HttpRequest, data: Data<PgPool>, ses: Session) -> Rsp<Vec<Task>> {
     with_auth(ses, data, |user, conn| {
-        let today = get_date(req);
-        let tasks = get_todo_tasks(user, &conn, today);
+        let today = get_date(req, &user)?;
+        let tasks = get_todo_tasks(user, &conn, today)?;
         Ok(Json(tasks))
     })
 }
@@ -25,26 +26,65 @@ async fn get_todo(req: HttpRequest, data: Data<PgPool>, ses: Session) -> Rsp<Vec
 #[get("/task/done")]
 async fn get_done(req: HttpRequest, data: Data<PgPool>, ses: Session) -> Rsp<Vec<Task>> {
     with_auth(ses, data, |user, conn| {
-        let today = get_date(req);
-        let tasks_lists = get_done_tasks(user, &conn, today);
+        let today = get_date(req, &user)?;
+        let tasks_lists = get_done_tasks(user, &conn, today)?;
         Ok(Json(tasks_lists))
     })
 }
 
+/// Gets the tasks that were deleted, and the one-off tasks that were done
+#[get("/task/archived")]
+async fn get_archived(req: HttpRequest, data: Data<PgPool>, ses: Session) -> Rsp<Vec<Task>> {
+    with_auth(ses, data, |user, conn| {
+        let today = get_date(req, &user)?;
+        let tasks = get_archived_tasks(user, &conn, today)?;
+        Ok(Json(tasks))
+    })
+}
+
+/// Rolls the user's tasks over to today right away rather than waiting for the
+/// scheduler. Returns the list of done tasks that were due again.
+/// Today is always the server's date in the user's time zone, since the scheduler
+/// skips the user until the day they were last rolled over to
 #[post("/task/undo")]
-async fn undo(req: HttpRequest, data: Data<PgPool>, ses: Session) -> Rsp<Vec<Task>> {
+async fn undo(data: Data<PgPool>, ses: Session) -> Rsp<Vec<Task>> {
     with_auth(ses, data, |user, conn| {
-        let today = get_date(req);
-        let tasks_lists = move_tasks_to_todo_if_ready(user, &conn, today);
-        Ok(Json(tasks_lists))
+        let today = local_date(&user, Utc::now());
+        let (reset_tasks, _) = roll_over_tasks(&user, &conn, today)?;
+        Ok(Json(reset_tasks))
+    })
+}
+
+/// Gets every time the task was completed, newest first
+#[get("/task/{id}/history")]
+async fn get_history_by_id(web::Path(id): web::Path<i32>, data: Data<PgPool>, ses: Session) -> Rsp<Vec<TaskCompletion>> {
+    with_auth(ses, data, |user, conn| {
+        let history = get_task_history(id, &user, &conn)?;
+        Ok(Json(history))
+    })
+}
+
+/// Gets the tasks completed from one day to another, including both, newest first.
+/// Defaults to the last 30 days up to today
+#[get("/history")]
+async fn get_history_range(
+    params: Query<HistoryParams>,
+    req: HttpRequest,
+    data: Data<PgPool>,
+    ses: Session
+) -> Rsp<Vec<TaskCompletion>> {
+    with_auth(ses, data, |user, conn| {
+        let (from, to) = get_history_days(&params, req, &user)?;
+        let history = get_history(&user, from, to, &conn)?;
+        Ok(Json(history))
     })
 }
 
 #[get("/task/{id}")]
 async fn get_by_id(web::Path(id): web::Path<i32>, req: HttpRequest, data: Data<PgPool>, ses: Session) -> Rsp<Task> {
-    with_auth(ses, data, |_, conn| {
-        let today = get_date(req);
-        let task = get_task(id, &conn, today)?;
+    with_auth(ses, data, |user, conn| {
+        let today = get_date(req, &user)?;
+        let task = get_task(id, &user, &conn, today)?;
         Ok(Json(task))
     })
 }
@@ -53,8 +93,9 @@ async fn get_by_id(web::Path(id): web::Path<i32>, req: HttpRequest, data: Data<P
 async fn commit_new(req: HttpRequest, payload: Json<NewTask>, data: Data<PgPool>, ses: Session) -> Rsp<Task> {
     with_auth(ses, data, |user, conn| {
         let Json(new_task) = payload;
-        let today = get_date(req);
-        let committed_task = commit_new_task(new_task, user, conn, today);
+        check_valid(&new_task)?;
+        let today = get_date(req, &user)?;
+        let committed_task = commit_new_task(new_task, user, conn, today)?;
         Ok(Json(committed_task))
     })
 }
@@ -67,10 +108,11 @@ async fn update(
     data: Data<PgPool>,
     ses: Session
 ) -> Rsp<Task> {
-    with_auth(ses, data, |_, conn| {
+    with_auth(ses, data, |user, conn| {
         let Json(task_updates) = payload;
-        let today = get_date(req);
-        let updated_task = update_task(id, task_updates, &conn, today)?;
+        check_valid(&task_updates)?;
+        let today = get_date(req, &user)?;
+        let updated_task = update_task(id, &user, task_updates, &conn, today)?;
         Ok(Json(updated_task))
     })
 }
@@ -83,21 +125,64 @@ async fn complete(
     ses: Session
 ) -> Rsp<Task> {
     println!("In complete task route");
-    with_auth(ses, data, |_, conn| {
-        let today = get_date(req);
-        let updated_pts = complete_task(id, &conn, today)?;
+    with_auth(ses, data, |user, conn| {
+        let today = get_date(req, &user)?;
+        let updated_pts = complete_task(id, &user, &conn, today)?;
         Ok(Json(updated_pts))
     })
 }
 
+/// Undoes completing the task, in case it was done by mistake.
+/// Only allowed until the task resets, going by the server's date in the user's time zone
+#[post("/task/uncomplete/{id}")]
+async fn uncomplete(
+    web::Path(id): web::Path<i32>,
+    data: Data<PgPool>,
+    ses: Session
+) -> Rsp<Task> {
+    with_auth(ses, data, |user, conn| {
+        let today = local_date(&user, Utc::now());
+        let task = uncomplete_task(id, &user, &conn, today)?;
+        Ok(Json(task))
+    })
+}
+
+/// Archives the task, which can be restored or purged later
 #[delete("/task/{id}")]
 async fn delete(
     web::Path(id): web::Path<i32>,
     data: Data<PgPool>,
     ses: Session
 ) -> Rsp<()> {
-    with_auth(ses, data, |_, conn| {
-        delete_task(id, &conn)?;
+    with_auth(ses, data, |user, conn| {
+        archive_task(id, &user, &conn)?;
+        Ok(Json(()))
+    })
+}
+
+#[post("/task/restore/{id}")]
+async fn restore(
+    web::Path(id): web::Path<i32>,
+    req: HttpRequest,
+    data: Data<PgPool>,
+    ses: Session
+) -> Rsp<Task> {
+    with_auth(ses, data, |user, conn| {
+        let today = get_date(req, &user)?;
+        let task = restore_task(id, &user, &conn, today)?;
+        Ok(Json(task))
+    })
+}
+
+/// Deletes an archived task and its history for good
+#[delete("/task/purge/{id}")]
+async fn purge(
+    web::Path(id): web::Path<i32>,
+    data: Data<PgPool>,
+    ses: Session
+) -> Rsp<()> {
+    with_auth(ses, data, |user, conn| {
+        purge_task(id, &user, &conn)?;
         Ok(Json(()))
     })
 }
@@ -105,10 +190,16 @@ async fn delete(
 pub fn configure(config: &mut ServiceConfig) {
     config.service(get_todo);
     config.service(get_done);
+    config.service(get_archived);
     config.service(undo);
+    config.service(get_history_by_id);
+    config.service(get_history_range);
     config.service(get_by_id);
     config.service(commit_new);
     config.service(update);
     config.service(complete);
+    config.service(uncomplete);
     config.service(delete);
+    config.service(restore);
+    config.service(purge);
 }
\ No newline at end of file
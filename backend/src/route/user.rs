@@ -1,6 +1,8 @@
 use actix_web::{
     get,
     post,
+    put,
+    delete,
     web::{Data, Json, ServiceConfig}
 };
 use data::user::*;
@@ -14,35 +16,72 @@ use crate::{
 
 #[post("/login")]
 async fn sign_in(payload: Json<NewUser>, database: Data<PgPool>, ses: Session) -> Rsp<User>  {
-    let pool = database.get_ref().clone();
-    let conn = pool.get().expect("Failed to get database connection");
+    let conn = get_conn(&database)?;
     let Json(new_user) = payload;
     let user = login_user(new_user, &conn)?;
-    let new_session = start_session(&user, &conn);
+    let bspts = reconcile_bspts(user.id, &conn)?;
+    let new_session = start_session(&user, &conn)?;
     ses.set(SESSION_ID_KEY, new_session.id)?;
-    Ok(Json(User {uname: user.uname, bspts: user.bspts}))
+    Ok(Json(User {uname: user.uname, bspts, time_zone: user.time_zone, household_id: user.household_id}))
 }
 
 #[get("/user")]
 async fn get_user(data: Data<PgPool>, ses: Session) -> Rsp<User> {
     with_auth(ses, data, |user, _| {
-        Ok(Json(User {uname: user.uname, bspts: user.bspts}))
+        Ok(Json(User {uname: user.uname, bspts: user.bspts, time_zone: user.time_zone, household_id: user.household_id}))
     })
 }
 
 #[post("/user")]
 async fn sign_up(payload: Json<NewUser>, database: Data<PgPool>, ses: Session) -> Rsp<User> {
-    let pool = database.get_ref().clone();
-    let conn = pool.get().expect("Failed to get database connection");
+    let conn = get_conn(&database)?;
     let Json(new_user) = payload;
+    check_valid(&new_user)?;
     let user = save_new_user(&new_user, &conn)?;
-    let new_session = start_session(&user, &conn);
+    let new_session = start_session(&user, &conn)?;
     ses.set(SESSION_ID_KEY, new_session.id)?;
-    Ok(Json(User {uname: user.uname, bspts: user.bspts}))
+    Ok(Json(User {uname: user.uname, bspts: user.bspts, time_zone: user.time_zone, household_id: user.household_id}))
+}
+
+/// Sets the time zone the user's days are counted in
+#[put("/user/time_zone")]
+async fn set_user_time_zone(payload: Json<String>, data: Data<PgPool>, ses: Session) -> Rsp<User> {
+    with_auth(ses, data, |user, conn| {
+        let Json(time_zone) = payload;
+        let user = set_time_zone(user, &time_zone, &conn)?;
+        Ok(Json(User {uname: user.uname, bspts: user.bspts, time_zone: user.time_zone, household_id: user.household_id}))
+    })
+}
+
+/// Changes the user's password and logs them out everywhere else
+#[put("/user/password")]
+async fn update_password(payload: Json<PasswordChange>, data: Data<PgPool>, ses: Session) -> Rsp<User> {
+    let current_session_id = get_session_id(&ses)?;
+    with_auth(ses, data, |user, conn| {
+        let Json(change) = payload;
+        let user = change_password(user, &change, &conn)?;
+        end_other_sessions(&user, current_session_id, &conn)?;
+        Ok(Json(User {uname: user.uname, bspts: user.bspts, time_zone: user.time_zone, household_id: user.household_id}))
+    })
+}
+
+/// Deletes the user's account and everything in it, then logs them out
+#[delete("/user")]
+async fn delete_account(payload: Json<AccountDeletion>, database: Data<PgPool>, ses: Session) -> Rsp<()> {
+    let conn = get_conn(&database)?;
+    let session_id = get_session_id(&ses)?;
+    let user = get_session_user(session_id, &conn)?;
+    let Json(deletion) = payload;
+    delete_user(&user, &deletion.password, &conn)?;
+    ses.purge();
+    Ok(Json(()))
 }
 
 pub fn configure(config: &mut ServiceConfig) {
     config.service(sign_in);
     config.service(get_user);
     config.service(sign_up);
+    config.service(set_user_time_zone);
+    config.service(update_password);
+    config.service(delete_account);
 }
\ No newline at end of file
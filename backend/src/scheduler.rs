@@ -0,0 +1,56 @@
+use actix::{Actor, AsyncContext, Context};
+use actix_web::web;
+use chrono::Utc;
+use std::time::Duration;
+use crate::{PgPool, query::rollover::roll_over_due_users};
+
+/// How often to check for users whose tasks need to be rolled over.
+/// Each user is only rolled over once per day, no matter how often this runs
+const CHECK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Runs in the background of the server and rolls every user's tasks over to the
+/// new day: missed tasks are penalized and done tasks that are due again go back to todo.
+/// This keeps tasks correct even if nobody opens the app
+pub struct RolloverScheduler {
+    pool: PgPool,
+}
+
+impl RolloverScheduler {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Rolls over any users that are due on a blocking thread so that the
+    /// database calls don't hold up the server
+    fn run(&self) {
+        let pool = self.pool.clone();
+        actix::spawn(async move {
+            let result = web::block(move || {
+                let conn = pool.get().map_err(|e| e.to_string())?;
+                roll_over_due_users(&conn, Utc::now()).map_err(|e| e.to_string())
+            }).await;
+            match result {
+                Ok(rollovers) if !rollovers.is_empty() => {
+                    let reset: i32 = rollovers.iter().map(|r| r.tasks_reset).sum();
+                    let missed: i32 = rollovers.iter().map(|r| r.tasks_missed).sum();
+                    println!(
+                        "Rolled over {} users: {} tasks reset, {} tasks missed",
+                        rollovers.len(), reset, missed
+                    );
+                }
+                Ok(_) => (),
+                Err(e) => println!("Could not roll over tasks: {}", e),
+            }
+        });
+    }
+}
+
+impl Actor for RolloverScheduler {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        // Catch up on anything missed while the server was down, then keep checking
+        self.run();
+        ctx.run_interval(CHECK_INTERVAL, |scheduler, _| scheduler.run());
+    }
+}
\ No newline at end of file
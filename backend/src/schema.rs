@@ -1,3 +1,44 @@
+table! {
+    households (id) {
+        id -> Int4,
+        name -> Text,
+        invite_code -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    points_ledger (id) {
+        id -> Int4,
+        user_id -> Int4,
+        source_type -> Text,
+        source_id -> Nullable<Int4>,
+        amount -> Int4,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    goals (id) {
+        id -> Int4,
+        user_id -> Int4,
+        reward_id -> Int4,
+        saved -> Int4,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    reward_redemptions (id) {
+        id -> Int4,
+        reward_id -> Int4,
+        user_id -> Int4,
+        redeemed_on -> Date,
+        bspts -> Int4,
+        created_at -> Timestamp,
+    }
+}
+
 table! {
     rewards (id) {
         id -> Int4,
@@ -6,6 +47,26 @@ table! {
         description -> Text,
         bspts -> Int4,
         icon -> Text,
+        archived_at -> Nullable<Timestamp>,
+        cooldown_days -> Int4,
+        limit_period -> Text,
+        limit_times -> Int4,
+        allow_overdraft -> Bool,
+        price_increment -> Int4,
+        price_decay -> Int4,
+        household_id -> Nullable<Int4>,
+    }
+}
+
+table! {
+    rollovers (id) {
+        id -> Int4,
+        user_id -> Int4,
+        rolled_over_on -> Date,
+        tasks_reset -> Int4,
+        tasks_missed -> Int4,
+        pts_lost -> Int4,
+        created_at -> Timestamp,
     }
 }
 
@@ -13,6 +74,24 @@ table! {
     sessions (id) {
         id -> Int4,
         user_id -> Int4,
+        created_at -> Timestamp,
+        last_seen -> Timestamp,
+        expires_at -> Timestamp,
+    }
+}
+
+table! {
+    task_completions (id) {
+        id -> Int4,
+        task_id -> Int4,
+        user_id -> Int4,
+        completed_on -> Date,
+        bspts -> Int4,
+        on_time -> Bool,
+        created_at -> Timestamp,
+        misses -> Int4,
+        streak -> Int4,
+        bonus -> Int4,
     }
 }
 
@@ -30,6 +109,15 @@ table! {
         user_id -> Int4,
         icon -> Text,
         pts_lost -> Int4,
+        penalty_policy -> Text,
+        penalty_pts -> Int4,
+        misses -> Int4,
+        streak -> Int4,
+        best_streak -> Int4,
+        streak_bonus_policy -> Text,
+        streak_bonus_amount -> Int4,
+        archived_at -> Nullable<Timestamp>,
+        household_id -> Nullable<Int4>,
     }
 }
 
@@ -40,12 +128,31 @@ table! {
         password -> Bytea,
         salt -> Bytea,
         bspts -> Int4,
+        last_rollover -> Nullable<Date>,
+        time_zone -> Text,
+        hash_version -> Int4,
+        hash_algorithm -> Text,
+        hash_iterations -> Int4,
+        hash_memory_kib -> Int4,
+        calendar_token_hash -> Nullable<Bytea>,
+        household_id -> Nullable<Int4>,
     }
 }
 
+joinable!(goals -> rewards (reward_id));
+joinable!(users -> households (household_id));
+joinable!(reward_redemptions -> rewards (reward_id));
+joinable!(task_completions -> tasks (task_id));
+
 allow_tables_to_appear_in_same_query!(
+    goals,
+    households,
+    points_ledger,
+    reward_redemptions,
     rewards,
+    rollovers,
     sessions,
+    task_completions,
     tasks,
     users,
 );
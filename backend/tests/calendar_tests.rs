@@ -0,0 +1,98 @@
+mod setup;
+
+use backend_lib::*;
+use actix_web::{self, test, http::{Method, StatusCode}};
+use data::user::CalendarToken;
+use setup::*;
+
+#[actix_rt::test]
+async fn calendar_feed_lists_tasks() {
+    let user = make_user("calendar_feed_lists_tasks");
+    let pool = get_connection_pool();
+    let session_cookie = login(&user, &pool).await.expect("Failed to login");
+    let mut app = make_service(
+        |c| {
+            c.service(route::task::commit_new);
+            route::calendar::configure(c);
+        },
+        &pool
+    ).await;
+
+    println!("Add a task that repeats twice a week");
+    let req = test::TestRequest::with_header("content-type", "application/json")
+        .uri("/task")
+        .method(Method::POST)
+        .cookie(session_cookie.clone())
+        .set_payload(r#"{
+            "name": "Laundry, towels",
+            "description": "",
+            "bspts": 2,
+            "icon": {"color": "Yellow", "category": "Clean"},
+            "rrule": "FREQ=WEEKLY;BYDAY=MO,TH"
+        }"#)
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+
+    println!("Make a calendar token");
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/calendar")
+        .method(Method::POST)
+        .cookie(session_cookie.clone())
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+    let CalendarToken {token} = test::read_body_json(resp).await;
+
+    println!("Get the feed without logging in");
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri(&format!("/calendar/{}.ics", token))
+        .method(Method::GET)
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+    let body = String::from_utf8(test::read_body(resp).await.to_vec()).expect("The feed isn't UTF-8");
+    println!("{}", body);
+    assert!(body.starts_with("BEGIN:VCALENDAR\r\n"));
+    assert!(body.contains("BEGIN:VEVENT\r\n"));
+    assert!(body.contains("SUMMARY:Laundry\\, towels (2 pts)\r\n"));
+    assert!(body.contains("RRULE:FREQ=WEEKLY;BYDAY=MO,TH\r\n"));
+
+    println!("A made up token has no feed");
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/calendar/not-a-token.ics")
+        .method(Method::GET)
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    println!("Make a new token, which stops the old one working");
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/calendar")
+        .method(Method::POST)
+        .cookie(session_cookie.clone())
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    let CalendarToken {token: new_token} = test::read_body_json(resp).await;
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri(&format!("/calendar/{}.ics", token))
+        .method(Method::GET)
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    println!("Turn the feed off");
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/calendar")
+        .method(Method::DELETE)
+        .cookie(session_cookie.clone())
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri(&format!("/calendar/{}.ics", new_token))
+        .method(Method::GET)
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
\ No newline at end of file
@@ -0,0 +1,116 @@
+mod setup;
+
+use backend_lib::*;
+use actix_web::{self, test, http::{Method, StatusCode}};
+use data::{task::*, reward::*, goal::*, error::ApiError, icon::{TaskIcon, RewardIcon}};
+use chrono::NaiveDate;
+use setup::*;
+
+#[actix_rt::test]
+async fn saving_for_a_goal() {
+    println!("Setup goal test");
+    let user = make_user("saving_for_a_goal");
+    let pool = get_connection_pool();
+    let session_cookie = login(&user, &pool).await.expect("Failed to login");
+    let mut app = make_service(
+        |c| {
+            c.service(route::task::commit_new);
+            c.service(route::task::complete);
+            route::reward::configure(c);
+            route::goal::configure(c);
+        },
+        &pool
+    ).await;
+    let req = |method: Method, uri: String| test::TestRequest::with_header("content-type", "text/plain")
+        .header("year", "2021")
+        .header("month", "1")
+        .header("day", "1")
+        .uri(&uri)
+        .method(method)
+        .cookie(session_cookie.clone());
+    let deposit_req = |goal_id: i32, pts: i32| req(Method::POST, format!("/goal/{}/deposit", goal_id))
+        .set_json(&GoalDeposit{pts})
+        .to_request();
+
+    println!("Earn 14 pts today, which is a rate of 1 pt a day");
+    let earn = |bspts: i32| req(Method::POST, "/task".to_string())
+        .set_json(&NewTask {
+            name: "TaskName".to_string(),
+            description: "".to_string(),
+            bspts,
+            frequency: TaskInterval::Days{every: 1},
+            icon: TaskIcon::default(),
+            penalty: PenaltyPolicy::None,
+            streak_bonus: StreakBonus::None,
+            rrule: None,
+            shared: false,
+        })
+        .to_request();
+    let task: Task = test::read_body_json(test::call_service(&mut app, earn(14)).await).await;
+    let resp = test::call_service(&mut app, req(Method::POST, format!("/task/complete/{}", task.id)).to_request()).await;
+    assert!(resp.status().is_success());
+
+    println!("Pin a reward that costs 20 as a goal");
+    let reward: Reward = test::read_body_json(test::call_service(&mut app, req(Method::POST, "/reward".to_string())
+        .set_json(&NewReward {
+            name: "Bike".to_string(),
+            description: "".to_string(),
+            bspts: 20,
+            icon: RewardIcon::default(),
+            cooldown_days: 0,
+            limit: RedemptionLimit::None,
+            allow_overdraft: false,
+            price_increment: 0,
+            price_decay: 0,
+            shared: false,
+        })
+        .to_request()
+    ).await).await;
+    let pin = |reward_id: i32| req(Method::POST, "/goal".to_string()).set_json(&NewGoal{reward_id}).to_request();
+    let goal: Goal = test::read_body_json(test::call_service(&mut app, pin(reward.id)).await).await;
+    assert_eq!((goal.saved, goal.target, goal.daily_rate), (0, 20, 1.0));
+    assert_eq!(goal.projected_on, NaiveDate::from_ymd_opt(2021, 1, 21));
+    let resp = test::call_service(&mut app, pin(reward.id)).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    println!("Only points the user has can be set aside, and no more than the goal needs");
+    let resp = test::call_service(&mut app, deposit_req(goal.id, 0)).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let resp = test::call_service(&mut app, deposit_req(goal.id, 15)).await;
+    let error: ApiError = test::read_body_json(resp).await;
+    assert!(matches!(error, ApiError::InsufficientPoints(_)));
+    let goal: Goal = test::read_body_json(test::call_service(&mut app, deposit_req(goal.id, 10)).await).await;
+    assert_eq!(goal.saved, 10);
+    assert_eq!(goal.percent_saved(), 50);
+    assert_eq!(goal.projected_on, NaiveDate::from_ymd_opt(2021, 1, 11));
+    let resp = test::call_service(&mut app, deposit_req(goal.id, 11)).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    println!("A reward that's a goal can't be archived");
+    let resp = test::call_service(&mut app, req(Method::DELETE, format!("/reward/{}", reward.id)).to_request()).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    println!("Cancelling the goal gives its points back");
+    let pts: i32 = test::read_body_json(
+        test::call_service(&mut app, req(Method::DELETE, format!("/goal/{}", goal.id)).to_request()).await
+    ).await;
+    assert_eq!(pts, 14);
+    let goals: Vec<Goal> = test::read_body_json(test::call_service(&mut app, req(Method::GET, "/goal".to_string()).to_request()).await).await;
+    assert!(goals.is_empty());
+
+    println!("The saved points are spent when the reward is taken");
+    let goal: Goal = test::read_body_json(test::call_service(&mut app, pin(reward.id)).await).await;
+    test::call_service(&mut app, deposit_req(goal.id, 10)).await;
+    let resp = test::call_service(&mut app, req(Method::POST, format!("/reward/do/{}", reward.id)).to_request()).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+    let goals: Vec<Goal> = test::read_body_json(test::call_service(&mut app, req(Method::GET, "/goal".to_string()).to_request()).await).await;
+    assert_eq!(goals[0].saved, 10);
+    let task: Task = test::read_body_json(test::call_service(&mut app, earn(6)).await).await;
+    test::call_service(&mut app, req(Method::POST, format!("/task/complete/{}", task.id)).to_request()).await;
+    let pts: i32 = test::read_body_json(
+        test::call_service(&mut app, req(Method::POST, format!("/reward/do/{}", reward.id)).to_request()).await
+    ).await;
+    assert_eq!(pts, 0);
+    let goals: Vec<Goal> = test::read_body_json(test::call_service(&mut app, req(Method::GET, "/goal".to_string()).to_request()).await).await;
+    assert!(goals.is_empty());
+}
\ No newline at end of file
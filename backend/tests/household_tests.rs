@@ -0,0 +1,177 @@
+mod setup;
+
+use backend_lib::*;
+use actix_web::{self, test, http::{Method, StatusCode, Cookie}};
+use data::{task::*, reward::*, goal::*, household::*, user::NewUser, error::ApiError, icon::{TaskIcon, RewardIcon}};
+use chrono::NaiveDate;
+use setup::*;
+
+#[actix_rt::test]
+async fn sharing_a_household() {
+    println!("Setup household test");
+    let owner = make_user("household_owner");
+    let member = make_user("household_member");
+    let pool = get_connection_pool();
+    let owner_cookie = login(&owner, &pool).await.expect("Failed to login");
+    let member_cookie = login(&member, &pool).await.expect("Failed to login");
+    let mut app = make_service(
+        |c| {
+            route::task::configure(c);
+            route::reward::configure(c);
+            route::household::configure(c);
+            route::goal::configure(c);
+        },
+        &pool
+    ).await;
+    let req = |method: Method, uri: &str, cookie: &Cookie<'static>| test::TestRequest::with_header("content-type", "text/plain")
+        .header("year", "2021")
+        .header("month", "1")
+        .header("day", "1")
+        .uri(uri)
+        .method(method)
+        .cookie(cookie.clone());
+    let new_task = |shared: bool| NewTask {
+        name: "Dishes".to_string(),
+        description: "".to_string(),
+        bspts: 5,
+        frequency: TaskInterval::Days{every: 1},
+        icon: TaskIcon::default(),
+        penalty: PenaltyPolicy::None,
+        streak_bonus: StreakBonus::None,
+        rrule: None,
+        shared,
+    };
+
+    println!("Sharing a task without a household isn't valid");
+    let resp = test::call_service(&mut app, req(Method::POST, "/task", &member_cookie).set_json(&new_task(true)).to_request()).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    match test::read_body_json(resp).await {
+        ApiError::Validation{fields, ..} => assert_eq!(fields[0].field, "shared"),
+        error => panic!("Expected a validation error, got {:?}", error),
+    }
+
+    println!("Start a household, and only one at a time");
+    let start = |cookie: &Cookie<'static>| req(Method::POST, "/household", cookie)
+        .set_json(&NewHousehold{name: "The Flat".to_string()})
+        .to_request();
+    let household: Household = test::read_body_json(test::call_service(&mut app, start(&owner_cookie)).await).await;
+    assert_eq!(household.invite_code.len(), 8);
+    assert_eq!(household.members.len(), 1);
+    let resp = test::call_service(&mut app, start(&owner_cookie)).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    println!("Join with the invite code, which isn't case sensitive");
+    let join = |invite_code: &str| req(Method::POST, "/household/join", &member_cookie)
+        .set_json(&JoinHousehold{invite_code: invite_code.to_string()})
+        .to_request();
+    let resp = test::call_service(&mut app, join("NOTACODE")).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    let joined: Household = test::read_body_json(
+        test::call_service(&mut app, join(&household.invite_code.to_lowercase())).await
+    ).await;
+    assert_eq!(joined.id, household.id);
+    assert_eq!(joined.members.len(), 2);
+
+    println!("Only the tasks and rewards the owner shares can be seen by the member");
+    let shared_task: Task = test::read_body_json(test::call_service(&mut app,
+        req(Method::POST, "/task", &owner_cookie).set_json(&new_task(true)).to_request()
+    ).await).await;
+    assert_eq!(shared_task.household_id, Some(household.id));
+    let resp = test::call_service(&mut app, req(Method::POST, "/task", &owner_cookie).set_json(&new_task(false)).to_request()).await;
+    assert!(resp.status().is_success());
+    let shared_reward: Reward = test::read_body_json(test::call_service(&mut app, req(Method::POST, "/reward", &owner_cookie)
+        .set_json(&NewReward {
+            name: "Pizza".to_string(),
+            description: "".to_string(),
+            bspts: 2,
+            icon: RewardIcon::default(),
+            cooldown_days: 0,
+            limit: RedemptionLimit::None,
+            allow_overdraft: false,
+            price_increment: 0,
+            price_decay: 0,
+            shared: true,
+        })
+        .to_request()
+    ).await).await;
+    let member_todo: Vec<Task> = test::read_body_json(test::call_service(&mut app, req(Method::GET, "/task/todo", &member_cookie).to_request()).await).await;
+    assert_eq!(member_todo.iter().map(|t| t.id).collect::<Vec<i32>>(), vec![shared_task.id]);
+    let member_rewards: Vec<Reward> = test::read_body_json(test::call_service(&mut app, req(Method::GET, "/reward", &member_cookie).to_request()).await).await;
+    assert_eq!(member_rewards.iter().map(|r| r.id).collect::<Vec<i32>>(), vec![shared_reward.id]);
+
+    println!("The member completes the shared task and gets its points");
+    let resp = test::call_service(&mut app, req(Method::POST, &format!("/task/complete/{}", shared_task.id), &member_cookie).to_request()).await;
+    assert!(resp.status().is_success());
+    let resp = test::call_service(&mut app, req(Method::POST, &format!("/reward/do/{}", shared_reward.id), &member_cookie).to_request()).await;
+    let member_pts: i32 = test::read_body_json(resp).await;
+    assert_eq!(member_pts, 3);
+    let household: Option<Household> = test::read_body_json(test::call_service(&mut app, req(Method::GET, "/household", &owner_cookie).to_request()).await).await;
+    // Members are listed by name, so the member comes before the owner
+    let pts: Vec<i32> = household.expect("The owner should be in a household").members.iter()
+        .map(|m| m.bspts)
+        .collect();
+    assert_eq!(pts, vec![3, 0]);
+
+    println!("Only the member who completed the shared task can undo it");
+    // The route always uses the server's date, so this undoes it on the day it was completed directly
+    let uncomplete = |user: &NewUser| {
+        let conn = pool.get().expect("Failed to get database connection");
+        let q_user = query::user::login_user(user.clone(), &conn).expect("Failed to get the user");
+        query::task::uncomplete_task(shared_task.id, &q_user, &conn, NaiveDate::from_ymd_opt(2021, 1, 1).unwrap())
+            .map_err(|e| e.as_response_error().status_code())
+    };
+    assert_eq!(uncomplete(&owner).err(), Some(StatusCode::FORBIDDEN));
+    assert!(uncomplete(&member).is_ok());
+    let resp = test::call_service(&mut app, req(Method::POST, &format!("/task/complete/{}", shared_task.id), &member_cookie).to_request()).await;
+    assert!(resp.status().is_success());
+
+    println!("Only the owner can change, archive or delete what they share");
+    let task_uri = format!("/task/{}", shared_task.id);
+    for shared in &[false, true] {
+        let resp = test::call_service(&mut app, req(Method::PUT, &task_uri, &member_cookie).set_json(&new_task(*shared)).to_request()).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+    let resp = test::call_service(&mut app, req(Method::DELETE, &task_uri, &member_cookie).to_request()).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let resp = test::call_service(&mut app, req(Method::DELETE, &format!("/reward/{}", shared_reward.id), &member_cookie).to_request()).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let resp = test::call_service(&mut app, req(Method::DELETE, &task_uri, &owner_cookie).to_request()).await;
+    assert!(resp.status().is_success());
+    for (method, uri) in [
+        (Method::POST, format!("/task/restore/{}", shared_task.id)),
+        (Method::DELETE, format!("/task/purge/{}", shared_task.id)),
+    ] {
+        let resp = test::call_service(&mut app, req(method, &uri, &member_cookie).to_request()).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+    let resp = test::call_service(&mut app, req(Method::POST, &format!("/task/restore/{}", shared_task.id), &owner_cookie).to_request()).await;
+    assert!(resp.status().is_success());
+
+    println!("The member saving for a shared reward doesn't stop the owner archiving it, and gets their points back");
+    let goal: Goal = test::read_body_json(test::call_service(&mut app,
+        req(Method::POST, "/goal", &member_cookie).set_json(&NewGoal{reward_id: shared_reward.id}).to_request()
+    ).await).await;
+    let resp = test::call_service(&mut app,
+        req(Method::POST, &format!("/goal/{}/deposit", goal.id), &member_cookie).set_json(&GoalDeposit{pts: 2}).to_request()
+    ).await;
+    assert!(resp.status().is_success());
+    let resp = test::call_service(&mut app, req(Method::DELETE, &format!("/reward/{}", shared_reward.id), &owner_cookie).to_request()).await;
+    assert!(resp.status().is_success());
+    let goals: Vec<Goal> = test::read_body_json(test::call_service(&mut app, req(Method::GET, "/goal", &member_cookie).to_request()).await).await;
+    assert!(goals.is_empty());
+    let household: Option<Household> = test::read_body_json(test::call_service(&mut app, req(Method::GET, "/household", &member_cookie).to_request()).await).await;
+    let pts: Vec<i32> = household.expect("The member should be in a household").members.iter()
+        .map(|m| m.bspts)
+        .collect();
+    assert_eq!(pts, vec![3, 0]);
+
+    println!("After leaving, the member can't see the shared task");
+    let resp = test::call_service(&mut app, req(Method::POST, "/household/leave", &member_cookie).to_request()).await;
+    assert!(resp.status().is_success());
+    let resp = test::call_service(&mut app, req(Method::GET, &format!("/task/{}", shared_task.id), &member_cookie).to_request()).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    let household: Option<Household> = test::read_body_json(test::call_service(&mut app, req(Method::GET, "/household", &member_cookie).to_request()).await).await;
+    assert_eq!(household, None);
+    let resp = test::call_service(&mut app, req(Method::POST, "/household/leave", &member_cookie).to_request()).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
\ No newline at end of file
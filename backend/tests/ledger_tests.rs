@@ -0,0 +1,143 @@
+mod setup;
+
+use backend_lib::*;
+use actix_web::{self, test, http::{Method, StatusCode}};
+use data::{user::User, task::*, reward::*, ledger::*, icon::{TaskIcon, RewardIcon}};
+use setup::*;
+
+#[actix_rt::test]
+async fn ledger_records_points() {
+    println!("Setup ledger test");
+    let user = make_user("ledger");
+    let pool = get_connection_pool();
+    let session_cookie = login(&user, &pool).await.expect("Failed to login");
+    let mut app = make_service(
+        |c| {
+            c.service(route::user::get_user);
+            c.service(route::task::commit_new);
+            c.service(route::task::complete);
+            c.service(route::reward::new);
+            c.service(route::reward::did_it);
+            c.service(route::ledger::get_page);
+        },
+        &pool
+    ).await;
+    let task_points = 3;
+    let reward_points = 2;
+
+    println!("Create and complete a task");
+    let new_task = NewTask {
+        name: "TaskName".to_string(),
+        description: "".to_string(),
+        bspts: task_points,
+        frequency: TaskInterval::Days{every: 1},
+        icon: TaskIcon::default(),
+        penalty: PenaltyPolicy::None,
+        streak_bonus: StreakBonus::None,
+        rrule: None,
+        shared: false,
+    };
+    let task_req = test::TestRequest::with_header("content-type", "text/plain")
+        .header("year", "2021")
+        .header("month", "1")
+        .header("day", "1")
+        .uri("/task")
+        .method(Method::POST)
+        .cookie(session_cookie.clone())
+        .set_json(&new_task)
+        .to_request();
+    let task_resp = test::call_service(&mut app, task_req).await;
+    assert!(task_resp.status().is_success());
+    let task: Task = test::read_body_json(task_resp).await;
+    let complete_req = test::TestRequest::with_header("content-type", "text/plain")
+        .header("year", "2021")
+        .header("month", "1")
+        .header("day", "1")
+        .uri(format!("/task/complete/{}", task.id).as_str())
+        .method(Method::POST)
+        .cookie(session_cookie.clone())
+        .to_request();
+    let complete_resp = test::call_service(&mut app, complete_req).await;
+    assert!(complete_resp.status().is_success());
+
+    println!("Create and take a reward");
+    let new_reward = NewReward {
+        name: "RewardName".to_string(),
+        description: "".to_string(),
+        bspts: reward_points,
+        icon: RewardIcon::default(),
+        cooldown_days: 0,
+        limit: RedemptionLimit::None,
+        allow_overdraft: false,
+        price_increment: 0,
+        price_decay: 0,
+        shared: false,
+    };
+    let reward_req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/reward")
+        .method(Method::POST)
+        .cookie(session_cookie.clone())
+        .set_json(&new_reward)
+        .to_request();
+    let reward_resp = test::call_service(&mut app, reward_req).await;
+    assert!(reward_resp.status().is_success());
+    let reward: Reward = test::read_body_json(reward_resp).await;
+    let do_req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri(format!("/reward/do/{}", reward.id).as_str())
+        .method(Method::POST)
+        .cookie(session_cookie.clone())
+        .to_request();
+    let do_resp = test::call_service(&mut app, do_req).await;
+    assert!(do_resp.status().is_success());
+
+    println!("The ledger should hold both changes, newest first");
+    let ledger_req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/ledger")
+        .method(Method::GET)
+        .cookie(session_cookie.clone())
+        .to_request();
+    let ledger_resp = test::call_service(&mut app, ledger_req).await;
+    println!("{:#?}", ledger_resp);
+    assert!(ledger_resp.status().is_success());
+    let ledger: LedgerPage = test::read_body_json(ledger_resp).await;
+    assert_eq!(ledger.total, 2);
+    let sources: Vec<(LedgerSource, Option<i32>, i32)> = ledger.entries.iter()
+        .map(|e| (e.source, e.source_id, e.amount))
+        .collect();
+    assert_eq!(sources, vec![
+        (LedgerSource::RewardRedemption, Some(reward.id), -reward_points),
+        (LedgerSource::TaskCompletion, Some(task.id), task_points),
+    ]);
+
+    println!("The ledger should add up to the user's points");
+    let get_user_req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/user")
+        .method(Method::GET)
+        .cookie(session_cookie.clone())
+        .to_request();
+    let get_user_resp = test::call_service(&mut app, get_user_req).await;
+    let user: User = test::read_body_json(get_user_resp).await;
+    let ledger_total: i32 = ledger.entries.iter().map(|e| e.amount).sum();
+    assert_eq!(user.bspts, ledger_total);
+
+    println!("Get the second page with one entry per page");
+    let page_req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/ledger?page=1&per_page=1")
+        .method(Method::GET)
+        .cookie(session_cookie.clone())
+        .to_request();
+    let page_resp = test::call_service(&mut app, page_req).await;
+    assert!(page_resp.status().is_success());
+    let page: LedgerPage = test::read_body_json(page_resp).await;
+    assert_eq!(page.entries.len(), 1);
+    assert_eq!(page.entries[0].source, LedgerSource::TaskCompletion);
+
+    println!("Pages that are too big are refused");
+    let bad_req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/ledger?per_page=1000")
+        .method(Method::GET)
+        .cookie(session_cookie)
+        .to_request();
+    let bad_resp = test::call_service(&mut app, bad_req).await;
+    assert_eq!(bad_resp.status(), StatusCode::BAD_REQUEST);
+}
\ No newline at end of file
@@ -1,10 +1,23 @@
 mod setup;
 
 use backend_lib::*;
-use actix_web::{self, test, http::Method};
-use data::{user::User, reward::*, icon::RewardIcon};
+use actix_web::{self, test, http::{Method, StatusCode}};
+use data::{user::{User, NewUser}, reward::*, icon::RewardIcon, error::ApiError};
+use chrono::NaiveDate;
+use diesel::{RunQueryDsl, sql_types::Integer};
 use setup::*;
 
+/// Takes the reward on the given day in January 2021. The route always uses the server's date,
+/// so this calls the query directly to take it on another day
+fn redeem_on(pool: &PgPool, user: &NewUser, reward_id: i32, day: u32) -> Result<i32, ApiError> {
+    let conn = pool.get().expect("Failed to get database connection");
+    let q_user = query::user::login_user(user.clone(), &conn).expect("Failed to get the user");
+    let today = NaiveDate::from_ymd_opt(2021, 1, day).unwrap();
+    query::reward::redeem_reward(reward_id, &q_user, &conn, today).map_err(|e| {
+        e.as_error::<error::ErrorResponse>().expect("Expected an API error").0.clone()
+    })
+}
+
 #[actix_rt::test]
 pub async fn get_rewards() {
     // create a new user and log in
@@ -34,6 +47,12 @@ pub async fn add_reward() {
         description: "".to_string(),
         bspts: 1,
         icon: RewardIcon::default(),
+        cooldown_days: 0,
+        limit: RedemptionLimit::None,
+        allow_overdraft: false,
+        price_increment: 0,
+        price_decay: 0,
+        shared: false,
     };
     let req = test::TestRequest::with_header("content-type", "text/plain")
         .uri("/reward")
@@ -68,6 +87,12 @@ async fn update_reward() {
         description: "".to_string(),
         bspts: 1,
         icon: RewardIcon::default(),
+        cooldown_days: 0,
+        limit: RedemptionLimit::None,
+        allow_overdraft: false,
+        price_increment: 0,
+        price_decay: 0,
+        shared: false,
     };
     let set_req = test::TestRequest::with_header("content-type", "text/plain")
         .uri("/reward")
@@ -121,6 +146,12 @@ async fn delete_reward() {
         description: "".to_string(),
         bspts: 1,
         icon: RewardIcon::default(),
+        cooldown_days: 0,
+        limit: RedemptionLimit::None,
+        allow_overdraft: false,
+        price_increment: 0,
+        price_decay: 0,
+        shared: false,
     };
     let set_req = test::TestRequest::with_header("content-type", "text/plain")
         .uri("/reward")
@@ -188,6 +219,12 @@ async fn do_reward() {
         description: "".to_string(),
         bspts: reward_points,
         icon: RewardIcon::default(),
+        cooldown_days: 0,
+        limit: RedemptionLimit::None,
+        allow_overdraft: true,
+        price_increment: 0,
+        price_decay: 0,
+        shared: false,
     };
     let set_req = test::TestRequest::with_header("content-type", "text/plain")
         .uri("/reward")
@@ -221,4 +258,400 @@ async fn do_reward() {
     assert!(get_user_resp.status().is_success());
     let user: User = test::read_body_json(get_user_resp).await;
     assert_eq!(user.bspts, -reward_points, "BS Pts were not subtracted");
+}
+#[actix_rt::test]
+async fn other_users_reward_is_not_found() {
+    println!("Setup cross user test");
+    let owner = make_user("reward_owner");
+    let intruder = make_user("reward_intruder");
+    let pool = get_connection_pool();
+    let owner_cookie = login(&owner, &pool).await.expect("Failed to login owner");
+    let intruder_cookie = login(&intruder, &pool).await.expect("Failed to login intruder");
+    let mut app = make_service(
+        |c| {
+            c.service(route::user::get_user);
+            c.service(route::reward::get_by_id);
+            c.service(route::reward::new);
+            c.service(route::reward::did_it);
+            c.service(route::reward::update);
+            c.service(route::reward::delete);
+        },
+        &pool
+    ).await;
+
+    println!("Create the reward as the owner");
+    let reward = NewReward {
+        name: "RewardName".to_string(),
+        description: "".to_string(),
+        bspts: 1,
+        icon: RewardIcon::default(),
+        cooldown_days: 0,
+        limit: RedemptionLimit::None,
+        allow_overdraft: false,
+        price_increment: 0,
+        price_decay: 0,
+        shared: false,
+    };
+    let set_req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/reward")
+        .method(Method::POST)
+        .cookie(owner_cookie.clone())
+        .set_json(&reward)
+        .to_request();
+    let set_resp = test::call_service(&mut app, set_req).await;
+    assert!(set_resp.status().is_success());
+    let saved_reward: Reward = test::read_body_json(set_resp).await;
+    let reward_uri = format!("/reward/{}", saved_reward.id);
+    let do_uri = format!("/reward/do/{}", saved_reward.id);
+
+    println!("The intruder should not be able to read, edit, take or delete the reward");
+    let requests = vec![
+        (reward_uri.as_str(), Method::GET),
+        (reward_uri.as_str(), Method::PUT),
+        (do_uri.as_str(), Method::POST),
+        (reward_uri.as_str(), Method::DELETE),
+    ];
+    for (uri, method) in requests {
+        let req = test::TestRequest::with_header("content-type", "text/plain")
+            .uri(uri)
+            .method(method.clone())
+            .cookie(intruder_cookie.clone())
+            .set_json(&reward)
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        println!("{:#?}", resp);
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND, "{} {} should not be found", method, uri);
+    }
+
+    println!("Make sure the intruder's points were not touched");
+    let get_user_req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/user")
+        .method(Method::GET)
+        .cookie(intruder_cookie.clone())
+        .to_request();
+    let get_user_resp = test::call_service(&mut app, get_user_req).await;
+    assert!(get_user_resp.status().is_success());
+    let user: User = test::read_body_json(get_user_resp).await;
+    assert_eq!(user.bspts, 0);
+
+    println!("The owner can still get the reward");
+    let get_req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri(reward_uri.as_str())
+        .method(Method::GET)
+        .cookie(owner_cookie)
+        .to_request();
+    let get_resp = test::call_service(&mut app, get_req).await;
+    assert!(get_resp.status().is_success());
+    let body: Reward = test::read_body_json(get_resp).await;
+    assert_eq!(body.name, saved_reward.name);
+}
+
+
+#[actix_rt::test]
+async fn archived_reward_can_be_restored_or_purged() {
+    let user = make_user("archived_reward");
+    let pool = get_connection_pool();
+    let session_cookie = login(&user, &pool).await.expect("Failed to login");
+    let mut app = make_service(route::reward::configure, &pool).await;
+    let req = |method: Method, uri: String| test::TestRequest::with_header("content-type", "text/plain")
+        .uri(&uri)
+        .method(method)
+        .cookie(session_cookie.clone())
+        .to_request();
+
+    println!("Create a reward and archive it");
+    let new_req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/reward")
+        .method(Method::POST)
+        .cookie(session_cookie.clone())
+        .set_json(&NewReward {
+            name: "Ice cream".to_string(),
+            description: "".to_string(),
+            bspts: 4,
+            icon: RewardIcon::default(),
+            cooldown_days: 0,
+            limit: RedemptionLimit::None,
+            allow_overdraft: false,
+            price_increment: 0,
+            price_decay: 0,
+            shared: false,
+        })
+        .to_request();
+    let reward: Reward = test::read_body_json(test::call_service(&mut app, new_req).await).await;
+    let resp = test::call_service(&mut app, req(Method::DELETE, format!("/reward/{}", reward.id))).await;
+    assert!(resp.status().is_success());
+
+    println!("It's only in the archive, and can't be redeemed");
+    let archived: Vec<Reward> = test::read_body_json(
+        test::call_service(&mut app, req(Method::GET, "/reward/archived".to_string())).await
+    ).await;
+    assert_eq!(archived.len(), 1);
+    assert!(archived[0].archived_at.is_some());
+    let resp = test::call_service(&mut app, req(Method::POST, format!("/reward/do/{}", reward.id))).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    println!("Restore it to the list of rewards");
+    let resp = test::call_service(&mut app, req(Method::POST, format!("/reward/restore/{}", reward.id))).await;
+    assert!(resp.status().is_success());
+    let restored: Reward = test::read_body_json(resp).await;
+    assert_eq!(restored.archived_at, None);
+    let rewards: Vec<Reward> = test::read_body_json(
+        test::call_service(&mut app, req(Method::GET, "/reward".to_string())).await
+    ).await;
+    assert!(rewards.iter().any(|r| r.id == reward.id));
+
+    println!("Only an archived reward can be purged");
+    let resp = test::call_service(&mut app, req(Method::DELETE, format!("/reward/purge/{}", reward.id))).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    test::call_service(&mut app, req(Method::DELETE, format!("/reward/{}", reward.id))).await;
+    let resp = test::call_service(&mut app, req(Method::DELETE, format!("/reward/purge/{}", reward.id))).await;
+    assert!(resp.status().is_success());
+    let resp = test::call_service(&mut app, req(Method::GET, format!("/reward/{}", reward.id))).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_rt::test]
+async fn taking_a_reward_is_recorded() {
+    let user = make_user("taking_a_reward_is_recorded");
+    let pool = get_connection_pool();
+    let session_cookie = login(&user, &pool).await.expect("Failed to login");
+    let mut app = make_service(route::reward::configure, &pool).await;
+    let req = |method: Method, uri: String, day: u32| test::TestRequest::with_header("content-type", "text/plain")
+        .header("year", "2021")
+        .header("month", "1")
+        .header("day", day.to_string())
+        .uri(&uri)
+        .method(method)
+        .cookie(session_cookie.clone())
+        .to_request();
+
+    println!("Create a reward, which hasn't been taken yet");
+    let new_req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/reward")
+        .method(Method::POST)
+        .cookie(session_cookie.clone())
+        .set_json(&NewReward {
+            name: "Coffee".to_string(),
+            description: "".to_string(),
+            bspts: 2,
+            icon: RewardIcon::default(),
+            cooldown_days: 0,
+            limit: RedemptionLimit::None,
+            allow_overdraft: true,
+            price_increment: 0,
+            price_decay: 0,
+            shared: false,
+        })
+        .to_request();
+    let reward: Reward = test::read_body_json(test::call_service(&mut app, new_req).await).await;
+    assert_eq!((reward.last_redeemed, reward.redeemed_today), (None, 0));
+
+    println!("Take it twice on the 1st and once on the 3rd");
+    for day in &[1, 1, 3] {
+        redeem_on(&pool, &user, reward.id, *day).expect("Failed to take the reward");
+    }
+    assert_eq!(redeem_on(&pool, &user, reward.id, 3), Ok(-8));
+
+    println!("The list of rewards shows when it was last taken and how often today");
+    let rewards: Vec<Reward> = test::read_body_json(
+        test::call_service(&mut app, req(Method::GET, "/reward".to_string(), 3)).await
+    ).await;
+    assert_eq!(rewards[0].last_redeemed, NaiveDate::from_ymd_opt(2021, 1, 3));
+    assert_eq!(rewards[0].redeemed_today, 2);
+    let rewards: Vec<Reward> = test::read_body_json(
+        test::call_service(&mut app, req(Method::GET, "/reward".to_string(), 4)).await
+    ).await;
+    assert_eq!(rewards[0].redeemed_today, 0);
+
+    println!("Every time it was taken is in its history, newest first");
+    let history: Vec<RewardRedemption> = test::read_body_json(
+        test::call_service(&mut app, req(Method::GET, format!("/reward/{}/history", reward.id), 4)).await
+    ).await;
+    assert_eq!(history.len(), 4);
+    assert_eq!(history[0].redeemed_on, NaiveDate::from_ymd_opt(2021, 1, 3).unwrap());
+    assert!(history.iter().all(|r| r.reward_name == "Coffee" && r.bspts == 2));
+
+    println!("The rewards taken in a range of days can be listed");
+    let redemptions: Vec<RewardRedemption> = test::read_body_json(
+        test::call_service(&mut app, req(Method::GET, "/redemptions?from=2021-01-01&to=2021-01-02".to_string(), 4)).await
+    ).await;
+    assert_eq!(redemptions.len(), 2);
+    let resp = test::call_service(&mut app, req(Method::GET, "/redemptions?from=2021-01-03&to=2021-01-01".to_string(), 4)).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_rt::test]
+async fn reward_rules_are_enforced() {
+    let user = make_user("reward_rules_are_enforced");
+    let pool = get_connection_pool();
+    let session_cookie = login(&user, &pool).await.expect("Failed to login");
+    let mut app = make_service(route::reward::configure, &pool).await;
+    // The 4th of January 2021 is a Monday
+    let req = |method: Method, uri: String, day: u32| test::TestRequest::with_header("content-type", "text/plain")
+        .header("year", "2021")
+        .header("month", "1")
+        .header("day", day.to_string())
+        .uri(&uri)
+        .method(method)
+        .cookie(session_cookie.clone())
+        .to_request();
+    let new_reward = |name: &str, cooldown_days: u32, limit: RedemptionLimit, allow_overdraft: bool| {
+        test::TestRequest::with_header("content-type", "text/plain")
+            .uri("/reward")
+            .method(Method::POST)
+            .cookie(session_cookie.clone())
+            .set_json(&NewReward {
+                name: name.to_string(),
+                description: "".to_string(),
+                bspts: 1,
+                icon: RewardIcon::default(),
+                cooldown_days,
+                limit,
+                allow_overdraft,
+                price_increment: 0,
+                price_decay: 0,
+                shared: false,
+            })
+            .to_request()
+    };
+
+    println!("A limit of zero times isn't valid");
+    let resp = test::call_service(&mut app, new_reward("Never", 0, RedemptionLimit::PerDay{times: 0}, true)).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    println!("Nor is one of more than the most allowed");
+    let too_often = RedemptionLimit::PerWeek{times: MAX_LIMIT_TIMES + 1};
+    let resp = test::call_service(&mut app, new_reward("Always", 0, too_often, true)).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    println!("A reward with a cooldown of 2 days can't be taken the day after");
+    let cake: Reward = test::read_body_json(
+        test::call_service(&mut app, new_reward("Cake", 2, RedemptionLimit::None, true)).await
+    ).await;
+    redeem_on(&pool, &user, cake.id, 4).expect("Failed to take the cake");
+    assert!(matches!(redeem_on(&pool, &user, cake.id, 5), Err(ApiError::CoolingDown(_))));
+    let rewards: Vec<Reward> = test::read_body_json(
+        test::call_service(&mut app, req(Method::GET, "/reward".to_string(), 5)).await
+    ).await;
+    assert_eq!(rewards[0].available_on, NaiveDate::from_ymd_opt(2021, 1, 6));
+    redeem_on(&pool, &user, cake.id, 6).expect("Failed to take the cake once it cooled down");
+
+    println!("A reward limited to twice a week can be taken again once the week is over");
+    let movie: Reward = test::read_body_json(
+        test::call_service(&mut app, new_reward("Movie", 0, RedemptionLimit::PerWeek{times: 2}, true)).await
+    ).await;
+    for day in &[4, 6] {
+        redeem_on(&pool, &user, movie.id, *day).expect("Failed to take the movie");
+    }
+    assert!(matches!(redeem_on(&pool, &user, movie.id, 10), Err(ApiError::LimitReached(_))));
+    redeem_on(&pool, &user, movie.id, 11).expect("Failed to take the movie the next week");
+
+    println!("A reward without overdraft can't be taken when the user is short of points");
+    let book: Reward = test::read_body_json(
+        test::call_service(&mut app, new_reward("Book", 0, RedemptionLimit::None, false)).await
+    ).await;
+    assert!(matches!(redeem_on(&pool, &user, book.id, 11), Err(ApiError::InsufficientPoints(_))));
+    let history: Vec<RewardRedemption> = test::read_body_json(
+        test::call_service(&mut app, req(Method::GET, format!("/reward/{}/history", book.id), 11)).await
+    ).await;
+    assert!(history.is_empty());
+
+    println!("Taking a reward goes by the server's date, so sending a later one doesn't skip the cooldown");
+    let snack: Reward = test::read_body_json(
+        test::call_service(&mut app, new_reward("Snack", 2, RedemptionLimit::None, true)).await
+    ).await;
+    let take_in_year = |year: &str| test::TestRequest::with_header("content-type", "text/plain")
+        .header("year", year)
+        .header("month", "1")
+        .header("day", "1")
+        .uri(&format!("/reward/do/{}", snack.id))
+        .method(Method::POST)
+        .cookie(session_cookie.clone())
+        .to_request();
+    let resp = test::call_service(&mut app, take_in_year("2021")).await;
+    assert!(resp.status().is_success());
+    let resp = test::call_service(&mut app, take_in_year("2100")).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+    let error: ApiError = test::read_body_json(resp).await;
+    assert!(matches!(error, ApiError::CoolingDown(_)));
+}
+
+#[actix_rt::test]
+async fn reward_price_rises_and_wears_off() {
+    let user = make_user("reward_price_rises_and_wears_off");
+    let pool = get_connection_pool();
+    let session_cookie = login(&user, &pool).await.expect("Failed to login");
+    let mut app = make_service(route::reward::configure, &pool).await;
+    let req = |method: Method, uri: String, day: u32| test::TestRequest::with_header("content-type", "text/plain")
+        .header("year", "2021")
+        .header("month", "1")
+        .header("day", day.to_string())
+        .uri(&uri)
+        .method(method)
+        .cookie(session_cookie.clone())
+        .to_request();
+    let new_reward = |price_increment: u32, price_decay: u32| {
+        test::TestRequest::with_header("content-type", "text/plain")
+            .uri("/reward")
+            .method(Method::POST)
+            .cookie(session_cookie.clone())
+            .set_json(&NewReward {
+                name: "Episode".to_string(),
+                description: "".to_string(),
+                bspts: 2,
+                icon: RewardIcon::default(),
+                cooldown_days: 0,
+                limit: RedemptionLimit::None,
+                allow_overdraft: true,
+                price_increment,
+                price_decay,
+                shared: false,
+            })
+            .to_request()
+    };
+    let price_on = |rewards: Vec<Reward>| rewards[0].current_price;
+
+    println!("A price that never wears off isn't valid");
+    let resp = test::call_service(&mut app, new_reward(3, 0)).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    println!("Nor is one that rises by more than the most allowed");
+    let resp = test::call_service(&mut app, new_reward(MAX_PRICE_INCREMENT + 1, MAX_PRICE_INCREMENT)).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    println!("Or wears off by more than the most allowed");
+    let resp = test::call_service(&mut app, new_reward(3, MAX_PRICE_DECAY + 1)).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    println!("The reward costs its base price until it's taken");
+    let reward: Reward = test::read_body_json(test::call_service(&mut app, new_reward(3, 1)).await).await;
+    let rewards = test::read_body_json(test::call_service(&mut app, req(Method::GET, "/reward".to_string(), 1)).await).await;
+    assert_eq!(price_on(rewards), 2);
+
+    println!("Each time it's taken it costs 3 more");
+    assert_eq!(redeem_on(&pool, &user, reward.id, 1), Ok(-2));
+    assert_eq!(redeem_on(&pool, &user, reward.id, 1), Ok(-7));
+    let rewards = test::read_body_json(test::call_service(&mut app, req(Method::GET, "/reward".to_string(), 1)).await).await;
+    assert_eq!(price_on(rewards), 8);
+
+    println!("The extra cost wears off by a point a day");
+    let rewards = test::read_body_json(test::call_service(&mut app, req(Method::GET, "/reward".to_string(), 2)).await).await;
+    assert_eq!(price_on(rewards), 6);
+    let rewards = test::read_body_json(test::call_service(&mut app, req(Method::GET, "/reward".to_string(), 4)).await).await;
+    assert_eq!(price_on(rewards), 2);
+
+    println!("The history records the price paid each time");
+    let history: Vec<RewardRedemption> = test::read_body_json(
+        test::call_service(&mut app, req(Method::GET, format!("/reward/{}/history", reward.id), 4)).await
+    ).await;
+    assert_eq!(history.iter().map(|r| r.bspts).collect::<Vec<i32>>(), vec![5, 2]);
+
+    println!("A price saved before increments were limited tops out instead of overflowing");
+    let conn = pool.get().expect("Failed to get database connection");
+    diesel::sql_query("UPDATE rewards SET price_increment = 2147483647, price_decay = 1 WHERE id = $1")
+        .bind::<Integer, _>(reward.id)
+        .execute(&conn)
+        .expect("Failed to raise the price increment");
+    let rewards = test::read_body_json(test::call_service(&mut app, req(Method::GET, "/reward".to_string(), 4)).await).await;
+    assert_eq!(price_on(rewards), i32::MAX);
 }
\ No newline at end of file
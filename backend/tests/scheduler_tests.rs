@@ -0,0 +1,119 @@
+mod setup;
+
+use backend_lib::*;
+use backend_lib::query::{rollover::{roll_over_due_users, roll_over_tasks}, user::{login_user, set_time_zone}};
+use actix_web::{self, test, http::Method};
+use chrono::NaiveDate;
+use data::{task::*, icon::TaskIcon};
+use setup::*;
+
+#[actix_rt::test]
+async fn scheduler_rolls_over_tasks() {
+    println!("Setup scheduler test");
+    let user = make_user("scheduler");
+    let pool = get_connection_pool();
+    let session_cookie = login(&user, &pool).await.expect("Failed to login");
+    let mut app = make_service(
+        |c| {
+            c.service(route::task::commit_new);
+            c.service(route::task::complete);
+            c.service(route::task::get_todo);
+        },
+        &pool
+    ).await;
+
+    println!("Create and complete a task on the first of the year");
+    let new_task = NewTask {
+        name: "TaskName".to_string(),
+        description: "".to_string(),
+        bspts: 1,
+        frequency: TaskInterval::Days{every: 1},
+        icon: TaskIcon::default(),
+        penalty: PenaltyPolicy::None,
+        streak_bonus: StreakBonus::None,
+        rrule: None,
+        shared: false,
+    };
+    let task_req = test::TestRequest::with_header("content-type", "text/plain")
+        .header("year", "2021")
+        .header("month", "1")
+        .header("day", "1")
+        .uri("/task")
+        .method(Method::POST)
+        .cookie(session_cookie.clone())
+        .set_json(&new_task)
+        .to_request();
+    let task_resp = test::call_service(&mut app, task_req).await;
+    assert!(task_resp.status().is_success());
+    let task: Task = test::read_body_json(task_resp).await;
+    let complete_req = test::TestRequest::with_header("content-type", "text/plain")
+        .header("year", "2021")
+        .header("month", "1")
+        .header("day", "1")
+        .uri(format!("/task/complete/{}", task.id).as_str())
+        .method(Method::POST)
+        .cookie(session_cookie.clone())
+        .to_request();
+    let complete_resp = test::call_service(&mut app, complete_req).await;
+    assert!(complete_resp.status().is_success());
+
+    println!("Roll over every user a few days later");
+    let conn = pool.get().unwrap();
+    let user_id = login_user(user.clone(), &conn).expect("Failed to get the user").id;
+    let today = NaiveDate::from_ymd_opt(2021, 1, 5).unwrap();
+    let now = today.and_hms_opt(12, 0, 0).unwrap().and_utc();
+    let rollovers = roll_over_due_users(&conn, now).expect("Failed to roll over");
+    let rollover = rollovers.iter().find(|r| r.user_id == user_id).expect("The user was not rolled over");
+    assert_eq!(rollover.rolled_over_on, today);
+    assert_eq!(rollover.tasks_reset, 1);
+    assert_eq!(rollover.tasks_missed, 0);
+
+    println!("Check that the task is back in todo");
+    let todo_req = test::TestRequest::with_header("content-type", "text/plain")
+        .header("year", "2021")
+        .header("month", "1")
+        .header("day", "5")
+        .uri("/task/todo")
+        .cookie(session_cookie.clone())
+        .to_request();
+    let todo_resp = test::call_service(&mut app, todo_req).await;
+    assert!(todo_resp.status().is_success());
+    let todo_tasks: Vec<Task> = test::read_body_json(todo_resp).await;
+    assert!(todo_tasks.iter().any(|t| t.id == task.id));
+
+    println!("Check that the user isn't rolled over twice on the same day");
+    let rollovers = roll_over_due_users(&conn, now).expect("Failed to roll over");
+    assert!(rollovers.iter().all(|r| r.user_id != user_id));
+
+    println!("Check that nothing is recorded when there's nothing to roll over");
+    let q_user = login_user(user.clone(), &conn).expect("Failed to get the user");
+    let (reset_tasks, rollover) = roll_over_tasks(&q_user, &conn, today).expect("Failed to roll over");
+    assert!(reset_tasks.is_empty());
+    assert!(rollover.is_none());
+
+    println!("Setup a user whose day starts 14 hours before UTC's");
+    let ahead_user = make_user("scheduler_ahead");
+    let ahead_cookie = login(&ahead_user, &pool).await.expect("Failed to login");
+    let q_ahead_user = login_user(ahead_user.clone(), &conn).expect("Failed to get the user");
+    let q_ahead_user = set_time_zone(q_ahead_user, "Pacific/Kiritimati", &conn).expect("Failed to set the time zone");
+    roll_over_tasks(&q_ahead_user, &conn, today).expect("Failed to roll over");
+    let ahead_req = |uri: String| test::TestRequest::with_header("content-type", "text/plain")
+        .header("year", "2021")
+        .header("month", "1")
+        .header("day", "5")
+        .uri(&uri)
+        .method(Method::POST)
+        .cookie(ahead_cookie.clone());
+    let ahead_task: Task = test::read_body_json(
+        test::call_service(&mut app, ahead_req("/task".to_string()).set_json(&new_task).to_request()).await
+    ).await;
+    let resp = test::call_service(&mut app, ahead_req(format!("/task/complete/{}", ahead_task.id)).to_request()).await;
+    assert!(resp.status().is_success());
+
+    println!("At noon UTC on the 5th it's already the 6th for them, so only they are rolled over");
+    let rollovers = roll_over_due_users(&conn, now).expect("Failed to roll over");
+    assert!(rollovers.iter().all(|r| r.user_id != user_id));
+    let rollover = rollovers.iter().find(|r| r.user_id == q_ahead_user.id).expect("The user ahead of UTC was not rolled over");
+    assert_eq!(rollover.rolled_over_on, NaiveDate::from_ymd_opt(2021, 1, 6).unwrap());
+    assert_eq!(rollover.tasks_reset, 1);
+}
\ No newline at end of file
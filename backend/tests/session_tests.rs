@@ -0,0 +1,100 @@
+mod setup;
+
+use backend_lib::*;
+use actix_web::{self, test, http::{Method, StatusCode}};
+use data::session::SessionInfo;
+use diesel::RunQueryDsl;
+use setup::*;
+
+/// Gets the status of a request for the user made with the session cookie
+async fn get_user_status(pool: &PgPool, ses: &actix_web::http::Cookie<'static>) -> StatusCode {
+    let mut app = make_service(|c| {c.service(route::user::get_user);}, pool).await;
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/user")
+        .cookie(ses.clone())
+        .to_request();
+    test::call_service(&mut app, req).await.status()
+}
+
+async fn get_sessions(pool: &PgPool, ses: &actix_web::http::Cookie<'static>) -> Vec<SessionInfo> {
+    let mut app = make_service(|c| {c.service(route::session::get_all);}, pool).await;
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/sessions")
+        .cookie(ses.clone())
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+    test::read_body_json(resp).await
+}
+
+#[actix_rt::test]
+async fn logout_ends_session() {
+    let user = make_user("logout");
+    let pool = get_connection_pool();
+    let session_cookie = login(&user, &pool).await.expect("Failed to login");
+    assert!(get_user_status(&pool, &session_cookie).await.is_success());
+
+    println!("Log out");
+    let mut app = make_service(|c| {c.service(route::session::logout);}, &pool).await;
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/logout")
+        .method(Method::POST)
+        .cookie(session_cookie.clone())
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+
+    println!("Check that the old cookie no longer works");
+    assert_eq!(get_user_status(&pool, &session_cookie).await, StatusCode::UNAUTHORIZED);
+}
+
+#[actix_rt::test]
+async fn revoke_other_session() {
+    let user = make_user("revoke_session");
+    let pool = get_connection_pool();
+    let laptop_cookie = login(&user, &pool).await.expect("Failed to login");
+    let phone_cookie = login(&user, &pool).await.expect("Failed to login");
+
+    println!("List the sessions from the laptop");
+    let sessions = get_sessions(&pool, &laptop_cookie).await;
+    assert_eq!(sessions.len(), 2);
+    let phone_session = sessions.iter().find(|s| !s.current).expect("Found no other session");
+    assert_eq!(sessions.iter().filter(|s| s.current).count(), 1);
+
+    println!("Revoke the phone's session");
+    let mut app = make_service(|c| {c.service(route::session::revoke);}, &pool).await;
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri(format!("/sessions/{}", phone_session.id).as_str())
+        .method(Method::DELETE)
+        .cookie(laptop_cookie.clone())
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+    assert_eq!(get_user_status(&pool, &phone_cookie).await, StatusCode::UNAUTHORIZED);
+    assert!(get_user_status(&pool, &laptop_cookie).await.is_success());
+
+    println!("Revoking it again finds nothing");
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri(format!("/sessions/{}", phone_session.id).as_str())
+        .method(Method::DELETE)
+        .cookie(laptop_cookie.clone())
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_rt::test]
+async fn expired_session_is_unauthorized() {
+    let user = make_user("expired_session");
+    let pool = get_connection_pool();
+    let session_cookie = login(&user, &pool).await.expect("Failed to login");
+    let session = get_sessions(&pool, &session_cookie).await.pop().expect("Found no session");
+
+    println!("Expire the session");
+    let conn = pool.get().unwrap();
+    diesel::sql_query(format!(
+        "UPDATE sessions SET expires_at = NOW() - INTERVAL '1 day' WHERE id = {}",
+        session.id
+    )).execute(&conn).expect("Failed to expire the session");
+    assert_eq!(get_user_status(&pool, &session_cookie).await, StatusCode::UNAUTHORIZED);
+}
\ No newline at end of file
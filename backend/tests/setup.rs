@@ -29,6 +29,8 @@ where
     test::init_service(
         App::new()
             .data(pool.clone())
+            .app_data(route::json_config())
+            .app_data(route::path_config())
             .wrap(
                 CookieSession::signed(&[0; 32]).secure(false)
             )
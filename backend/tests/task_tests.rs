@@ -5,7 +5,10 @@ use actix_web::{self, test, http::Method};
 use data::user::*;
 use data::task::*;
 use data::icon::TaskIcon;
+use data::error::ApiError;
+use diesel::{RunQueryDsl, sql_types::Integer};
 use setup::*;
+use chrono::{NaiveDate, Utc};
 use actix_web::http::StatusCode;
 
 const STANDARD_TASK_FREQUENCY: u32 = 3;
@@ -17,6 +20,27 @@ async fn create_new_task(
     ses: &actix_web::http::Cookie<'static>,
     name: &str,
     bspts: i32
+) -> Task {
+    create_new_task_with_penalty(pool, ses, name, bspts, PenaltyPolicy::None).await
+}
+
+async fn create_new_task_with_penalty(
+    pool: &PgPool,
+    ses: &actix_web::http::Cookie<'static>,
+    name: &str,
+    bspts: i32,
+    penalty: PenaltyPolicy,
+) -> Task {
+    create_new_task_with_policies(pool, ses, name, bspts, penalty, StreakBonus::None).await
+}
+
+async fn create_new_task_with_policies(
+    pool: &PgPool,
+    ses: &actix_web::http::Cookie<'static>,
+    name: &str,
+    bspts: i32,
+    penalty: PenaltyPolicy,
+    streak_bonus: StreakBonus,
 ) -> Task {
     println!("Creating task {}", name);
     let mut app = make_service(|c| {c.service(route::task::commit_new);}, &pool).await;
@@ -26,6 +50,10 @@ async fn create_new_task(
         bspts,
         frequency: TaskInterval::Days{every: STANDARD_TASK_FREQUENCY},
         icon: TaskIcon::default(),
+        penalty,
+        streak_bonus,
+        rrule: None,
+        shared: false,
     };
     let req = test::TestRequest::with_header("content-type", "text/plain")
         .header("year", "2021")
@@ -78,30 +106,25 @@ async fn complete_task_in_days(
     Ok(task)
 }
 
-async fn undo_complete_task(
-    pool: &PgPool,
-    ses: &actix_web::http::Cookie<'static>,
-    task: &Task,
-    days_in_future: u32,
-) -> Vec<Task> {
-    println!("Completing task {}", task.id);
-    let mut app = make_service(|c| {
-        c.service(route::task::undo);
-        c.service(route::task::get_by_id);
-    }, &pool).await;
-    let undo_req = test::TestRequest::with_header("content-type", "text/plain")
-        .header("year", "2021")
-        .header("month", "1")
-        .header("day", (1 + days_in_future).to_string())
-        .uri("/task/undo")
-        .method(Method::POST)
-        .cookie(ses.clone())
-        .set_json(&task)
-        .to_request();
-    let undo_resp = test::call_service(&mut app, undo_req).await;
-    println!("{:#?}", undo_resp);
-    assert!(undo_resp.status().is_success(), "Bad return value");
-    test::read_body_json(undo_resp).await
+/// Rolls the user's tasks over to the given day, the way the scheduler does
+fn roll_over_in_days(pool: &PgPool, user: &NewUser, days_in_future: u32) -> Vec<Task> {
+    println!("Rolling over {} days in the future", days_in_future);
+    let conn = pool.get().expect("Failed to get database connection");
+    let q_user = query::user::login_user(user.clone(), &conn).expect("Failed to get the user");
+    let today = NaiveDate::from_ymd_opt(2021, 1, 1 + days_in_future).unwrap();
+    let (reset_tasks, _) = query::rollover::roll_over_tasks(&q_user, &conn, today).expect("Failed to roll over");
+    reset_tasks
+}
+
+/// Undoes the task's last completion on the given day. The route always uses the server's date,
+/// so this calls the query directly to undo it on another day
+fn uncomplete_in_days(pool: &PgPool, user: &NewUser, task: &Task, days_in_future: u32) -> Result<Task, StatusCode> {
+    println!("Undoing task {} {} days in the future", task.id, days_in_future);
+    let conn = pool.get().expect("Failed to get database connection");
+    let q_user = query::user::login_user(user.clone(), &conn).expect("Failed to get the user");
+    let today = NaiveDate::from_ymd_opt(2021, 1, 1 + days_in_future).unwrap();
+    query::task::uncomplete_task(task.id, &q_user, &conn, today)
+        .map_err(|e| e.as_response_error().status_code())
 }
 
 /* TESTS START HERE */
@@ -123,6 +146,267 @@ async fn get_tasks() {
     assert!(resp.status().is_success());
 }
 
+#[actix_rt::test]
+async fn bad_date_headers_are_rejected() {
+    let user = make_user("bad_date_headers");
+    let pool = get_connection_pool();
+    let session_cookie = login(&user, &pool).await.expect("Failed to login");
+    let mut app = make_service(|c| {c.service(route::task::get_todo);}, &pool).await;
+    let bad_headers = [
+        ("2021", "2", "30"),
+        ("2021", "one", "1"),
+    ];
+    for (year, month, day) in bad_headers.iter() {
+        println!("Get tasks on {}-{}-{}", year, month, day);
+        let req = test::TestRequest::with_header("content-type", "text/plain")
+            .header("year", *year)
+            .header("month", *month)
+            .header("day", *day)
+            .uri("/task/todo")
+            .cookie(session_cookie.clone())
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+    println!("Get tasks with only some of the date headers");
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .header("year", "2021")
+        .uri("/task/todo")
+        .cookie(session_cookie.clone())
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_rt::test]
+async fn errors_are_json() {
+    let user = make_user("errors_are_json");
+    let pool = get_connection_pool();
+    let session_cookie = login(&user, &pool).await.expect("Failed to login");
+    let mut app = make_service(|c| {
+        c.service(route::task::get_by_id);
+        c.service(route::task::commit_new);
+    }, &pool).await;
+
+    println!("Get a task that doesn't exist");
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/task/-1")
+        .cookie(session_cookie.clone())
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    let error: ApiError = test::read_body_json(resp).await;
+    assert!(matches!(error, ApiError::NotFound(_)));
+
+    println!("Create a task with a body that isn't a task");
+    let req = test::TestRequest::with_header("content-type", "application/json")
+        .uri("/task")
+        .method(Method::POST)
+        .cookie(session_cookie.clone())
+        .set_payload("{\"name\": 5}")
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let error: ApiError = test::read_body_json(resp).await;
+    assert!(matches!(error, ApiError::BadRequest(_)));
+}
+
+#[actix_rt::test]
+async fn bad_task_data_is_an_internal_error() {
+    let user = make_user("bad_task_data_is_an_internal_error");
+    let pool = get_connection_pool();
+    let session_cookie = login(&user, &pool).await.expect("Failed to login");
+    let task = create_new_task(&pool, &session_cookie, "BadTask", 1).await;
+
+    println!("Give the task a time unit the server doesn't know about");
+    let conn = pool.get().expect("Failed to get database connection");
+    diesel::sql_query("UPDATE tasks SET time_unit = 'Fortnights' WHERE id = $1")
+        .bind::<Integer, _>(task.id)
+        .execute(&conn)
+        .expect("Failed to break the task");
+
+    println!("Getting the tasks should fail without bringing the server down");
+    let mut app = make_service(|c| {c.service(route::task::get_todo);}, &pool).await;
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/task/todo")
+        .cookie(session_cookie.clone())
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let error: ApiError = test::read_body_json(resp).await;
+    assert!(matches!(error, ApiError::Internal(_)));
+}
+
+#[actix_rt::test]
+async fn invalid_task_is_rejected() {
+    let user = make_user("invalid_task_is_rejected");
+    let pool = get_connection_pool();
+    let session_cookie = login(&user, &pool).await.expect("Failed to login");
+    let mut app = make_service(|c| {c.service(route::task::commit_new);}, &pool).await;
+
+    println!("Create a task with no name, negative points, too big a penalty and a weekday that doesn't exist");
+    let new_task = NewTask {
+        name: " ".to_string(),
+        description: "".to_string(),
+        bspts: -1,
+        frequency: TaskInterval::Weeks{every: 1, weekday: 9},
+        icon: TaskIcon::default(),
+        penalty: PenaltyPolicy::Escalating{pts: u32::MAX},
+        streak_bonus: StreakBonus::None,
+        rrule: None,
+        shared: false,
+    };
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/task")
+        .method(Method::POST)
+        .cookie(session_cookie.clone())
+        .set_json(&new_task)
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    match test::read_body_json(resp).await {
+        ApiError::Validation{fields, ..} => {
+            let field_names: Vec<&str> = fields.iter().map(|f| f.field.as_str()).collect();
+            assert_eq!(field_names, vec!["name", "bspts", "penalty", "frequency.weekday"]);
+        },
+        error => panic!("Expected a validation error, got {:?}", error),
+    }
+}
+
+#[actix_rt::test]
+async fn every_kind_of_interval_is_saved() {
+    let user = make_user("every_kind_of_interval");
+    let pool = get_connection_pool();
+    let session_cookie = login(&user, &pool).await.expect("Failed to login");
+    let mut app = make_service(|c| {
+        c.service(route::task::commit_new);
+        c.service(route::task::get_by_id);
+    }, &pool).await;
+
+    let intervals = vec![
+        TaskInterval::Days{every: 2},
+        TaskInterval::Weeks{every: 1, weekday: 3},
+        TaskInterval::Months{every: 1, day_of_month: LAST_DAY_OF_MONTH},
+        TaskInterval::DaysOfWeek{every: 1, weekdays: vec![0, 2, 4]},
+        TaskInterval::MonthWeekday{every: 1, week: 2, weekday: 1},
+        TaskInterval::MonthWeekday{every: 3, week: LAST_WEEK, weekday: 6},
+        TaskInterval::Years{every: 1, month: 4, day: 15},
+    ];
+    for frequency in intervals {
+        println!("Create a task that's done {:?}", frequency);
+        let new_task = NewTask {
+            name: "Chore".to_string(),
+            description: "".to_string(),
+            bspts: 1,
+            frequency: frequency.clone(),
+            icon: TaskIcon::default(),
+            penalty: PenaltyPolicy::None,
+            streak_bonus: StreakBonus::None,
+            rrule: None,
+            shared: false,
+        };
+        let req = test::TestRequest::with_header("content-type", "text/plain")
+            .header("year", "2021")
+            .header("month", "6")
+            .header("day", "1")
+            .uri("/task")
+            .method(Method::POST)
+            .cookie(session_cookie.clone())
+            .set_json(&new_task)
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert!(resp.status().is_success());
+        let task: Task = test::read_body_json(resp).await;
+        let today = NaiveDate::from_ymd_opt(2021, 6, 1).unwrap();
+        assert_eq!(Some(task.next_reset), frequency.next_reset_after(today));
+
+        println!("Read it back and check it has the same frequency");
+        let req = test::TestRequest::with_header("content-type", "text/plain")
+            .uri(&format!("/task/{}", task.id))
+            .cookie(session_cookie.clone())
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert!(resp.status().is_success());
+        let saved_task: Task = test::read_body_json(resp).await;
+        assert_eq!(saved_task.frequency, frequency);
+    }
+}
+
+#[actix_rt::test]
+async fn create_task_from_rrule() {
+    let user = make_user("create_task_from_rrule");
+    let pool = get_connection_pool();
+    let session_cookie = login(&user, &pool).await.expect("Failed to login");
+    let mut app = make_service(|c| {c.service(route::task::commit_new);}, &pool).await;
+
+    println!("Create a task with an rrule in place of a frequency");
+    let req = test::TestRequest::with_header("content-type", "application/json")
+        .header("year", "2021")
+        .header("month", "6")
+        .header("day", "1")
+        .uri("/task")
+        .method(Method::POST)
+        .cookie(session_cookie.clone())
+        .set_payload(r#"{
+            "name": "Laundry",
+            "description": "",
+            "bspts": 2,
+            "icon": {"color": "Yellow", "category": "Clean"},
+            "rrule": "RRULE:FREQ=WEEKLY;BYDAY=MO,WE,FR"
+        }"#)
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+    let task: Task = test::read_body_json(resp).await;
+    assert_eq!(task.frequency, TaskInterval::DaysOfWeek{every: 1, weekdays: vec![0, 2, 4]});
+    assert_eq!(task.rrule, "FREQ=WEEKLY;BYDAY=MO,WE,FR");
+    // June 1st 2021 was a Tuesday
+    assert_eq!(task.next_reset, NaiveDate::from_ymd_opt(2021, 6, 2).unwrap());
+
+    println!("Create a task with an rrule that tasks can't do");
+    let mut new_task: NewTask = task.into();
+    new_task.rrule = Some("FREQ=DAILY;COUNT=3".to_string());
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/task")
+        .method(Method::POST)
+        .cookie(session_cookie.clone())
+        .set_json(&new_task)
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    match test::read_body_json(resp).await {
+        ApiError::Validation{fields, ..} => assert_eq!(fields[0].field, "rrule"),
+        error => panic!("Expected a validation error, got {:?}", error),
+    }
+
+    println!("A yearly task in a month that doesn't exist is rejected, from an rrule or not");
+    let mut from_rrule = new_task.clone();
+    from_rrule.rrule = Some("FREQ=YEARLY;BYMONTH=0;BYMONTHDAY=1".to_string());
+    let mut from_frequency = new_task;
+    from_frequency.rrule = None;
+    from_frequency.frequency = TaskInterval::Years{every: 1, month: 0, day: 1};
+    for (new_task, field) in [(from_rrule, "rrule"), (from_frequency, "frequency.month")] {
+        let req = test::TestRequest::with_header("content-type", "text/plain")
+            .header("year", "2021")
+            .header("month", "6")
+            .header("day", "1")
+            .uri("/task")
+            .method(Method::POST)
+            .cookie(session_cookie.clone())
+            .set_json(&new_task)
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        match test::read_body_json(resp).await {
+            ApiError::Validation{fields, ..} => {
+                assert_eq!(fields[0].field, field);
+                assert!(fields[0].message.contains("month"), "Unexpected error {}", fields[0].message);
+            },
+            error => panic!("Expected a validation error, got {:?}", error),
+        }
+    }
+}
+
 #[actix_rt::test]
 async fn add_task() {
     let user = make_user("add_task");
@@ -257,8 +541,8 @@ async fn test_complete_task() {
     let get_user_resp = test::call_service(&mut app, get_user_req).await;
     println!("{:#?}", get_user_resp);
     assert!(get_user_resp.status().is_success());
-    let user: User = test::read_body_json(get_user_resp).await;
-    assert_eq!(user.bspts, task_points);
+    let account: User = test::read_body_json(get_user_resp).await;
+    assert_eq!(account.bspts, task_points);
     println!("Get the list of completed tasks and ensure this task is on there");
     let get_done_tasks_req = test::TestRequest::with_header("content-type", "text/plain")
         .uri("/task/done")
@@ -293,6 +577,77 @@ async fn test_point_loss() {
     assert!(!past_due_completion.is_ok(), "Task was successfully marked complete after due by date")
 }
 
+#[actix_rt::test]
+async fn missed_task_penalty() {
+    println!("Setup missed task penalty test");
+    let user = make_user("missed_penalty");
+    let pool = get_connection_pool();
+    let session_cookie = login(&user, &pool).await.expect("Failed to login");
+    let mut app = make_service(
+        |c| {
+            c.service(route::user::get_user);
+            c.service(route::task::get_by_id);
+        },
+        &pool
+    ).await;
+
+    let saved_task = create_new_task_with_penalty(
+        &pool, &session_cookie, "TaskName", 1, PenaltyPolicy::Linear{pts: 2}
+    ).await;
+
+    let get_task_and_user = |days_in_future: u32| {
+        let task_req = test::TestRequest::with_header("content-type", "text/plain")
+            .header("year", "2021")
+            .header("month", "1")
+            .header("day", (1 + days_in_future).to_string())
+            .uri(format!("/task/{}", saved_task.id).as_str())
+            .method(Method::GET)
+            .cookie(session_cookie.clone())
+            .to_request();
+        let user_req = test::TestRequest::with_header("content-type", "text/plain")
+            .uri("/user")
+            .method(Method::GET)
+            .cookie(session_cookie.clone())
+            .to_request();
+        (task_req, user_req)
+    };
+
+    println!("Roll over on the due date, which should NOT count as a miss");
+    roll_over_in_days(&pool, &user, STANDARD_TASK_FREQUENCY);
+    let (task_req, user_req) = get_task_and_user(STANDARD_TASK_FREQUENCY);
+    let task: Task = test::read_body_json(test::call_service(&mut app, task_req).await).await;
+    let account: User = test::read_body_json(test::call_service(&mut app, user_req).await).await;
+    assert_eq!(task.misses, 0);
+    assert_eq!(account.bspts, 0);
+
+    println!("Roll over the day after the due date, which misses the task once");
+    let one_miss = STANDARD_TASK_FREQUENCY + 1;
+    roll_over_in_days(&pool, &user, one_miss);
+    let (task_req, user_req) = get_task_and_user(one_miss);
+    let task: Task = test::read_body_json(test::call_service(&mut app, task_req).await).await;
+    let account: User = test::read_body_json(test::call_service(&mut app, user_req).await).await;
+    assert_eq!(task.misses, 1);
+    assert_eq!(task.pts_lost, 2);
+    assert_eq!(account.bspts, -2);
+    assert!(task.days_to_next_reset >= 0, "The task should have moved on to its next reset");
+
+    println!("Skip two more periods, the linear penalty should grow with each miss");
+    let three_misses = 3 * STANDARD_TASK_FREQUENCY + 1;
+    roll_over_in_days(&pool, &user, three_misses);
+    let (task_req, user_req) = get_task_and_user(three_misses);
+    let task: Task = test::read_body_json(test::call_service(&mut app, task_req).await).await;
+    let account: User = test::read_body_json(test::call_service(&mut app, user_req).await).await;
+    assert_eq!(task.misses, 3);
+    assert_eq!(task.pts_lost, 2 + 4 + 6);
+    assert_eq!(account.bspts, -(2 + 4 + 6));
+
+    println!("Completing the task ends the run of misses");
+    let completed = complete_task_in_days(&pool, &session_cookie, &task, three_misses).await
+        .expect("Could not complete task");
+    assert_eq!(completed.misses, 0);
+    assert_eq!(completed.pts_lost, 2 + 4 + 6);
+}
+
 #[actix_rt::test]
 async fn undo_task() {
     println!("Setup undo test");
@@ -309,9 +664,7 @@ async fn undo_task() {
     );
 
     println!("Now run undo with a time freq-1 day in the future, which should NOT trigger an undo");
-    let tasks_in_0_days: Vec<Task> = undo_complete_task(
-        &pool, &session_cookie, &saved_task, STANDARD_TASK_FREQUENCY - 1
-    ).await;
+    let tasks_in_0_days: Vec<Task> = roll_over_in_days(&pool, &user, STANDARD_TASK_FREQUENCY - 1);
     let mut in_list: bool = false;
     for task in tasks_in_0_days {
         if task.id == saved_task.id {
@@ -321,9 +674,7 @@ async fn undo_task() {
     assert!(!in_list, format!("Task {} should not have been undone yet.", saved_task.id));
 
     println!("Now run undo with a time freq days in the future, which SHOULD trigger an undo");
-    let tasks_in_0_days: Vec<Task> = undo_complete_task(
-        &pool, &session_cookie, &saved_task, STANDARD_TASK_FREQUENCY
-    ).await;
+    let tasks_in_0_days: Vec<Task> = roll_over_in_days(&pool, &user, STANDARD_TASK_FREQUENCY);
     let mut in_list: bool = false;
     for task in tasks_in_0_days {
         if task.id == saved_task.id {
@@ -334,4 +685,472 @@ async fn undo_task() {
         }
     }
     assert!(in_list, format!("Task {} should have been undone.", saved_task.id));
+}
+#[actix_rt::test]
+async fn rolling_over_ignores_the_client_date() {
+    let user = make_user("rolling_over_ignores_the_client_date");
+    let pool = get_connection_pool();
+    let session_cookie = login(&user, &pool).await.expect("Failed to login");
+    let mut app = make_service(|c| {c.service(route::task::undo);}, &pool).await;
+
+    println!("Roll over with a date far in the future");
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .header("year", "2100")
+        .header("month", "1")
+        .header("day", "1")
+        .uri("/task/undo")
+        .method(Method::POST)
+        .cookie(session_cookie.clone())
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+
+    println!("The user is only rolled over to their own date, so the scheduler doesn't skip them");
+    let conn = pool.get().expect("Failed to get database connection");
+    let q_user = query::user::login_user(user, &conn).expect("Failed to get the user");
+    assert_eq!(q_user.last_rollover, Some(query::user::local_date(&q_user, Utc::now())));
+}
+
+#[actix_rt::test]
+async fn other_users_task_is_not_found() {
+    println!("Setup cross user test");
+    let owner = make_user("task_owner");
+    let intruder = make_user("task_intruder");
+    let pool = get_connection_pool();
+    let owner_cookie = login(&owner, &pool).await.expect("Failed to login owner");
+    let intruder_cookie = login(&intruder, &pool).await.expect("Failed to login intruder");
+    let mut app = make_service(
+        |c| {
+            c.service(route::user::get_user);
+            c.service(route::task::get_by_id);
+            c.service(route::task::update);
+            c.service(route::task::complete);
+            c.service(route::task::delete);
+        },
+        &pool
+    ).await;
+
+    let saved_task = create_new_task(&pool, &owner_cookie, "TaskName", 1).await;
+    let task_uri = format!("/task/{}", saved_task.id);
+    let complete_uri = format!("/task/complete/{}", saved_task.id);
+    let new_task: NewTask = saved_task.clone().into();
+
+    println!("The intruder should not be able to read, edit, complete or delete the task");
+    let requests = vec![
+        (task_uri.as_str(), Method::GET),
+        (task_uri.as_str(), Method::PUT),
+        (complete_uri.as_str(), Method::POST),
+        (task_uri.as_str(), Method::DELETE),
+    ];
+    for (uri, method) in requests {
+        let req = test::TestRequest::with_header("content-type", "text/plain")
+            .header("year", "2021")
+            .header("month", "1")
+            .header("day", "1")
+            .uri(uri)
+            .method(method.clone())
+            .cookie(intruder_cookie.clone())
+            .set_json(&new_task)
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        println!("{:#?}", resp);
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND, "{} {} should not be found", method, uri);
+    }
+
+    println!("Make sure the intruder did not get any points");
+    let get_user_req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/user")
+        .method(Method::GET)
+        .cookie(intruder_cookie.clone())
+        .to_request();
+    let get_user_resp = test::call_service(&mut app, get_user_req).await;
+    assert!(get_user_resp.status().is_success());
+    let account: User = test::read_body_json(get_user_resp).await;
+    assert_eq!(account.bspts, 0);
+
+    println!("The owner can still get the task, unchanged");
+    let get_req = test::TestRequest::with_header("content-type", "text/plain")
+        .header("year", "2021")
+        .header("month", "1")
+        .header("day", "1")
+        .uri(task_uri.as_str())
+        .method(Method::GET)
+        .cookie(owner_cookie.clone())
+        .to_request();
+    let get_resp = test::call_service(&mut app, get_req).await;
+    assert!(get_resp.status().is_success());
+    let task: Task = test::read_body_json(get_resp).await;
+    assert!(!task.is_done, "The intruder should not have completed the task");
+}
+
+
+#[actix_rt::test]
+async fn completions_are_kept_in_the_history() {
+    let user = make_user("completions_are_kept_in_the_history");
+    let pool = get_connection_pool();
+    let session_cookie = login(&user, &pool).await.expect("Failed to login");
+    let task = create_new_task(&pool, &session_cookie, "HistoryTask", 4).await;
+
+    println!("Complete the task, let it reset, then miss it once and complete it again");
+    complete_task(&pool, &session_cookie, &task).await.expect("Failed to complete the task");
+    roll_over_in_days(&pool, &user, STANDARD_TASK_FREQUENCY);
+    let conn = pool.get().expect("Failed to get database connection");
+    diesel::sql_query("UPDATE tasks SET misses = 1 WHERE id = $1")
+        .bind::<Integer, _>(task.id)
+        .execute(&conn)
+        .expect("Failed to miss the task");
+    complete_task_in_days(&pool, &session_cookie, &task, STANDARD_TASK_FREQUENCY).await
+        .expect("Failed to complete the task again");
+
+    let mut app = make_service(|c| {
+        c.service(route::task::get_history_by_id);
+        c.service(route::task::get_history_range);
+    }, &pool).await;
+
+    println!("The task's history has both completions, newest first");
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri(&format!("/task/{}/history", task.id))
+        .cookie(session_cookie.clone())
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+    let history: Vec<TaskCompletion> = test::read_body_json(resp).await;
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].completed_on, NaiveDate::from_ymd_opt(2021, 1, 4).unwrap());
+    assert!(!history[0].on_time);
+    assert_eq!(history[1].completed_on, NaiveDate::from_ymd_opt(2021, 1, 1).unwrap());
+    assert!(history[1].on_time);
+    assert!(history.iter().all(|completion| completion.bspts == 4 && completion.task_name == "HistoryTask"));
+
+    println!("Only the first completion is in the first three days");
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/history?from=2021-01-01&to=2021-01-03")
+        .cookie(session_cookie.clone())
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+    let history: Vec<TaskCompletion> = test::read_body_json(resp).await;
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].task_id, task.id);
+
+    println!("A range that ends before it starts is rejected");
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/history?from=2021-01-03&to=2021-01-01")
+        .cookie(session_cookie.clone())
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    println!("Another user can't see the task's history");
+    let other_user = make_user("completions_are_kept_in_the_history_other");
+    let other_cookie = login(&other_user, &pool).await.expect("Failed to login");
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri(&format!("/task/{}/history", task.id))
+        .cookie(other_cookie)
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_rt::test]
+async fn completing_a_task_can_be_undone() {
+    let user = make_user("completing_a_task_can_be_undone");
+    let pool = get_connection_pool();
+    let session_cookie = login(&user, &pool).await.expect("Failed to login");
+    let task = create_new_task(&pool, &session_cookie, "UndoTask", 5).await;
+    let conn = pool.get().expect("Failed to get database connection");
+    diesel::sql_query("UPDATE tasks SET misses = 2 WHERE id = $1")
+        .bind::<Integer, _>(task.id)
+        .execute(&conn)
+        .expect("Failed to miss the task");
+    complete_task(&pool, &session_cookie, &task).await.expect("Failed to complete the task");
+
+    let mut app = make_service(|c| {
+        c.service(route::task::get_history_by_id);
+        c.service(route::user::get_user);
+    }, &pool).await;
+
+    println!("Undo the completion");
+    let undone = uncomplete_in_days(&pool, &user, &task, 0).expect("Failed to undo the completion");
+    assert!(!undone.is_done);
+    assert_eq!(undone.misses, 2);
+
+    println!("The points and the history entry are gone");
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/user")
+        .cookie(session_cookie.clone())
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    let account: User = test::read_body_json(resp).await;
+    assert_eq!(account.bspts, 0);
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri(&format!("/task/{}/history", task.id))
+        .cookie(session_cookie.clone())
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    let history: Vec<TaskCompletion> = test::read_body_json(resp).await;
+    assert!(history.is_empty());
+
+    println!("A task that isn't done can't be undone");
+    assert_eq!(uncomplete_in_days(&pool, &user, &task, 0).err(), Some(StatusCode::BAD_REQUEST));
+
+    println!("Once the task has reset its completion can't be undone");
+    complete_task(&pool, &session_cookie, &task).await.expect("Failed to complete the task");
+    roll_over_in_days(&pool, &user, STANDARD_TASK_FREQUENCY);
+    assert_eq!(uncomplete_in_days(&pool, &user, &task, STANDARD_TASK_FREQUENCY).err(), Some(StatusCode::BAD_REQUEST));
+}
+
+#[actix_rt::test]
+async fn streaks_grow_and_earn_bonuses() {
+    let user = make_user("streaks_grow_and_earn_bonuses");
+    let pool = get_connection_pool();
+    let session_cookie = login(&user, &pool).await.expect("Failed to login");
+    let task = create_new_task_with_policies(
+        &pool, &session_cookie, "StreakTask", 5, PenaltyPolicy::None, StreakBonus::Flat{pts: 2}
+    ).await;
+    let mut app = make_service(|c| {c.service(route::user::get_user);}, &pool).await;
+    let get_user_req = || test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/user")
+        .cookie(session_cookie.clone())
+        .to_request();
+
+    println!("The first completion starts the streak without a bonus");
+    let done = complete_task(&pool, &session_cookie, &task).await.expect("Failed to complete the task");
+    assert_eq!((done.streak, done.best_streak), (1, 1));
+
+    println!("Completing it again in each of the next two periods earns the bonus each time");
+    for period in 1..=2 {
+        let day = period * STANDARD_TASK_FREQUENCY;
+        roll_over_in_days(&pool, &user, day);
+        let done = complete_task_in_days(&pool, &session_cookie, &task, day).await
+            .expect("Failed to complete the task");
+        assert_eq!(done.streak, period as i32 + 1);
+    }
+    let account: User = test::read_body_json(test::call_service(&mut app, get_user_req()).await).await;
+    assert_eq!(account.bspts, 3 * 5 + 2 * 2);
+
+    println!("Undoing the last completion takes back its bonus and shortens the streak");
+    let undone = uncomplete_in_days(&pool, &user, &task, 2 * STANDARD_TASK_FREQUENCY)
+        .expect("Failed to undo the completion");
+    assert_eq!((undone.streak, undone.best_streak), (2, 2));
+    let account: User = test::read_body_json(test::call_service(&mut app, get_user_req()).await).await;
+    assert_eq!(account.bspts, 2 * 5 + 2);
+
+    println!("Letting a period pass breaks the streak, but the best is kept");
+    let missed_day = 3 * STANDARD_TASK_FREQUENCY + 1;
+    let reset_tasks = roll_over_in_days(&pool, &user, missed_day);
+    assert!(reset_tasks.iter().all(|reset_task| reset_task.id != task.id));
+    let done = complete_task_in_days(&pool, &session_cookie, &task, missed_day).await
+        .expect("Failed to complete the task");
+    assert_eq!((done.streak, done.best_streak), (1, 2));
+}
+#[actix_rt::test]
+async fn one_off_task_is_archived_once_done() {
+    let user = make_user("one_off_task_is_archived");
+    let pool = get_connection_pool();
+    let session_cookie = login(&user, &pool).await.expect("Failed to login");
+    let mut app = make_service(|c| {
+        c.service(route::task::get_todo);
+        c.service(route::task::get_done);
+        c.service(route::task::get_archived);
+        c.service(route::task::commit_new);
+        c.service(route::task::uncomplete);
+        c.service(route::user::get_user);
+    }, &pool).await;
+    let new_task_req = |due: NaiveDate| test::TestRequest::with_header("content-type", "text/plain")
+        .header("year", "2021")
+        .header("month", "1")
+        .header("day", "1")
+        .uri("/task")
+        .method(Method::POST)
+        .cookie(session_cookie.clone())
+        .set_json(&NewTask {
+            name: "File taxes".to_string(),
+            description: "".to_string(),
+            bspts: 10,
+            frequency: TaskInterval::Once{due},
+            icon: TaskIcon::default(),
+            penalty: PenaltyPolicy::Flat{pts: 3},
+            streak_bonus: StreakBonus::None,
+            rrule: None,
+            shared: false,
+        })
+        .to_request();
+    let list_req = |list: &str, day: u32| test::TestRequest::with_header("content-type", "text/plain")
+        .header("year", "2021")
+        .header("month", "1")
+        .header("day", day.to_string())
+        .uri(&format!("/task/{}", list))
+        .cookie(session_cookie.clone())
+        .to_request();
+    let get_user_req = || test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/user")
+        .cookie(session_cookie.clone())
+        .to_request();
+
+    println!("A one-off task can't be due before today");
+    let resp = test::call_service(&mut app, new_task_req(NaiveDate::from_ymd_opt(2020, 12, 31).unwrap())).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    println!("Make a task due on the 5th");
+    let due = NaiveDate::from_ymd_opt(2021, 1, 5).unwrap();
+    let resp = test::call_service(&mut app, new_task_req(due)).await;
+    assert!(resp.status().is_success());
+    let task: Task = test::read_body_json(resp).await;
+    assert_eq!(task.next_reset, due);
+    assert_eq!(task.frequency, TaskInterval::Once{due});
+    assert_eq!(task.archived_at, None);
+
+    println!("Missing it is only penalized once, however long it goes undone");
+    roll_over_in_days(&pool, &user, 7);
+    roll_over_in_days(&pool, &user, 11);
+    let account: User = test::read_body_json(test::call_service(&mut app, get_user_req()).await).await;
+    assert_eq!(account.bspts, -3);
+    let todo: Vec<Task> = test::read_body_json(test::call_service(&mut app, list_req("todo", 12)).await).await;
+    let missed = todo.iter().find(|t| t.id == task.id).expect("The missed task should still be todo");
+    assert_eq!(missed.misses, 1);
+    assert_eq!(missed.next_reset, due);
+
+    println!("It can still be done late, which archives it");
+    let done = complete_task_in_days(&pool, &session_cookie, &task, 11).await
+        .expect("Failed to complete the past-due task");
+    assert!(done.is_done);
+    assert!(done.archived_at.is_some());
+
+    println!("Rolling over never brings it back, and it's only in the archive");
+    let reset_tasks = roll_over_in_days(&pool, &user, 30);
+    assert!(reset_tasks.iter().all(|t| t.id != task.id));
+    for list in &["todo", "done"] {
+        let tasks: Vec<Task> = test::read_body_json(test::call_service(&mut app, list_req(list, 31)).await).await;
+        assert!(tasks.iter().all(|t| t.id != task.id), "The task is in the {} list", list);
+    }
+    let archived: Vec<Task> = test::read_body_json(test::call_service(&mut app, list_req("archived", 31)).await).await;
+    assert_eq!(archived.iter().map(|t| t.id).collect::<Vec<i32>>(), vec![task.id]);
+
+    println!("Undoing the completion takes it out of the archive");
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .header("year", "2021")
+        .header("month", "1")
+        .header("day", "31")
+        .uri(&format!("/task/uncomplete/{}", task.id))
+        .method(Method::POST)
+        .cookie(session_cookie.clone())
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+    let undone: Task = test::read_body_json(resp).await;
+    assert!(!undone.is_done);
+    assert_eq!(undone.archived_at, None);
+    let account: User = test::read_body_json(test::call_service(&mut app, get_user_req()).await).await;
+    assert_eq!(account.bspts, -3);
+}
+
+#[actix_rt::test]
+async fn archived_task_can_be_restored_or_purged() {
+    let user = make_user("archived_task");
+    let pool = get_connection_pool();
+    let session_cookie = login(&user, &pool).await.expect("Failed to login");
+    let mut app = make_service(route::task::configure, &pool).await;
+    let req = |method: Method, uri: String, day: u32| test::TestRequest::with_header("content-type", "text/plain")
+        .header("year", "2021")
+        .header("month", "1")
+        .header("day", day.to_string())
+        .uri(&uri)
+        .method(method)
+        .cookie(session_cookie.clone())
+        .to_request();
+    let task = create_new_task(&pool, &session_cookie, "ArchivedTask", 2).await;
+    complete_task(&pool, &session_cookie, &task).await.expect("Failed to complete the task");
+
+    println!("Archive the task, which keeps its history");
+    let resp = test::call_service(&mut app, req(Method::DELETE, format!("/task/{}", task.id), 1)).await;
+    assert!(resp.status().is_success());
+    for list in &["todo", "done"] {
+        let tasks: Vec<Task> = test::read_body_json(
+            test::call_service(&mut app, req(Method::GET, format!("/task/{}", list), 1)).await
+        ).await;
+        assert!(tasks.iter().all(|t| t.id != task.id), "The task is in the {} list", list);
+    }
+    let archived: Vec<Task> = test::read_body_json(
+        test::call_service(&mut app, req(Method::GET, "/task/archived".to_string(), 1)).await
+    ).await;
+    assert_eq!(archived.iter().map(|t| t.id).collect::<Vec<i32>>(), vec![task.id]);
+    let history: Vec<TaskCompletion> = test::read_body_json(
+        test::call_service(&mut app, req(Method::GET, format!("/task/{}/history", task.id), 1)).await
+    ).await;
+    assert_eq!(history.len(), 1);
+
+    println!("Archived tasks are left alone when rolling over");
+    let reset_tasks = roll_over_in_days(&pool, &user, 10);
+    assert!(reset_tasks.iter().all(|t| t.id != task.id));
+
+    println!("Restoring it long after it would have reset brings it back as todo");
+    let resp = test::call_service(&mut app, req(Method::POST, format!("/task/restore/{}", task.id), 11)).await;
+    assert!(resp.status().is_success());
+    let restored: Task = test::read_body_json(resp).await;
+    assert_eq!(restored.archived_at, None);
+    assert!(!restored.is_done);
+    assert_eq!(restored.misses, 0);
+    assert!(restored.days_to_next_reset > 0);
+
+    println!("Only an archived task can be purged, which deletes its history too");
+    let resp = test::call_service(&mut app, req(Method::DELETE, format!("/task/purge/{}", task.id), 11)).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    test::call_service(&mut app, req(Method::DELETE, format!("/task/{}", task.id), 11)).await;
+    let resp = test::call_service(&mut app, req(Method::DELETE, format!("/task/purge/{}", task.id), 11)).await;
+    assert!(resp.status().is_success());
+    let resp = test::call_service(&mut app, req(Method::GET, format!("/task/{}", task.id), 11)).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_rt::test]
+async fn restored_one_off_task_cannot_be_completed_again() {
+    let user = make_user("restored_one_off_task");
+    let pool = get_connection_pool();
+    let session_cookie = login(&user, &pool).await.expect("Failed to login");
+    let mut app = make_service(route::task::configure, &pool).await;
+    let req = |method: Method, uri: String, day: u32| test::TestRequest::with_header("content-type", "text/plain")
+        .header("year", "2021")
+        .header("month", "1")
+        .header("day", day.to_string())
+        .uri(&uri)
+        .method(method)
+        .cookie(session_cookie.clone());
+
+    println!("Make a one-off task and complete it, which archives it");
+    let new_task = NewTask {
+        name: "Renew passport".to_string(),
+        description: "".to_string(),
+        bspts: 10,
+        frequency: TaskInterval::Once{due: NaiveDate::from_ymd_opt(2021, 1, 5).unwrap()},
+        icon: TaskIcon::default(),
+        penalty: PenaltyPolicy::None,
+        streak_bonus: StreakBonus::None,
+        rrule: None,
+        shared: false,
+    };
+    let resp = test::call_service(&mut app, req(Method::POST, "/task".to_string(), 1).set_json(&new_task).to_request()).await;
+    assert!(resp.status().is_success());
+    let task: Task = test::read_body_json(resp).await;
+    let done = complete_task(&pool, &session_cookie, &task).await.expect("Failed to complete the task");
+    assert!(done.archived_at.is_some());
+
+    println!("Restoring it brings it back still done");
+    let resp = test::call_service(&mut app, req(Method::POST, format!("/task/restore/{}", task.id), 2).to_request()).await;
+    assert!(resp.status().is_success());
+    let restored: Task = test::read_body_json(resp).await;
+    assert_eq!(restored.archived_at, None);
+    assert!(restored.is_done);
+
+    println!("So it can't be paid out again");
+    assert_eq!(complete_task_in_days(&pool, &session_cookie, &task, 1).await.err(), Some(StatusCode::BAD_REQUEST));
+
+    println!("And rolling over past its due date leaves it done");
+    let reset_tasks = roll_over_in_days(&pool, &user, 9);
+    assert!(reset_tasks.iter().all(|t| t.id != task.id));
+    let resp = test::call_service(&mut app, req(Method::GET, format!("/task/{}", task.id), 10).to_request()).await;
+    let rolled_over: Task = test::read_body_json(resp).await;
+    assert!(rolled_over.is_done);
+    let conn = pool.get().unwrap();
+    assert_eq!(query::user::login_user(user, &conn).unwrap().bspts, 10);
 }
\ No newline at end of file
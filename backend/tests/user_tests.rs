@@ -1,7 +1,13 @@
 mod setup;
 
 use backend_lib::*;
-use actix_web::{self, test, http::Method};
+use actix_web::{self, test, http::{Method, StatusCode}};
+use data::user::{User, NewUser, PasswordChange, AccountDeletion};
+use data::error::ApiError;
+use data::ledger::LedgerSource;
+use diesel::{RunQueryDsl, sql_types::{Bytea, Integer}};
+use ring::pbkdf2;
+use std::num::NonZeroU32;
 use setup::*;
 
 #[actix_rt::test]
@@ -20,4 +26,219 @@ async fn sign_up_user() {
     assert!(resp.status().is_success());
 }
 
+#[actix_rt::test]
+async fn set_time_zone() {
+    let pool = get_connection_pool();
+    let user = make_user("set_time_zone");
+    let session_cookie = login(&user, &pool).await.expect("Failed to login");
+    let mut app = make_service(|c| {c.service(route::user::set_user_time_zone);}, &pool).await;
+
+    println!("Set a real time zone");
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/user/time_zone")
+        .method(Method::PUT)
+        .cookie(session_cookie.clone())
+        .set_json(&"America/Denver")
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+    let updated_user: User = test::read_body_json(resp).await;
+    assert_eq!(updated_user.time_zone, "America/Denver");
+
+    println!("Set a time zone that doesn't exist");
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/user/time_zone")
+        .method(Method::PUT)
+        .cookie(session_cookie.clone())
+        .set_json(&"Mars/Olympus_Mons")
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    match test::read_body_json(resp).await {
+        ApiError::Validation{fields, ..} => assert_eq!(fields[0].field, "time_zone"),
+        error => panic!("Expected a validation error, got {:?}", error),
+    }
+}
+
+#[actix_rt::test]
+async fn invalid_user_is_rejected() {
+    let pool = get_connection_pool();
+    let mut app = make_service(|c| {c.service(route::user::sign_up);}, &pool).await;
+
+    println!("Sign up with a username that has a space in it");
+    let new_user = NewUser {
+        uname: "not valid".to_string(),
+        password: "pw1".to_string(),
+    };
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/user")
+        .method(Method::POST)
+        .set_json(&new_user)
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    match test::read_body_json(resp).await {
+        ApiError::Validation{fields, ..} => assert_eq!(fields[0].field, "uname"),
+        error => panic!("Expected a validation error, got {:?}", error),
+    }
+}
+
+#[actix_rt::test]
+async fn new_user_gets_current_hash() {
+    let pool = get_connection_pool();
+    let conn = pool.get().unwrap();
+    let user = make_user("current_hash");
+    query::user::save_new_user(&user, &conn).expect("Failed to save user");
+    let q_user = query::user::login_user(user.clone(), &conn).expect("Failed to login");
+    assert_eq!(q_user.hash_version, 2);
+    assert_eq!(q_user.hash_algorithm, "argon2id");
+    assert_eq!(q_user.salt.len(), 16);
+
+    println!("Log in with the wrong password");
+    let wrong_user = NewUser {password: "wrong".to_string(), ..user};
+    assert!(query::user::login_user(wrong_user, &conn).is_err());
+}
+
+#[actix_rt::test]
+async fn legacy_hash_is_upgraded_on_login() {
+    let pool = get_connection_pool();
+    let conn = pool.get().unwrap();
+    let user = make_user("legacy_hash");
+    let q_user = query::user::save_new_user(&user, &conn).expect("Failed to save user");
+
+    println!("Save the password the way it was before hashes were versioned");
+    let legacy_salt = vec![7u8, 3];
+    let mut legacy_hash = [0u8; 32];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        NonZeroU32::new(100_000).unwrap(),
+        &legacy_salt,
+        user.password.as_bytes(),
+        &mut legacy_hash
+    );
+    diesel::sql_query(
+        "UPDATE users SET password = $1, salt = $2, hash_version = 1, hash_algorithm = 'pbkdf2-sha256', \
+        hash_iterations = 100000, hash_memory_kib = 0 WHERE id = $3"
+    )
+        .bind::<Bytea, _>(legacy_hash.to_vec())
+        .bind::<Bytea, _>(legacy_salt)
+        .bind::<Integer, _>(q_user.id)
+        .execute(&conn)
+        .expect("Failed to save the legacy hash");
+
+    println!("Log in with the wrong password");
+    let wrong_user = NewUser {password: "wrong".to_string(), ..user.clone()};
+    assert!(query::user::login_user(wrong_user, &conn).is_err());
+
+    println!("Log in with the legacy hash");
+    let upgraded_user = query::user::login_user(user.clone(), &conn).expect("Failed to login");
+    assert_eq!(upgraded_user.hash_version, 2);
+    assert_eq!(upgraded_user.hash_algorithm, "argon2id");
+    assert_eq!(upgraded_user.salt.len(), 16);
+
+    println!("Log in again with the upgraded hash");
+    let q_user = query::user::login_user(user, &conn).expect("Failed to login");
+    assert_eq!(q_user.password, upgraded_user.password);
+}
+
+/// Gets the status of a request for the user made with the session cookie
+async fn get_user_status(pool: &PgPool, ses: &actix_web::http::Cookie<'static>) -> StatusCode {
+    let mut app = make_service(|c| {c.service(route::user::get_user);}, pool).await;
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/user")
+        .cookie(ses.clone())
+        .to_request();
+    test::call_service(&mut app, req).await.status()
+}
+
+#[actix_rt::test]
+async fn change_password() {
+    let pool = get_connection_pool();
+    let user = make_user("change_password");
+    let laptop_cookie = login(&user, &pool).await.expect("Failed to login");
+    let phone_cookie = login(&user, &pool).await.expect("Failed to login");
+    let mut app = make_service(|c| {c.service(route::user::update_password);}, &pool).await;
+
+    println!("Change the password without the right current password");
+    let wrong_change = PasswordChange {
+        current_password: "wrong".to_string(),
+        new_password: "pw2".to_string(),
+    };
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/user/password")
+        .method(Method::PUT)
+        .cookie(laptop_cookie.clone())
+        .set_json(&wrong_change)
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    println!("Change the password from the laptop");
+    let change = PasswordChange {
+        current_password: user.password.clone(),
+        new_password: "pw2".to_string(),
+    };
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/user/password")
+        .method(Method::PUT)
+        .cookie(laptop_cookie.clone())
+        .set_json(&change)
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+
+    println!("Check that only the laptop is still logged in");
+    assert!(get_user_status(&pool, &laptop_cookie).await.is_success());
+    assert_eq!(get_user_status(&pool, &phone_cookie).await, StatusCode::UNAUTHORIZED);
+
+    println!("Check that only the new password works");
+    let conn = pool.get().unwrap();
+    assert!(query::user::login_user(user.clone(), &conn).is_err());
+    let new_user = NewUser {password: "pw2".to_string(), ..user};
+    let stale_user = query::user::login_user(new_user, &conn).expect("Failed to login");
+
+    println!("Change the password with a user loaded before they earned points");
+    query::user::update_bspts(stale_user.id, 5, LedgerSource::Adjustment, None, &conn).unwrap();
+    let change = PasswordChange {
+        current_password: "pw2".to_string(),
+        new_password: "pw3".to_string(),
+    };
+    let changed_user = query::user::change_password(stale_user, &change, &conn).unwrap();
+    assert_eq!(changed_user.bspts, 5);
+}
+
+#[actix_rt::test]
+async fn delete_account() {
+    let pool = get_connection_pool();
+    let user = make_user("delete_account");
+    let session_cookie = login(&user, &pool).await.expect("Failed to login");
+    let mut app = make_service(|c| {c.service(route::user::delete_account);}, &pool).await;
+
+    println!("Delete the account without the right password");
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/user")
+        .method(Method::DELETE)
+        .cookie(session_cookie.clone())
+        .set_json(&AccountDeletion {password: "wrong".to_string()})
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    assert!(get_user_status(&pool, &session_cookie).await.is_success());
+
+    println!("Delete the account");
+    let req = test::TestRequest::with_header("content-type", "text/plain")
+        .uri("/user")
+        .method(Method::DELETE)
+        .cookie(session_cookie.clone())
+        .set_json(&AccountDeletion {password: user.password.clone()})
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert!(resp.status().is_success());
+
+    println!("Check that the account is gone");
+    assert_eq!(get_user_status(&pool, &session_cookie).await, StatusCode::UNAUTHORIZED);
+    let conn = pool.get().unwrap();
+    assert!(query::user::login_user(user, &conn).is_err());
+}
+
 // TODO: test login fails for uname and pw
\ No newline at end of file
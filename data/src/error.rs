@@ -0,0 +1,175 @@
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+use std::fmt;
+
+/// A machine readable name for each kind of error, so that
+/// clients can react to an error without parsing its message
+#[derive(Display, EnumString, Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorCode {
+    NotFound,
+    Unauthorized,
+    Forbidden,
+    Validation,
+    Conflict,
+    BadRequest,
+    Internal,
+    CoolingDown,
+    LimitReached,
+    InsufficientPoints,
+}
+
+/// A problem with one of the fields of a request
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct FieldError {
+    /// The name of the field, as it's spelled in the request
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, message: &str) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+/// An error sent back by the backend in place of what was asked for.
+/// Sent as JSON in the form `{code, message, details}`
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(into = "ApiErrorBody", from = "ApiErrorBody")]
+pub enum ApiError {
+    /// The thing asked for doesn't exist, or belongs to someone else
+    NotFound(String),
+    /// The user isn't logged in, or gave the wrong password when logging in
+    Unauthorized(String),
+    /// The user is logged in but isn't allowed to do this
+    Forbidden(String),
+    /// Some of the fields of the request aren't valid
+    Validation{message: String, fields: Vec<FieldError>},
+    /// The request clashes with something that already exists
+    Conflict(String),
+    /// The request can't be done, e.g. completing a task that's already done
+    BadRequest(String),
+    /// Something went wrong on the server
+    Internal(String),
+    /// The reward can't be taken again until its cooldown is over
+    CoolingDown(String),
+    /// The reward has been taken as many times as its limit allows
+    LimitReached(String),
+    /// Taking the reward would cost more points than the user has
+    InsufficientPoints(String),
+}
+
+/// How an ApiError is laid out as JSON
+#[derive(Deserialize, Serialize)]
+struct ApiErrorBody {
+    code: ErrorCode,
+    message: String,
+    /// Which fields were invalid, for validation errors
+    #[serde(default)]
+    details: Vec<FieldError>,
+}
+
+impl ApiError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ApiError::NotFound(_) => ErrorCode::NotFound,
+            ApiError::Unauthorized(_) => ErrorCode::Unauthorized,
+            ApiError::Forbidden(_) => ErrorCode::Forbidden,
+            ApiError::Validation{..} => ErrorCode::Validation,
+            ApiError::Conflict(_) => ErrorCode::Conflict,
+            ApiError::BadRequest(_) => ErrorCode::BadRequest,
+            ApiError::Internal(_) => ErrorCode::Internal,
+            ApiError::CoolingDown(_) => ErrorCode::CoolingDown,
+            ApiError::LimitReached(_) => ErrorCode::LimitReached,
+            ApiError::InsufficientPoints(_) => ErrorCode::InsufficientPoints,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            ApiError::NotFound(message)
+            | ApiError::Unauthorized(message)
+            | ApiError::Forbidden(message)
+            | ApiError::Validation{message, ..}
+            | ApiError::Conflict(message)
+            | ApiError::BadRequest(message)
+            | ApiError::Internal(message)
+            | ApiError::CoolingDown(message)
+            | ApiError::LimitReached(message)
+            | ApiError::InsufficientPoints(message) => message,
+        }
+    }
+
+    /// The HTTP status code that the error is sent with
+    pub fn status(&self) -> u16 {
+        match self {
+            ApiError::NotFound(_) => 404,
+            ApiError::Unauthorized(_) => 401,
+            ApiError::Forbidden(_) => 403,
+            ApiError::Validation{..} => 400,
+            ApiError::Conflict(_) => 409,
+            ApiError::BadRequest(_) => 400,
+            ApiError::Internal(_) => 500,
+            ApiError::CoolingDown(_) => 409,
+            ApiError::LimitReached(_) => 409,
+            ApiError::InsufficientPoints(_) => 409,
+        }
+    }
+
+    /// Makes an error from just a status code, for responses that didn't
+    /// come with an ApiError, e.g. from a proxy in front of the backend
+    pub fn from_status(status: u16, message: String) -> Self {
+        match status {
+            401 => ApiError::Unauthorized(message),
+            403 => ApiError::Forbidden(message),
+            404 => ApiError::NotFound(message),
+            409 => ApiError::Conflict(message),
+            422 => ApiError::Validation{message, fields: vec![]},
+            400..=499 => ApiError::BadRequest(message),
+            _ => ApiError::Internal(message),
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message())?;
+        if let ApiError::Validation{fields, ..} = self {
+            for field_error in fields {
+                write!(f, "; {}: {}", field_error.field, field_error.message)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<ApiError> for ApiErrorBody {
+    fn from(error: ApiError) -> Self {
+        let code = error.code();
+        match error {
+            ApiError::Validation{message, fields} => ApiErrorBody {code, message, details: fields},
+            other => ApiErrorBody {code, message: other.message().to_string(), details: vec![]},
+        }
+    }
+}
+
+impl From<ApiErrorBody> for ApiError {
+    fn from(body: ApiErrorBody) -> Self {
+        let message = body.message;
+        match body.code {
+            ErrorCode::NotFound => ApiError::NotFound(message),
+            ErrorCode::Unauthorized => ApiError::Unauthorized(message),
+            ErrorCode::Forbidden => ApiError::Forbidden(message),
+            ErrorCode::Validation => ApiError::Validation{message, fields: body.details},
+            ErrorCode::Conflict => ApiError::Conflict(message),
+            ErrorCode::BadRequest => ApiError::BadRequest(message),
+            ErrorCode::Internal => ApiError::Internal(message),
+            ErrorCode::CoolingDown => ApiError::CoolingDown(message),
+            ErrorCode::LimitReached => ApiError::LimitReached(message),
+            ErrorCode::InsufficientPoints => ApiError::InsufficientPoints(message),
+        }
+    }
+}
\ No newline at end of file
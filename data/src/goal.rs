@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use chrono::{NaiveDate, NaiveDateTime};
+use crate::error::FieldError;
+use crate::validate::*;
+
+/// A reward to pin as a goal and start saving points for
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct NewGoal {
+    pub reward_id: i32,
+}
+
+/// Points to set aside for a goal, taken out of the user's spendable points
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct GoalDeposit {
+    pub pts: i32,
+}
+
+impl Validate for GoalDeposit {
+    fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = vec![];
+        if self.pts < 1 {
+            errors.push(FieldError::new("pts", "At least 1 pt must be set aside"));
+        }
+        errors
+    }
+}
+
+/// A reward the user is saving points for
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Goal {
+    pub id: i32,
+    pub reward_id: i32,
+    /// The name of the reward as it is now
+    pub reward_name: String,
+    /// The points set aside for the reward so far
+    pub saved: i32,
+    /// What the reward costs today
+    pub target: i32,
+    /// The points the user has earned a day on average lately
+    pub daily_rate: f64,
+    /// The day enough will have been saved if the user keeps earning at the same rate,
+    /// none if they haven't been earning any points
+    pub projected_on: Option<NaiveDate>,
+    pub created_at: NaiveDateTime,
+}
+
+impl Goal {
+    /// How much of the goal has been saved, from 0 to 100
+    pub fn percent_saved(&self) -> i32 {
+        if self.target <= 0 {
+            return 100;
+        }
+        (self.saved * 100 / self.target).clamp(0, 100)
+    }
+
+    /// How many more points need to be set aside
+    pub fn remaining(&self) -> i32 {
+        (self.target - self.saved).max(0)
+    }
+}
\ No newline at end of file
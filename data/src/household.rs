@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+use crate::error::FieldError;
+use crate::validate::*;
+
+/// Starts a new household, which the user who creates it joins
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct NewHousehold {
+    pub name: String,
+}
+
+impl Validate for NewHousehold {
+    fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = vec![];
+        check_name(&mut errors, &self.name);
+        errors
+    }
+}
+
+/// Joins the household with the given invite code
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct JoinHousehold {
+    pub invite_code: String,
+}
+
+impl Validate for JoinHousehold {
+    fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = vec![];
+        if self.invite_code.trim().is_empty() {
+            errors.push(FieldError::new("invite_code", "The invite code can't be empty"));
+        }
+        errors
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct HouseholdMember {
+    pub uname: String,
+    pub bspts: i32,
+}
+
+/// A group of users who share tasks and rewards
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Household {
+    pub id: i32,
+    pub name: String,
+    /// Given to others so that they can join the household
+    pub invite_code: String,
+    pub members: Vec<HouseholdMember>,
+}
\ No newline at end of file
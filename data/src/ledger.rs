@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use chrono::NaiveDateTime;
+use strum_macros::{Display, EnumString};
+
+/// What caused the user's points to change
+#[derive(Display, EnumString, Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LedgerSource {
+    /// Points earned by completing a task
+    TaskCompletion,
+    /// Points spent on taking a reward
+    RewardRedemption,
+    /// Points lost by missing a task
+    Penalty,
+    /// Extra points earned by completing a task on time several times in a row
+    StreakBonus,
+    /// Points taken back when a task completion is undone
+    CompletionUndone,
+    /// Points changed by hand, or carried over from before the ledger existed
+    Adjustment,
+    /// Points set aside for a goal
+    GoalDeposit,
+    /// Points given back from a goal when it's cancelled or its reward is taken
+    GoalRelease,
+}
+
+/// A single credit (positive amount) or debit (negative amount) of points
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct LedgerEntry {
+    pub id: i32,
+    pub source: LedgerSource,
+    /// The id of the task, reward, etc. that caused this entry, if any
+    pub source_id: Option<i32>,
+    pub amount: i32,
+    pub created_at: NaiveDateTime,
+}
+
+/// One page of the ledger, newest entries first
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct LedgerPage {
+    pub entries: Vec<LedgerEntry>,
+    /// The index of this page, starting at 0
+    pub page: i64,
+    pub per_page: i64,
+    /// The total number of entries in the user's ledger
+    pub total: i64,
+}
@@ -3,4 +3,11 @@ extern crate chrono;
 pub mod user;
 pub mod task;
 pub mod reward;
-pub mod icon;
\ No newline at end of file
+pub mod goal;
+pub mod household;
+pub mod icon;
+pub mod ledger;
+pub mod session;
+pub mod error;
+pub mod validate;
+pub mod rrule;
\ No newline at end of file
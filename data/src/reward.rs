@@ -1,5 +1,50 @@
 use serde::{Deserialize, Serialize};
+use chrono::{NaiveDate, NaiveDateTime, Datelike, Duration};
 use crate::icon::RewardIcon;
+use crate::error::FieldError;
+use crate::validate::*;
+
+/// The most days a reward's cooldown can last
+pub const MAX_COOLDOWN_DAYS: u32 = 365;
+/// The most times a reward's limit can allow it to be taken in a day or a week
+pub const MAX_LIMIT_TIMES: u32 = 1_000;
+/// The most points a reward's price can go up by each time it's taken
+pub const MAX_PRICE_INCREMENT: u32 = 10_000;
+/// The most points a day a reward's added price can wear off by
+pub const MAX_PRICE_DECAY: u32 = 10_000;
+
+/// How many times a reward can be taken in a day or a week
+#[derive(Default, Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RedemptionLimit {
+    /// The reward can be taken as often as the user likes
+    #[default]
+    None,
+    PerDay{times: u32},
+    /// Weeks start on Monday
+    PerWeek{times: u32},
+}
+
+impl RedemptionLimit {
+    /// How many times the reward can be taken in each period, 0 if there's no limit
+    pub fn times(&self) -> u32 {
+        match self {
+            Self::None => 0,
+            Self::PerDay{times} | Self::PerWeek{times} => *times,
+        }
+    }
+
+    /// The first day of the period that today is in, none if there's no limit
+    pub fn period_start(&self, today: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Self::None => None,
+            Self::PerDay{..} => Some(today),
+            Self::PerWeek{..} => {
+                let days_since_monday = today.weekday().num_days_from_monday() as i64;
+                today.checked_sub_signed(Duration::days(days_since_monday))
+            },
+        }
+    }
+}
 
 /// The fields that must be specified when creating a new reward
 /// The rest will be set to default values
@@ -9,6 +54,63 @@ pub struct NewReward {
     pub description: String,
     pub bspts: i32,
     pub icon: RewardIcon,
+    /// How many days after being taken the reward can be taken again, 0 for no cooldown
+    #[serde(default)]
+    pub cooldown_days: u32,
+    #[serde(default)]
+    pub limit: RedemptionLimit,
+    /// Whether the reward can be taken when the user doesn't have enough points for it
+    #[serde(default)]
+    pub allow_overdraft: bool,
+    /// How many points are added to the price each time the reward is taken, 0 for a fixed price
+    #[serde(default)]
+    pub price_increment: u32,
+    /// How many points a day each increment wears off by
+    #[serde(default)]
+    pub price_decay: u32,
+    /// Whether the reward is shared with the user's household, so any member can take it
+    #[serde(default)]
+    pub shared: bool,
+}
+
+impl Validate for NewReward {
+    fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = vec![];
+        check_name(&mut errors, &self.name);
+        check_description(&mut errors, &self.description);
+        check_bspts(&mut errors, self.bspts);
+        if self.cooldown_days > MAX_COOLDOWN_DAYS {
+            errors.push(FieldError::new(
+                "cooldown_days",
+                &format!("The cooldown can't be more than {} days", MAX_COOLDOWN_DAYS),
+            ));
+        }
+        if self.limit != RedemptionLimit::None && self.limit.times() == 0 {
+            errors.push(FieldError::new("limit.times", "The reward must be allowed at least once"));
+        }
+        if self.limit.times() > MAX_LIMIT_TIMES {
+            errors.push(FieldError::new(
+                "limit.times",
+                &format!("The reward can't be allowed more than {} times", MAX_LIMIT_TIMES),
+            ));
+        }
+        if self.price_increment > MAX_PRICE_INCREMENT {
+            errors.push(FieldError::new(
+                "price_increment",
+                &format!("The price can't go up by more than {} pts", MAX_PRICE_INCREMENT),
+            ));
+        }
+        if self.price_increment > 0 && self.price_decay == 0 {
+            errors.push(FieldError::new("price_decay", "The added price must wear off by at least 1 pt a day"));
+        }
+        if self.price_decay > MAX_PRICE_DECAY {
+            errors.push(FieldError::new(
+                "price_decay",
+                &format!("The added price can't wear off by more than {} pts a day", MAX_PRICE_DECAY),
+            ));
+        }
+        errors
+    }
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -19,6 +121,28 @@ pub struct Reward {
     pub user_id: i32,
     pub bspts: i32,
     pub icon: RewardIcon,
+    pub cooldown_days: u32,
+    pub limit: RedemptionLimit,
+    pub allow_overdraft: bool,
+    pub price_increment: u32,
+    pub price_decay: u32,
+    /// When the reward was archived, none for rewards still in use
+    pub archived_at: Option<NaiveDateTime>,
+    /// The last day the reward was taken, none if it never has been
+    #[serde(default)]
+    pub last_redeemed: Option<NaiveDate>,
+    /// How many times the reward has been taken today
+    #[serde(default)]
+    pub redeemed_today: i64,
+    /// The day the reward's cooldown is over, none if it isn't cooling down
+    #[serde(default)]
+    pub available_on: Option<NaiveDate>,
+    /// What the reward costs today, its base price plus what's left of recent increments
+    #[serde(default)]
+    pub current_price: i32,
+    /// The household the reward is shared with, none if only its owner can see it
+    #[serde(default)]
+    pub household_id: Option<i32>,
 }
 
 impl Into<NewReward> for Reward {
@@ -28,6 +152,25 @@ impl Into<NewReward> for Reward {
             description: self.description.clone(),
             bspts: self.bspts,
             icon: self.icon.clone(),
+            cooldown_days: self.cooldown_days,
+            limit: self.limit,
+            allow_overdraft: self.allow_overdraft,
+            price_increment: self.price_increment,
+            price_decay: self.price_decay,
+            shared: self.household_id.is_some(),
         }
     }
+}
+
+/// A record of one time a reward was taken
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct RewardRedemption {
+    pub id: i32,
+    pub reward_id: i32,
+    /// The name of the reward as it is now
+    pub reward_name: String,
+    /// The day the reward was taken on, in the user's time zone
+    pub redeemed_on: NaiveDate,
+    /// The points the reward cost
+    pub bspts: i32,
 }
\ No newline at end of file
@@ -0,0 +1,208 @@
+use std::fmt;
+use crate::task::{TaskInterval, LAST_DAY_OF_MONTH, LAST_WEEK};
+
+/// The two letter codes RRULEs use for weekdays, with Monday=0
+const WEEKDAY_CODES: [&str; 7] = ["MO", "TU", "WE", "TH", "FR", "SA", "SU"];
+
+/// Why an RRULE couldn't be turned into a task interval
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RRuleError {
+    /// A part of the rule isn't written as NAME=VALUE
+    Malformed(String),
+    /// The rule doesn't say how often it repeats
+    MissingFrequency,
+    /// The rule uses something tasks can't do, e.g. COUNT or FREQ=HOURLY
+    Unsupported(String),
+    /// A part has a value that can't be read, e.g. BYDAY=XX
+    InvalidValue{part: String, value: String},
+    /// The frequency needs another part that the rule doesn't have,
+    /// e.g. FREQ=WEEKLY without BYDAY
+    MissingPart{frequency: String, part: String},
+}
+
+impl fmt::Display for RRuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RRuleError::Malformed(part) => write!(f, "'{}' isn't written as NAME=VALUE", part),
+            RRuleError::MissingFrequency => write!(f, "The rule has no FREQ"),
+            RRuleError::Unsupported(what) => write!(f, "Tasks can't repeat with {}", what),
+            RRuleError::InvalidValue{part, value} => write!(f, "{} can't be {}", part, value),
+            RRuleError::MissingPart{frequency, part} => write!(f, "FREQ={} needs {}", frequency, part),
+        }
+    }
+}
+
+/// Writes the interval as an iCalendar (RFC 5545) RRULE, without the RRULE: prefix.
+/// e.g. every other Monday is `FREQ=WEEKLY;INTERVAL=2;BYDAY=MO`.
+/// One-off tasks happen once on the day they start, `FREQ=DAILY;COUNT=1`
+pub fn to_rrule(interval: &TaskInterval) -> String {
+    let (frequency, by_parts) = match interval {
+        TaskInterval::Once{..} => {
+            ("DAILY", vec!["COUNT=1".to_string()])
+        },
+        TaskInterval::Days{..} => {
+            ("DAILY", vec![])
+        },
+        TaskInterval::Weeks{weekday, ..} => {
+            ("WEEKLY", vec![format!("BYDAY={}", weekday_code(*weekday))])
+        },
+        TaskInterval::DaysOfWeek{weekdays, ..} => {
+            let codes: Vec<&str> = weekdays.iter().map(|weekday| weekday_code(*weekday)).collect();
+            ("WEEKLY", vec![format!("BYDAY={}", codes.join(","))])
+        },
+        TaskInterval::Months{day_of_month, ..} => {
+            let day = if *day_of_month == LAST_DAY_OF_MONTH { -1 } else { *day_of_month as i32 };
+            ("MONTHLY", vec![format!("BYMONTHDAY={}", day)])
+        },
+        TaskInterval::MonthWeekday{week, weekday, ..} => {
+            let nth = if *week == LAST_WEEK { -1 } else { *week as i32 };
+            ("MONTHLY", vec![format!("BYDAY={}{}", nth, weekday_code(*weekday))])
+        },
+        TaskInterval::Years{month, day, ..} => {
+            ("YEARLY", vec![format!("BYMONTH={}", month), format!("BYMONTHDAY={}", day)])
+        },
+    };
+    let mut parts = vec![format!("FREQ={}", frequency)];
+    if interval.every() > 1 {
+        parts.push(format!("INTERVAL={}", interval.every()));
+    }
+    parts.extend(by_parts);
+    parts.join(";")
+}
+
+/// Reads an iCalendar (RFC 5545) RRULE, with or without the RRULE: prefix, as a task interval.
+/// Only rules a TaskInterval can describe are read, so rules that end (COUNT, UNTIL)
+/// or repeat within a day are errors. A weekly rule on one day is read as Weeks,
+/// and on several as DaysOfWeek. BYMONTHDAY=-1 is the last day of the month.
+///
+/// Unlike RFC 5545, which skips months that are too short for the day of the month,
+/// tasks fall back to the last day of the month.
+/// The interval isn't validated, so e.g. BYMONTHDAY=40 should be caught by the caller
+pub fn from_rrule(rule: &str) -> Result<TaskInterval, RRuleError> {
+    let rule = rule.trim();
+    let rule = match rule.get(..6) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("RRULE:") => &rule[6..],
+        _ => rule,
+    };
+
+    let mut frequency = None;
+    let mut every = 1;
+    let mut by_day = None;
+    let mut by_month_day = None;
+    let mut by_month = None;
+    for part in rule.split(';').filter(|part| !part.is_empty()) {
+        let (name, value) = match part.split_once('=') {
+            Some((name, value)) => (name.trim().to_ascii_uppercase(), value.trim().to_ascii_uppercase()),
+            None => return Err(RRuleError::Malformed(part.to_string())),
+        };
+        match name.as_str() {
+            "FREQ" => frequency = Some(value),
+            "INTERVAL" => every = parse_number(&name, &value)?,
+            "BYDAY" => by_day = Some(value),
+            "BYMONTHDAY" => by_month_day = Some(value),
+            "BYMONTH" => by_month = Some(value),
+            // Weeks start on Monday for tasks already
+            "WKST" if value == "MO" => {},
+            _ => return Err(RRuleError::Unsupported(part.to_string())),
+        }
+    }
+    if every == 0 {
+        return Err(RRuleError::InvalidValue{part: "INTERVAL".to_string(), value: "0".to_string()});
+    }
+
+    let frequency = frequency.ok_or(RRuleError::MissingFrequency)?;
+    let missing = |part: &str| RRuleError::MissingPart{frequency: frequency.clone(), part: part.to_string()};
+    let unsupported = |part: &str| RRuleError::Unsupported(format!("{} with FREQ={}", part, frequency));
+    match frequency.as_str() {
+        "DAILY" => {
+            if by_day.is_some() || by_month_day.is_some() || by_month.is_some() {
+                return Err(unsupported("BY parts"));
+            }
+            Ok(TaskInterval::Days{every})
+        },
+        "WEEKLY" => {
+            if by_month_day.is_some() || by_month.is_some() {
+                return Err(unsupported("BYMONTHDAY or BYMONTH"));
+            }
+            let by_day = by_day.ok_or_else(|| missing("BYDAY"))?;
+            let mut weekdays = by_day.split(',')
+                .map(parse_weekday)
+                .collect::<Result<Vec<u32>, RRuleError>>()?;
+            weekdays.sort_unstable();
+            weekdays.dedup();
+            match weekdays[..] {
+                [weekday] => Ok(TaskInterval::Weeks{every, weekday}),
+                _ => Ok(TaskInterval::DaysOfWeek{every, weekdays}),
+            }
+        },
+        "MONTHLY" => {
+            if by_month.is_some() {
+                return Err(unsupported("BYMONTH"));
+            }
+            match (by_day, by_month_day) {
+                (Some(_), Some(_)) => Err(unsupported("both BYDAY and BYMONTHDAY")),
+                (None, None) => Err(missing("BYDAY or BYMONTHDAY")),
+                (None, Some(day)) => {
+                    let day_of_month = parse_month_day(&day)?;
+                    Ok(TaskInterval::Months{every, day_of_month})
+                },
+                (Some(day), None) => {
+                    if day.contains(',') {
+                        return Err(unsupported("several BYDAY values"));
+                    }
+                    let invalid = || RRuleError::InvalidValue{part: "BYDAY".to_string(), value: day.clone()};
+                    // e.g. 2TU or -1FR
+                    let split_at = day.len().checked_sub(2)
+                        .filter(|split_at| day.is_char_boundary(*split_at))
+                        .ok_or_else(invalid)?;
+                    let (nth, code) = (&day[..split_at], &day[split_at..]);
+                    let weekday = parse_weekday(code)?;
+                    let week = match nth.trim_start_matches('+') {
+                        "-1" => LAST_WEEK,
+                        "" => return Err(unsupported("BYDAY without a week number")),
+                        nth => match nth.parse::<u32>() {
+                            Ok(week) if (1..LAST_WEEK).contains(&week) => week,
+                            _ => return Err(invalid()),
+                        },
+                    };
+                    Ok(TaskInterval::MonthWeekday{every, week, weekday})
+                },
+            }
+        },
+        "YEARLY" => {
+            if by_day.is_some() {
+                return Err(unsupported("BYDAY"));
+            }
+            let month = parse_number("BYMONTH", &by_month.ok_or_else(|| missing("BYMONTH"))?)?;
+            let day = parse_number("BYMONTHDAY", &by_month_day.ok_or_else(|| missing("BYMONTHDAY"))?)?;
+            Ok(TaskInterval::Years{every, month, day})
+        },
+        _ => Err(RRuleError::Unsupported(format!("FREQ={}", frequency))),
+    }
+}
+
+fn weekday_code(weekday: u32) -> &'static str {
+    WEEKDAY_CODES.get(weekday as usize).copied().unwrap_or("MO")
+}
+
+fn parse_weekday(code: &str) -> Result<u32, RRuleError> {
+    WEEKDAY_CODES.iter()
+        .position(|weekday_code| *weekday_code == code)
+        .map(|weekday| weekday as u32)
+        .ok_or_else(|| RRuleError::InvalidValue{part: "BYDAY".to_string(), value: code.to_string()})
+}
+
+fn parse_number(part: &str, value: &str) -> Result<u32, RRuleError> {
+    value.parse::<u32>().map_err(|_| RRuleError::InvalidValue{part: part.to_string(), value: value.to_string()})
+}
+
+/// Reads a day of the month, where -1 is the last day
+fn parse_month_day(value: &str) -> Result<u32, RRuleError> {
+    if value == "-1" {
+        return Ok(LAST_DAY_OF_MONTH);
+    }
+    if value.contains(',') {
+        return Err(RRuleError::Unsupported("several BYMONTHDAY values".to_string()));
+    }
+    parse_number("BYMONTHDAY", value)
+}
\ No newline at end of file
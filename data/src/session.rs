@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+use chrono::NaiveDateTime;
+
+/// A place where the user is logged in, such as a browser on one of their devices
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct SessionInfo {
+    pub id: i32,
+    pub created_at: NaiveDateTime,
+    /// The last time this session was used to make a request
+    pub last_seen: NaiveDateTime,
+    /// When this session will be logged out if it isn't used before then.
+    /// Each request pushes this back
+    pub expires_at: NaiveDateTime,
+    /// Whether this is the session that asked for the list
+    pub current: bool,
+}
\ No newline at end of file
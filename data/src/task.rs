@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
-use chrono::{NaiveDate};
+use chrono::{NaiveDate, NaiveDateTime, Datelike, Duration};
+use std::convert::TryInto;
 use crate::icon::{TaskIcon};
+use crate::error::FieldError;
+use crate::validate::*;
+use crate::rrule::{self, RRuleError};
 
 /// The fields that must be specified when creating a new task
 /// The rest will be set to default values
@@ -9,12 +13,59 @@ pub struct NewTask {
     pub name: String,
     pub description: String,
     pub bspts: i32,
+    /// Ignored if the task is given an rrule
+    #[serde(default)]
     pub frequency: TaskInterval, 
     pub icon: TaskIcon,
+    /// How many points are lost each time this task is missed
+    #[serde(default)]
+    pub penalty: PenaltyPolicy,
+    /// How many extra points are earned for doing this task on time several times in a row
+    #[serde(default)]
+    pub streak_bonus: StreakBonus,
+    /// An iCalendar RRULE to create the task's frequency from instead,
+    /// e.g. `FREQ=WEEKLY;BYDAY=MO,WE,FR`
+    #[serde(default)]
+    pub rrule: Option<String>,
+    /// Whether the task is shared with the user's household, so any member can do it
+    #[serde(default)]
+    pub shared: bool,
+}
+
+impl NewTask {
+    /// The frequency the task repeats at, which is read from the rrule if there is one
+    pub fn schedule(&self) -> Result<TaskInterval, RRuleError> {
+        match &self.rrule {
+            Some(rule) => rrule::from_rrule(rule),
+            None => Ok(self.frequency.clone()),
+        }
+    }
+}
+
+impl Validate for NewTask {
+    fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = vec![];
+        check_name(&mut errors, &self.name);
+        check_description(&mut errors, &self.description);
+        check_bspts(&mut errors, self.bspts);
+        errors.extend(self.penalty.field_errors());
+        errors.extend(self.streak_bonus.field_errors());
+        match self.schedule() {
+            Ok(frequency) if self.rrule.is_some() => {
+                // The interval came from the rrule, so that's where the problem is
+                errors.extend(frequency.field_errors().into_iter().map(|error| {
+                    FieldError::new("rrule", &error.message)
+                }));
+            },
+            Ok(frequency) => errors.extend(frequency.field_errors()),
+            Err(error) => errors.push(FieldError::new("rrule", &error.to_string())),
+        }
+        errors
+    }
 }
 
 /// The interval at which this task should be completed
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
 pub enum TaskInterval {
     /// Task to be completed every X days
     Days{every: u32},
@@ -23,7 +74,72 @@ pub enum TaskInterval {
     Weeks{every: u32, weekday: u32},
     /// Task to be completed every X months on specified day of the month
     /// Day of the month is defined so 1st=1, 2nd=2, etc.
+    /// In months that are too short it falls on the last day of the month instead,
+    /// so LAST_DAY_OF_MONTH is always the end of the month
     Months{every: u32, day_of_month: u32},
+    /// Task to be completed on several days of the week, e.g. Mon/Wed/Fri,
+    /// every X weeks. Weekdays are defined with Monday=0 as for Weeks
+    DaysOfWeek{every: u32, weekdays: Vec<u32>},
+    /// Task to be completed every X months on the nth weekday of the month,
+    /// e.g. the second Tuesday. Week is 1 for the first weekday in the month up to 4
+    /// for the fourth, or LAST_WEEK for the last. Weekday is defined with Monday=0
+    MonthWeekday{every: u32, week: u32, weekday: u32},
+    /// Task to be completed every X years on the given day, e.g. April 15th.
+    /// Month is defined with January=1. Feb 29th falls on Feb 28th in other years
+    Years{every: u32, month: u32, day: u32},
+    /// Task to be completed once by the due date. It's archived once it's done
+    /// rather than being reset
+    Once{due: NaiveDate},
+}
+
+/// Tasks are done every day unless they say otherwise
+impl Default for TaskInterval {
+    fn default() -> Self {
+        TaskInterval::Days{every: 1}
+    }
+}
+
+/// The day of the month that always falls on the last day of the month
+pub const LAST_DAY_OF_MONTH: u32 = 31;
+/// The week of MonthWeekday that means the last of the weekdays in the month
+pub const LAST_WEEK: u32 = 5;
+
+/// Gets the number of days in the month, where January=1
+fn days_in_month(year: i32, month: u32) -> Option<u32> {
+    if !(1..=12).contains(&month) {
+        return None;
+    }
+    let (next_year, next_month) = if month == 12 { (year.checked_add(1)?, 1) } else { (year, month + 1) };
+    let first_of_next_month = NaiveDate::from_ymd_opt(next_year, next_month, 1)?;
+    Some(first_of_next_month.pred_opt()?.day())
+}
+
+/// Gets the given day of the month, or the last day of the month if there aren't that many days in it
+fn clamped_date(year: i32, month: u32, day_of_month: u32) -> Option<NaiveDate> {
+    let day = day_of_month.min(days_in_month(year, month)?);
+    NaiveDate::from_ymd_opt(year, month, day)
+}
+
+/// Gets the year and month (January=1) that is some number of months after the date's month
+fn add_months(date: NaiveDate, months: i64) -> Option<(i32, u32)> {
+    let month0 = date.month0() as i64 + months;
+    let year = date.year() as i64 + month0 / 12;
+    Some((year.try_into().ok()?, (month0 % 12) as u32 + 1))
+}
+
+/// Gets the nth weekday of the month, e.g. the second Tuesday, or the last one for LAST_WEEK
+fn nth_weekday(year: i32, month: u32, week: u32, weekday: u32) -> Option<NaiveDate> {
+    if week == LAST_WEEK {
+        let last_day = clamped_date(year, month, LAST_DAY_OF_MONTH)?;
+        let last_weekday = last_day.weekday().num_days_from_monday();
+        let days_back = (last_weekday + 7 - weekday) % 7;
+        last_day.checked_sub_signed(Duration::days(days_back as i64))
+    } else {
+        let first_day = NaiveDate::from_ymd_opt(year, month, 1)?;
+        let first_weekday = first_day.weekday().num_days_from_monday();
+        let days_forward = (weekday + 7 - first_weekday) % 7 + 7 * (week - 1);
+        first_day.checked_add_signed(Duration::days(days_forward as i64))
+    }
 }
 
 impl TaskInterval {
@@ -33,7 +149,142 @@ impl TaskInterval {
             Self::Days{every} => *every,
             Self::Weeks{every, weekday:_} => *every,
             Self::Months{every, day_of_month:_} => *every,
+            Self::DaysOfWeek{every, ..} => *every,
+            Self::MonthWeekday{every, ..} => *every,
+            Self::Years{every, ..} => *every,
+            Self::Once{..} => 1,
+        }
+    }
+
+    /// Whether the task comes back after it's done, which all but one-off tasks do
+    pub fn repeats(&self) -> bool {
+        !matches!(self, Self::Once{..})
+    }
+
+    /// Works out the date a new task is first due on, which is the due date for a one-off task,
+    /// even if that's today, and the next reset after today for the rest
+    pub fn first_reset(&self, today: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Self::Once{due} => Some(*due).filter(|due| *due >= today),
+            _ => self.next_reset_after(today),
+        }
+    }
+
+    /// Works out the next date the task resets on after the given date.
+    /// The result is always after the date, and is:
+    /// * Days: `every` days later
+    /// * Weeks: the first `weekday` after the date, then `every - 1` more weeks
+    /// * Months: the first `day_of_month` after the date, then `every - 1` more months,
+    ///   with the day moved back to the end of the month in months that are too short
+    /// * DaysOfWeek: the next of the `weekdays` later in the same week (weeks start on Monday),
+    ///   otherwise the first of them in the week after, then `every - 1` more weeks
+    /// * MonthWeekday: the first such weekday after the date, then `every - 1` more months
+    /// * Years: the first such day after the date, then `every - 1` more years
+    /// * Once: the due date, if it's after the date
+    ///
+    /// So a task that's done on the day it resets always resets again exactly one interval later.
+    /// Returns None if the interval isn't valid, or if the date would be out of range
+    pub fn next_reset_after(&self, date: NaiveDate) -> Option<NaiveDate> {
+        // A task can't repeat every 0 days, so treat that as every 1
+        let invalid = self.field_errors().iter().any(|error| error.field != "frequency.every");
+        if invalid {
+            return None;
+        }
+        let extra = self.every().max(1) as i64 - 1;
+        let current_weekday = date.weekday().num_days_from_monday() as i64;
+        match self {
+            Self::Days{..} => {
+                date.checked_add_signed(Duration::days(extra + 1))
+            },
+            Self::Weeks{weekday, ..} => {
+                // Between 1 and 7 days, so that the same weekday is the following week
+                let days_to_weekday = (*weekday as i64 - current_weekday + 6).rem_euclid(7) + 1;
+                date.checked_add_signed(Duration::days(days_to_weekday + 7 * extra))
+            },
+            Self::Months{day_of_month, ..} => {
+                let this_month = clamped_date(date.year(), date.month(), *day_of_month)?;
+                let months_to_add = if this_month > date { extra } else { extra + 1 };
+                let (year, month) = add_months(date, months_to_add)?;
+                clamped_date(year, month, *day_of_month)
+            },
+            Self::DaysOfWeek{weekdays, ..} => {
+                let later_this_week = weekdays.iter()
+                    .map(|weekday| *weekday as i64)
+                    .filter(|weekday| *weekday > current_weekday)
+                    .min();
+                let days_to_jump = match later_this_week {
+                    Some(weekday) => weekday - current_weekday,
+                    None => {
+                        let first_weekday = *weekdays.iter().min()? as i64;
+                        first_weekday + 7 - current_weekday + 7 * extra
+                    },
+                };
+                date.checked_add_signed(Duration::days(days_to_jump))
+            },
+            Self::MonthWeekday{week, weekday, ..} => {
+                let this_month = nth_weekday(date.year(), date.month(), *week, *weekday)?;
+                let months_to_add = if this_month > date { extra } else { extra + 1 };
+                let (year, month) = add_months(date, months_to_add)?;
+                nth_weekday(year, month, *week, *weekday)
+            },
+            Self::Years{month, day, ..} => {
+                let this_year = clamped_date(date.year(), *month, *day)?;
+                let years_to_add = if this_year > date { extra } else { extra + 1 };
+                let year: i32 = (date.year() as i64 + years_to_add).try_into().ok()?;
+                clamped_date(year, *month, *day)
+            },
+            Self::Once{due} => {
+                Some(*due).filter(|due| *due > date)
+            },
+        }
+    }
+
+    /// Lists any problems with the interval, e.g. a weekday that doesn't exist
+    pub fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = vec![];
+        let weekday_error = || FieldError::new("frequency.weekday", "The weekday must be from Monday to Sunday");
+        if self.every() == 0 {
+            errors.push(FieldError::new("frequency.every", "The task must repeat at least every 1"));
         }
+        match self {
+            Self::Days{..} => {},
+            Self::Weeks{weekday, ..} => {
+                if *weekday > 6 {
+                    errors.push(weekday_error());
+                }
+            },
+            Self::Months{day_of_month, ..} => {
+                if *day_of_month < 1 || *day_of_month > LAST_DAY_OF_MONTH {
+                    errors.push(FieldError::new("frequency.day_of_month", "The day of the month must be from 1 to 31"));
+                }
+            },
+            Self::DaysOfWeek{weekdays, ..} => {
+                if weekdays.is_empty() {
+                    errors.push(FieldError::new("frequency.weekdays", "At least one day of the week must be picked"));
+                } else if weekdays.iter().any(|weekday| *weekday > 6) {
+                    errors.push(FieldError::new("frequency.weekdays", "The weekdays must be from Monday to Sunday"));
+                }
+            },
+            Self::MonthWeekday{week, weekday, ..} => {
+                if *week < 1 || *week > LAST_WEEK {
+                    errors.push(FieldError::new("frequency.week", "The week must be the first to the fourth, or the last"));
+                }
+                if *weekday > 6 {
+                    errors.push(weekday_error());
+                }
+            },
+            Self::Years{month, day, ..} => {
+                // The day is checked against a leap year so that Feb 29th is allowed
+                if !(1..=12).contains(month) {
+                    errors.push(FieldError::new("frequency.month", "The month must be from January to December"));
+                } else if *day < 1 || days_in_month(2000, *month).is_some_and(|days| *day > days) {
+                    errors.push(FieldError::new("frequency.day", "That day isn't in the month"));
+                }
+            },
+            // Whether the due date has passed can only be checked against today
+            Self::Once{..} => {},
+        }
+        errors
     }
 
     pub fn in_days(self: &Self) -> bool {
@@ -58,6 +309,132 @@ impl TaskInterval {
     }
 }
 
+/// How many points are lost when a task's reset date passes
+/// without it being completed
+#[derive(Default, Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PenaltyPolicy {
+    /// Missing the task costs nothing
+    #[default]
+    None,
+    /// Lose the same number of points for every miss
+    Flat{pts: u32},
+    /// Lose pts more for each consecutive miss: pts, 2*pts, 3*pts...
+    Linear{pts: u32},
+    /// Double the loss for each consecutive miss: pts, 2*pts, 4*pts...
+    Escalating{pts: u32},
+}
+
+impl PenaltyPolicy {
+    /// The most times the escalating penalty will double
+    const MAX_DOUBLINGS: u32 = 16;
+    /// The most points a penalty can start at
+    pub const MAX_PTS: u32 = 1000;
+
+    /// The base number of points lost by this policy
+    pub fn pts(&self) -> u32 {
+        match self {
+            Self::None => 0,
+            Self::Flat{pts} => *pts,
+            Self::Linear{pts} => *pts,
+            Self::Escalating{pts} => *pts,
+        }
+    }
+
+    /// Returns the number of points that are lost for a miss
+    /// * consecutive_misses: How many times in a row the task has been missed,
+    ///   including this miss. So the first miss is 1.
+    pub fn penalty_for_miss(&self, consecutive_misses: u32) -> i32 {
+        let misses = consecutive_misses.max(1);
+        let penalty = match self {
+            Self::None => 0,
+            Self::Flat{pts} => *pts,
+            Self::Linear{pts} => pts.saturating_mul(misses),
+            Self::Escalating{pts} => {
+                let doublings = (misses - 1).min(Self::MAX_DOUBLINGS);
+                pts.saturating_mul(1 << doublings)
+            }
+        };
+        penalty.min(i32::MAX as u32) as i32
+    }
+}
+
+impl Validate for PenaltyPolicy {
+    fn field_errors(&self) -> Vec<FieldError> {
+        if self.pts() > Self::MAX_PTS {
+            vec![FieldError::new(
+                "penalty",
+                &format!("The penalty can't be more than {} pts", Self::MAX_PTS),
+            )]
+        } else {
+            vec![]
+        }
+    }
+}
+
+/// How many extra points are earned for completing a task on time several times in a row.
+/// The first completion of a streak never earns a bonus
+#[derive(Default, Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StreakBonus {
+    /// Streaks earn nothing extra
+    #[default]
+    None,
+    /// Earn the same extra points for each completion after the first in a streak
+    Flat{pts: u32},
+    /// Earn percent more of the task's points for each completion in the streak after the first,
+    /// e.g. 10 earns 10%, 20%, 30%... extra, rounded down
+    Multiplier{percent: u32},
+}
+
+impl StreakBonus {
+    /// The most completions in a row that the multiplier keeps growing for
+    pub const MAX_MULTIPLIER_STEPS: u32 = 10;
+    /// The largest percent the multiplier can grow by each time
+    pub const MAX_PERCENT: u32 = 100;
+
+    /// The points or percent of this bonus
+    pub fn amount(&self) -> u32 {
+        match self {
+            Self::None => 0,
+            Self::Flat{pts} => *pts,
+            Self::Multiplier{percent} => *percent,
+        }
+    }
+
+    /// Returns the extra points earned for a completion
+    /// * streak: How many times in a row the task has been completed on time,
+    ///   including this completion. So the first completion is 1.
+    /// * bspts: The points the task is worth
+    pub fn bonus_for_streak(&self, streak: u32, bspts: i32) -> i32 {
+        let steps = streak.saturating_sub(1);
+        if steps == 0 {
+            return 0;
+        }
+        let bonus = match self {
+            Self::None => 0,
+            Self::Flat{pts} => *pts as i64,
+            Self::Multiplier{percent} => {
+                let steps = steps.min(Self::MAX_MULTIPLIER_STEPS) as i64;
+                bspts.max(0) as i64 * *percent as i64 * steps / 100
+            }
+        };
+        bonus.min(i32::MAX as i64) as i32
+    }
+}
+
+impl Validate for StreakBonus {
+    fn field_errors(&self) -> Vec<FieldError> {
+        match self {
+            Self::Multiplier{percent} if *percent > Self::MAX_PERCENT => {
+                vec![FieldError::new(
+                    "streak_bonus",
+                    &format!("The streak bonus can't be more than {} percent", Self::MAX_PERCENT),
+                )]
+            },
+            _ => vec![],
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Task {
     pub id: i32,
@@ -65,12 +442,28 @@ pub struct Task {
     pub description: String,
     pub user_id: i32,
     pub bspts: i32,
+    /// The total number of points lost by missing this task
     pub pts_lost: i32,
+    /// How many times in a row this task has been missed
+    pub misses: i32,
+    pub penalty: PenaltyPolicy,
+    /// How many times in a row this task has been completed on time
+    pub streak: i32,
+    /// The longest streak this task has had
+    pub best_streak: i32,
+    pub streak_bonus: StreakBonus,
     pub is_done: bool,
+    /// When the task was archived, either by being deleted or by being a one-off task that was done
+    pub archived_at: Option<NaiveDateTime>,
     pub days_to_next_reset: i64,
     pub next_reset: NaiveDate,
     pub frequency: TaskInterval,
+    /// The frequency written as an iCalendar RRULE, for sharing with calendar tools
+    pub rrule: String,
     pub icon: TaskIcon,
+    /// The household the task is shared with, none if only its owner can see it
+    #[serde(default)]
+    pub household_id: Option<i32>,
 }
 
 impl Into<NewTask> for Task {
@@ -81,6 +474,29 @@ impl Into<NewTask> for Task {
             bspts: self.bspts,
             frequency: self.frequency.clone(), 
             icon: self.icon.clone(),
+            penalty: self.penalty,
+            streak_bonus: self.streak_bonus,
+            rrule: None,
+            shared: self.household_id.is_some(),
         }
     }
+}
+
+/// A record of one time a task was completed
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct TaskCompletion {
+    pub id: i32,
+    pub task_id: i32,
+    /// The name of the task as it is now
+    pub task_name: String,
+    /// The day the task was completed on, in the user's time zone
+    pub completed_on: NaiveDate,
+    /// The points that were awarded for completing it, not counting the streak bonus
+    pub bspts: i32,
+    /// The extra points that were awarded for the streak
+    pub bonus: i32,
+    /// How many times in a row the task had been completed on time, including this time
+    pub streak: i32,
+    /// False if the task had been missed since it was last completed
+    pub on_time: bool,
 }
\ No newline at end of file
@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+use crate::error::FieldError;
+use crate::validate::{Validate, MAX_UNAME_LEN};
 
 /// The fields that must be specified to create a new user or log in.
 /// The password will be
@@ -8,9 +10,67 @@ pub struct NewUser {
     pub password: String,
 }
 
+/// Only checked when signing up. Logging in with a name that
+/// isn't valid just finds no user
+impl Validate for NewUser {
+    fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = vec![];
+        let uname_chars_ok = self.uname.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.');
+        if self.uname.is_empty() {
+            errors.push(FieldError::new("uname", "The username can't be empty"));
+        } else if self.uname.len() > MAX_UNAME_LEN {
+            errors.push(FieldError::new("uname", &format!("The username can't be more than {} characters", MAX_UNAME_LEN)));
+        } else if !uname_chars_ok {
+            errors.push(FieldError::new("uname", "The username can only have letters, numbers, _, - and ."));
+        }
+        if self.password.is_empty() {
+            errors.push(FieldError::new("password", "The password can't be empty"));
+        }
+        errors
+    }
+}
+
 /// The fields that are exposed to the user
 #[derive(Default, Deserialize, Serialize, Clone, Debug)]
 pub struct User {
     pub uname: String,
     pub bspts: i32,
+    /// The IANA name of the user's time zone, e.g. America/Denver.
+    /// Used to work out which day it is for the user
+    pub time_zone: String,
+    /// The household the user belongs to, if any
+    #[serde(default)]
+    pub household_id: Option<i32>,
+}
+
+/// Asks to change the user's password. The current password
+/// must be given so that someone at an unattended device can't do it
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct PasswordChange {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+impl Validate for PasswordChange {
+    fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = vec![];
+        if self.new_password.is_empty() {
+            errors.push(FieldError::new("new_password", "The new password can't be empty"));
+        }
+        errors
+    }
+}
+
+/// Asks to delete the user's account along with everything in it
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct AccountDeletion {
+    pub password: String,
+}
+
+/// The secret token that lets calendar apps read the user's tasks without logging in.
+/// Only a hash of it is kept, so it can only be seen when it's made
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct CalendarToken {
+    pub token: String,
 }
\ No newline at end of file
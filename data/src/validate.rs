@@ -0,0 +1,62 @@
+use crate::error::{ApiError, FieldError};
+
+/// The most characters a task or reward name can have
+pub const MAX_NAME_LEN: usize = 64;
+/// The most characters a task or reward description can have
+pub const MAX_DESCRIPTION_LEN: usize = 1000;
+/// The most characters a username can have
+pub const MAX_UNAME_LEN: usize = 64;
+
+/// Something sent to the backend that can be checked before it's used.
+/// The same checks are run by the frontend, so forms can show what's
+/// wrong without a round trip, and by the backend, which doesn't trust them
+pub trait Validate {
+    /// Lists everything that is wrong with the fields. Empty if they're all fine
+    fn field_errors(&self) -> Vec<FieldError>;
+
+    /// Returns a validation error listing the invalid fields, if there are any
+    fn validate(&self) -> Result<(), ApiError> {
+        let fields = self.field_errors();
+        if fields.is_empty() {
+            Ok(())
+        } else {
+            Err(ApiError::Validation{
+                message: "Some of the fields aren't valid".to_string(),
+                fields,
+            })
+        }
+    }
+}
+
+/// Gets the message for the first error with the given field name, for showing next to that field
+pub fn error_for<'a>(errors: &'a [FieldError], field: &str) -> Option<&'a str> {
+    errors.iter()
+        .find(|error| error.field == field)
+        .map(|error| error.message.as_str())
+}
+
+/// Checks that a name has something in it and isn't too long
+pub(crate) fn check_name(errors: &mut Vec<FieldError>, name: &str) {
+    if name.trim().is_empty() {
+        errors.push(FieldError::new("name", "The name can't be empty"));
+    } else if name.chars().count() > MAX_NAME_LEN {
+        errors.push(FieldError::new("name", &format!("The name can't be more than {} characters", MAX_NAME_LEN)));
+    }
+}
+
+/// Checks that a description isn't too long. It may be empty
+pub(crate) fn check_description(errors: &mut Vec<FieldError>, description: &str) {
+    if description.chars().count() > MAX_DESCRIPTION_LEN {
+        errors.push(FieldError::new(
+            "description",
+            &format!("The description can't be more than {} characters", MAX_DESCRIPTION_LEN),
+        ));
+    }
+}
+
+/// Checks that a number of points isn't negative
+pub(crate) fn check_bspts(errors: &mut Vec<FieldError>, bspts: i32) {
+    if bspts < 0 {
+        errors.push(FieldError::new("bspts", "The points can't be negative"));
+    }
+}
\ No newline at end of file
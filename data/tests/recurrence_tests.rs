@@ -0,0 +1,247 @@
+use data::task::{TaskInterval, LAST_WEEK};
+use chrono::{NaiveDate, Datelike, Duration};
+
+/* HELPER FUNCTIONS */
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).expect("Not a real date")
+}
+
+/// Every day from the start of 1999 to the end of 2032, which covers
+/// plenty of leap years, including 2000 which is one despite being a century
+fn all_dates() -> impl Iterator<Item = NaiveDate> {
+    let end = date(2032, 12, 31);
+    date(1999, 1, 1).iter_days().take_while(move |day| *day <= end)
+}
+
+fn days_in_month(day: NaiveDate) -> u32 {
+    let first_of_next_month = if day.month() == 12 {
+        date(day.year() + 1, 1, 1)
+    } else {
+        date(day.year(), day.month() + 1, 1)
+    };
+    first_of_next_month.pred_opt().unwrap().day()
+}
+
+/// The number of months from one date's month to the other's
+fn months_between(from: NaiveDate, to: NaiveDate) -> i32 {
+    (to.year() - from.year()) * 12 + to.month() as i32 - from.month() as i32
+}
+
+/* TESTS */
+
+#[test]
+fn days_add_every_days() {
+    for every in 1..=10 {
+        let interval = TaskInterval::Days{every};
+        for day in all_dates() {
+            let next = interval.next_reset_after(day).expect("No next reset");
+            assert_eq!(next - day, Duration::days(every as i64), "{:?} after {}", interval, day);
+        }
+    }
+}
+
+#[test]
+fn weeks_land_on_the_weekday() {
+    for every in 1..=4 {
+        for weekday in 0..=6 {
+            let interval = TaskInterval::Weeks{every, weekday};
+            for day in all_dates() {
+                let next = interval.next_reset_after(day).expect("No next reset");
+                assert_eq!(next.weekday().num_days_from_monday(), weekday, "{:?} after {}", interval, day);
+                // The first weekday after the day, plus the extra weeks
+                let days = (next - day).num_days();
+                let days_past_extra_weeks = days - 7 * (every as i64 - 1);
+                assert!((1..=7).contains(&days_past_extra_weeks), "{:?} after {} is {}", interval, day, next);
+            }
+        }
+    }
+}
+
+#[test]
+fn every_two_weeks_is_fourteen_days() {
+    let interval = TaskInterval::Weeks{every: 2, weekday: 0};
+    let monday = date(2021, 3, 1);
+    assert_eq!(interval.next_reset_after(monday), Some(date(2021, 3, 15)));
+    // Done on the Sunday before, so the Monday after is the first of the two weeks
+    assert_eq!(interval.next_reset_after(date(2021, 2, 28)), Some(date(2021, 3, 8)));
+}
+
+#[test]
+fn months_land_on_the_day_or_the_end_of_the_month() {
+    for every in [1, 2, 3, 6, 12, 13] {
+        for day_of_month in 1..=31 {
+            let interval = TaskInterval::Months{every, day_of_month};
+            for day in all_dates() {
+                let next = interval.next_reset_after(day).expect("No next reset");
+                assert!(next > day, "{:?} after {} is {}", interval, day, next);
+                assert_eq!(next.day(), day_of_month.min(days_in_month(next)), "{:?} after {}", interval, day);
+                let months = months_between(day, next);
+                assert!(
+                    months == every as i32 || months == every as i32 - 1,
+                    "{:?} after {} is {}", interval, day, next
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn done_on_the_reset_day_resets_one_interval_later() {
+    let intervals = vec![
+        TaskInterval::Days{every: 3},
+        TaskInterval::Weeks{every: 2, weekday: 4},
+        TaskInterval::Months{every: 1, day_of_month: 31},
+        TaskInterval::Months{every: 12, day_of_month: 29},
+    ];
+    for interval in intervals {
+        let mut reset = date(1999, 1, 1);
+        while reset.year() < 2033 {
+            let next = interval.next_reset_after(reset).expect("No next reset");
+            match interval {
+                TaskInterval::Days{every} => assert_eq!((next - reset).num_days(), every as i64),
+                TaskInterval::Weeks{every, ..} if reset.year() > 1999 => {
+                    assert_eq!((next - reset).num_days(), 7 * every as i64)
+                },
+                TaskInterval::Months{every, ..} if reset.year() > 1999 => {
+                    assert_eq!(months_between(reset, next), every as i32)
+                },
+                _ => {},
+            }
+            reset = next;
+        }
+    }
+}
+
+#[test]
+fn month_ends_and_leap_years() {
+    let end_of_month = TaskInterval::Months{every: 1, day_of_month: 31};
+    assert_eq!(end_of_month.next_reset_after(date(2021, 1, 31)), Some(date(2021, 2, 28)));
+    assert_eq!(end_of_month.next_reset_after(date(2024, 1, 31)), Some(date(2024, 2, 29)));
+    assert_eq!(end_of_month.next_reset_after(date(2024, 2, 29)), Some(date(2024, 3, 31)));
+    assert_eq!(end_of_month.next_reset_after(date(2021, 4, 30)), Some(date(2021, 5, 31)));
+
+    let leap_day = TaskInterval::Months{every: 12, day_of_month: 29};
+    assert_eq!(leap_day.next_reset_after(date(2024, 2, 29)), Some(date(2025, 2, 28)));
+    assert_eq!(leap_day.next_reset_after(date(2027, 2, 28)), Some(date(2028, 2, 29)));
+    assert_eq!(leap_day.next_reset_after(date(2099, 2, 28)), Some(date(2100, 2, 28)));
+
+    // Later this month, so it doesn't wait for next month
+    let fifteenth = TaskInterval::Months{every: 1, day_of_month: 15};
+    assert_eq!(fifteenth.next_reset_after(date(2021, 12, 3)), Some(date(2021, 12, 15)));
+    assert_eq!(fifteenth.next_reset_after(date(2021, 12, 15)), Some(date(2022, 1, 15)));
+}
+
+#[test]
+fn days_of_week_land_on_the_next_picked_day() {
+    let weekday_sets: Vec<Vec<u32>> = vec![vec![0, 2, 4], vec![6], vec![1, 5], (0..7).collect()];
+    for every in 1..=3 {
+        for weekdays in &weekday_sets {
+            let interval = TaskInterval::DaysOfWeek{every, weekdays: weekdays.clone()};
+            for day in all_dates() {
+                let next = interval.next_reset_after(day).expect("No next reset");
+                let next_weekday = next.weekday().num_days_from_monday();
+                let current_weekday = day.weekday().num_days_from_monday();
+                assert!(weekdays.contains(&next_weekday), "{:?} after {} is {}", interval, day, next);
+                let later_this_week = weekdays.iter().any(|weekday| *weekday > current_weekday);
+                if later_this_week {
+                    // The next picked day this week, with none skipped over
+                    assert!(next_weekday > current_weekday && (next - day).num_days() < 7);
+                    assert!(!weekdays.iter().any(|weekday| *weekday > current_weekday && *weekday < next_weekday));
+                } else {
+                    // The first picked day of a later week
+                    let weeks_on = (next - day).num_days() + current_weekday as i64 - next_weekday as i64;
+                    assert_eq!(weeks_on, 7 * every as i64, "{:?} after {} is {}", interval, day, next);
+                    assert_eq!(Some(&next_weekday), weekdays.iter().min());
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn month_weekdays_land_on_the_nth_weekday() {
+    for every in [1, 2, 12] {
+        for week in 1..=LAST_WEEK {
+            for weekday in 0..=6 {
+                let interval = TaskInterval::MonthWeekday{every, week, weekday};
+                for day in all_dates() {
+                    let next = interval.next_reset_after(day).expect("No next reset");
+                    assert!(next > day, "{:?} after {} is {}", interval, day, next);
+                    assert_eq!(next.weekday().num_days_from_monday(), weekday);
+                    if week == LAST_WEEK {
+                        assert!(next.day() + 7 > days_in_month(next), "{:?} after {} is {}", interval, day, next);
+                    } else {
+                        assert_eq!((next.day() - 1) / 7 + 1, week, "{:?} after {} is {}", interval, day, next);
+                    }
+                    let months = months_between(day, next);
+                    assert!(months == every as i32 || months == every as i32 - 1);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn nth_weekday_examples() {
+    let second_tuesday = TaskInterval::MonthWeekday{every: 1, week: 2, weekday: 1};
+    assert_eq!(second_tuesday.next_reset_after(date(2021, 6, 1)), Some(date(2021, 6, 8)));
+    assert_eq!(second_tuesday.next_reset_after(date(2021, 6, 8)), Some(date(2021, 7, 13)));
+    let last_friday = TaskInterval::MonthWeekday{every: 1, week: LAST_WEEK, weekday: 4};
+    assert_eq!(last_friday.next_reset_after(date(2021, 4, 30)), Some(date(2021, 5, 28)));
+    let mon_wed_fri = TaskInterval::DaysOfWeek{every: 1, weekdays: vec![0, 2, 4]};
+    // Monday 2021-03-01
+    assert_eq!(mon_wed_fri.next_reset_after(date(2021, 3, 1)), Some(date(2021, 3, 3)));
+    assert_eq!(mon_wed_fri.next_reset_after(date(2021, 3, 5)), Some(date(2021, 3, 8)));
+}
+
+#[test]
+fn years_land_on_the_day() {
+    for every in 1..=3 {
+        for (month, day_of_month) in [(1, 1), (2, 29), (4, 15), (12, 31)] {
+            let interval = TaskInterval::Years{every, month, day: day_of_month};
+            for day in all_dates() {
+                let next = interval.next_reset_after(day).expect("No next reset");
+                assert!(next > day, "{:?} after {} is {}", interval, day, next);
+                assert_eq!(next.month(), month);
+                assert_eq!(next.day(), day_of_month.min(days_in_month(next)));
+                let years = next.year() - day.year();
+                assert!(years == every as i32 || years == every as i32 - 1);
+            }
+        }
+    }
+    let taxes = TaskInterval::Years{every: 1, month: 4, day: 15};
+    assert_eq!(taxes.next_reset_after(date(2021, 4, 15)), Some(date(2022, 4, 15)));
+    assert_eq!(taxes.next_reset_after(date(2021, 1, 1)), Some(date(2021, 4, 15)));
+}
+
+#[test]
+fn invalid_intervals_have_no_reset() {
+    let day = date(2021, 6, 1);
+    assert_eq!(TaskInterval::Weeks{every: 1, weekday: 7}.next_reset_after(day), None);
+    assert_eq!(TaskInterval::Months{every: 1, day_of_month: 0}.next_reset_after(day), None);
+    assert_eq!(TaskInterval::Months{every: 1, day_of_month: 32}.next_reset_after(day), None);
+    assert_eq!(NaiveDate::MAX.pred_opt().and_then(|day| TaskInterval::Days{every: 5}.next_reset_after(day)), None);
+    assert_eq!(TaskInterval::DaysOfWeek{every: 1, weekdays: vec![]}.next_reset_after(day), None);
+    assert_eq!(TaskInterval::MonthWeekday{every: 1, week: 6, weekday: 0}.next_reset_after(day), None);
+    assert_eq!(TaskInterval::Years{every: 1, month: 2, day: 30}.next_reset_after(day), None);
+    // Every 0 is treated as every 1 so the date still moves forward
+    assert_eq!(TaskInterval::Days{every: 0}.next_reset_after(day), Some(date(2021, 6, 2)));
+}
+
+#[test]
+fn one_off_tasks_are_due_once() {
+    let due = date(2021, 6, 10);
+    let once = TaskInterval::Once{due};
+    assert!(!once.repeats());
+    assert!(TaskInterval::Days{every: 1}.repeats());
+    // A new task can be due today, but it never resets on or after its due date
+    assert_eq!(once.first_reset(date(2021, 6, 1)), Some(due));
+    assert_eq!(once.first_reset(due), Some(due));
+    assert_eq!(once.first_reset(date(2021, 6, 11)), None);
+    assert_eq!(once.next_reset_after(date(2021, 6, 9)), Some(due));
+    assert_eq!(once.next_reset_after(due), None);
+    // Other intervals are first due after today
+    let days = TaskInterval::Days{every: 2};
+    assert_eq!(days.first_reset(due), days.next_reset_after(due));
+}
\ No newline at end of file
@@ -0,0 +1,89 @@
+use data::task::{TaskInterval, LAST_DAY_OF_MONTH, LAST_WEEK};
+use data::rrule::{to_rrule, from_rrule, RRuleError};
+
+/// One of each kind of interval, along with how it's written as an RRULE
+fn examples() -> Vec<(TaskInterval, &'static str)> {
+    vec![
+        (TaskInterval::Days{every: 1}, "FREQ=DAILY"),
+        (TaskInterval::Days{every: 3}, "FREQ=DAILY;INTERVAL=3"),
+        (TaskInterval::Weeks{every: 2, weekday: 0}, "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO"),
+        (TaskInterval::DaysOfWeek{every: 1, weekdays: vec![0, 2, 4]}, "FREQ=WEEKLY;BYDAY=MO,WE,FR"),
+        (TaskInterval::Months{every: 1, day_of_month: 15}, "FREQ=MONTHLY;BYMONTHDAY=15"),
+        (TaskInterval::Months{every: 1, day_of_month: LAST_DAY_OF_MONTH}, "FREQ=MONTHLY;BYMONTHDAY=-1"),
+        (TaskInterval::MonthWeekday{every: 1, week: 2, weekday: 1}, "FREQ=MONTHLY;BYDAY=2TU"),
+        (TaskInterval::MonthWeekday{every: 3, week: LAST_WEEK, weekday: 4}, "FREQ=MONTHLY;INTERVAL=3;BYDAY=-1FR"),
+        (TaskInterval::Years{every: 1, month: 4, day: 15}, "FREQ=YEARLY;BYMONTH=4;BYMONTHDAY=15"),
+    ]
+}
+
+#[test]
+fn intervals_are_written_as_rrules() {
+    for (interval, rule) in examples() {
+        assert_eq!(to_rrule(&interval), rule);
+    }
+}
+
+#[test]
+fn rrules_are_read_as_intervals() {
+    for (interval, rule) in examples() {
+        assert_eq!(from_rrule(rule), Ok(interval.clone()));
+        // The prefix, case and order of the parts don't matter
+        let mut parts: Vec<&str> = rule.split(';').collect();
+        parts.reverse();
+        let reordered = format!("rrule:{}", parts.join(";").to_lowercase());
+        assert_eq!(from_rrule(&reordered), Ok(interval));
+    }
+}
+
+#[test]
+fn rrules_round_trip() {
+    for every in 1..=4 {
+        for weekday in 0..=6 {
+            let intervals = vec![
+                TaskInterval::Days{every},
+                TaskInterval::Weeks{every, weekday},
+                TaskInterval::DaysOfWeek{every, weekdays: vec![weekday, (weekday + 3) % 7]},
+                TaskInterval::MonthWeekday{every, week: weekday % LAST_WEEK + 1, weekday},
+            ];
+            for interval in intervals {
+                let mut expected = interval.clone();
+                if let TaskInterval::DaysOfWeek{weekdays, ..} = &mut expected {
+                    weekdays.sort_unstable();
+                }
+                assert_eq!(from_rrule(&to_rrule(&interval)), Ok(expected));
+            }
+        }
+        for day_of_month in 1..=31 {
+            let interval = TaskInterval::Months{every, day_of_month};
+            assert_eq!(from_rrule(&to_rrule(&interval)), Ok(interval));
+        }
+        for month in 1..=12 {
+            let interval = TaskInterval::Years{every, month, day: 28};
+            assert_eq!(from_rrule(&to_rrule(&interval)), Ok(interval));
+        }
+    }
+}
+
+#[test]
+fn unsupported_rrules_are_errors() {
+    assert_eq!(from_rrule("INTERVAL=2"), Err(RRuleError::MissingFrequency));
+    assert_eq!(from_rrule("FREQ"), Err(RRuleError::Malformed("FREQ".to_string())));
+    assert_eq!(from_rrule("FREQ=HOURLY"), Err(RRuleError::Unsupported("FREQ=HOURLY".to_string())));
+    assert_eq!(from_rrule("FREQ=DAILY;COUNT=5"), Err(RRuleError::Unsupported("COUNT=5".to_string())));
+    assert_eq!(
+        from_rrule("FREQ=WEEKLY;BYDAY=XX"),
+        Err(RRuleError::InvalidValue{part: "BYDAY".to_string(), value: "XX".to_string()})
+    );
+    assert_eq!(
+        from_rrule("FREQ=WEEKLY"),
+        Err(RRuleError::MissingPart{frequency: "WEEKLY".to_string(), part: "BYDAY".to_string()})
+    );
+    assert!(matches!(from_rrule("FREQ=DAILY;INTERVAL=0"), Err(RRuleError::InvalidValue{..})));
+    assert!(matches!(from_rrule("FREQ=MONTHLY;BYDAY=5MO"), Err(RRuleError::InvalidValue{..})));
+    assert!(matches!(from_rrule("FREQ=MONTHLY;BYDAY=MO"), Err(RRuleError::Unsupported(_))));
+    assert!(matches!(from_rrule("FREQ=MONTHLY;BYDAY=1MO;BYMONTHDAY=3"), Err(RRuleError::Unsupported(_))));
+    assert!(matches!(from_rrule("FREQ=YEARLY;BYMONTH=4"), Err(RRuleError::MissingPart{..})));
+    assert!(matches!(from_rrule("FREQ=MONTHLY;BYDAY=é"), Err(RRuleError::InvalidValue{..})));
+    // The errors say what's wrong
+    assert_eq!(from_rrule("FREQ=DAILY;COUNT=5").unwrap_err().to_string(), "Tasks can't repeat with COUNT=5");
+}
\ No newline at end of file
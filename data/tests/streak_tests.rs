@@ -0,0 +1,39 @@
+use data::task::StreakBonus;
+use data::validate::Validate;
+
+#[test]
+fn first_completion_earns_no_bonus() {
+    let bonuses = [StreakBonus::None, StreakBonus::Flat{pts: 3}, StreakBonus::Multiplier{percent: 50}];
+    for bonus in bonuses.iter() {
+        assert_eq!(bonus.bonus_for_streak(0, 10), 0);
+        assert_eq!(bonus.bonus_for_streak(1, 10), 0);
+    }
+}
+
+#[test]
+fn flat_bonus_is_the_same_each_time() {
+    let bonus = StreakBonus::Flat{pts: 3};
+    for streak in 2..50 {
+        assert_eq!(bonus.bonus_for_streak(streak, 10), 3);
+    }
+}
+
+#[test]
+fn multiplier_grows_until_the_cap() {
+    let bonus = StreakBonus::Multiplier{percent: 10};
+    assert_eq!(bonus.bonus_for_streak(2, 20), 2);
+    assert_eq!(bonus.bonus_for_streak(3, 20), 4);
+    // Rounded down
+    assert_eq!(bonus.bonus_for_streak(2, 15), 1);
+    let capped = bonus.bonus_for_streak(StreakBonus::MAX_MULTIPLIER_STEPS + 1, 20);
+    assert_eq!(capped, 20);
+    assert_eq!(bonus.bonus_for_streak(1000, 20), capped);
+    // Huge numbers don't overflow
+    assert_eq!(StreakBonus::Multiplier{percent: 100}.bonus_for_streak(u32::MAX, i32::MAX), i32::MAX);
+}
+
+#[test]
+fn multiplier_is_limited() {
+    assert!(StreakBonus::Multiplier{percent: StreakBonus::MAX_PERCENT}.validate().is_ok());
+    assert!(StreakBonus::Multiplier{percent: StreakBonus::MAX_PERCENT + 1}.validate().is_err());
+}
\ No newline at end of file
@@ -1,6 +1,5 @@
-use anyhow::Error;
 use yew::callback::Callback;
-use yew::format::{Json, Nothing};
+use yew::format::{Json, Nothing, Text};
 use yew::services::fetch::{
     FetchService,
     FetchTask,
@@ -11,16 +10,53 @@ use http::request::Builder;
 use data::{
     task::*,
     user::*,
-    reward::*
+    reward::*,
+    goal::*,
+    household::*,
+    ledger::*,
+    session::SessionInfo,
+    error::ApiError,
 };
+use serde::de::DeserializeOwned;
+use std::marker::PhantomData;
 use crate::app;
 use yew_router::prelude::*;
 use yew_router::agent::RouteRequest::ChangeRoute;
-use js_sys::Date;
+use js_sys::{Array, Date, Intl, Object, Reflect};
+use wasm_bindgen::JsValue;
+use chrono::NaiveDate;
 
-pub type FetchResponse<T> = Response<Json<Result<T, Error>>>;
+/// The body of a response from the backend, before it's known
+/// whether it holds what was asked for or an error
+pub struct ApiBody<T> {
+    text: Text,
+    body_type: PhantomData<T>,
+}
+
+impl<T> From<Text> for ApiBody<T> {
+    fn from(text: Text) -> Self {
+        Self {
+            text,
+            body_type: PhantomData,
+        }
+    }
+}
+
+pub type FetchResponse<T> = Response<ApiBody<T>>;
 type FetchCallback<T> = Callback<FetchResponse<T>>;
 
+/// Gets what was asked for out of a response, or the error that the backend sent back instead
+pub fn decode<T: DeserializeOwned>(response: FetchResponse<T>) -> Result<T, ApiError> {
+    let (parts, body) = response.into_parts();
+    let unreadable = |e: &dyn std::fmt::Display| ApiError::Internal(format!("Could not read the response: {}", e));
+    let text = body.text.map_err(|e| unreadable(&e))?;
+    if parts.status.is_success() {
+        serde_json::from_str(&text).map_err(|e| unreadable(&e))
+    } else {
+        Err(serde_json::from_str(&text).unwrap_or_else(|_| ApiError::from_status(parts.status.as_u16(), text)))
+    }
+}
+
 fn get_with_head(route: &str) -> Builder {
     let get = Request::get(route);
     add_headers(get)
@@ -36,13 +72,27 @@ fn put_with_head(route: &str) -> Builder {
     add_headers(put)
 }
 
+fn delete_with_head(route: &str) -> Builder {
+    let delete = Request::delete(route);
+    add_headers(delete)
+}
+
 fn add_headers(request: Builder) -> Builder {
-    let today = Date::new_0();
-    request
-        .header("Content-Type", "application/json")
-        .header("year", today.get_full_year())
-        .header("month", today.get_month() + 1)
-        .header("day", today.get_date())
+    request.header("Content-Type", "application/json")
+}
+
+/// Gets the IANA name of the browser's time zone, e.g. America/Denver
+pub fn browser_time_zone() -> Option<String> {
+    let format = Intl::DateTimeFormat::new(&Array::new(), &Object::new());
+    let options = format.resolved_options();
+    Reflect::get(&options, &JsValue::from_str("timeZone")).ok()?.as_string()
+}
+
+/// Today in the browser's time zone
+pub fn browser_today() -> NaiveDate {
+    let now = Date::new_0();
+    NaiveDate::from_ymd_opt(now.get_full_year() as i32, now.get_month() + 1, now.get_date())
+        .unwrap_or(NaiveDate::MIN)
 }
 
 /// Gets the user state
@@ -53,17 +103,78 @@ pub fn get_user(callback: FetchCallback<Option<User>>) -> FetchTask {
     FetchService::fetch(get, callback).unwrap()
 }
 
-/// Remove the user's id from local storage to
-/// mark them as unauthorized, then re-routes to
-/// the home page
+/// Sets the time zone that the user's days are counted in
+pub fn set_time_zone(time_zone: &str, callback: FetchCallback<User>) -> FetchTask {
+    let put = put_with_head("/user/time_zone")
+        .body(Json(&time_zone))
+        .unwrap();
+    FetchService::fetch(put, callback).unwrap()
+}
+
+/// Re-routes to the home page, which will send the user
+/// to sign in if they're no longer authorized
 pub fn sign_out_frontend() {
-    // TODO: Make a call to invalidate the ses cookie on backend
-    // let mut stor = StorageService::new(Area::Local).expect("Could not connect to the local storage");
-    // stor.remove(UNAME_KEY);
     let mut agent_dispatch: RouteAgentDispatcher<()> = RouteAgentDispatcher::default();
     agent_dispatch.send(ChangeRoute(app::Route::HomePage.into()));
 }
 
+/// Changes the user's password, which logs them out everywhere else
+pub fn change_password(change: &PasswordChange, callback: FetchCallback<User>) -> FetchTask {
+    let put = put_with_head("/user/password")
+        .body(Json(change))
+        .unwrap();
+    FetchService::fetch(put, callback).unwrap()
+}
+
+/// Deletes the user's account along with everything in it
+pub fn delete_account(deletion: &AccountDeletion, callback: FetchCallback<()>) -> FetchTask {
+    let delete = delete_with_head("/user")
+        .body(Json(deletion))
+        .unwrap();
+    FetchService::fetch(delete, callback).unwrap()
+}
+
+/// Ends the user's session on the backend
+pub fn sign_out(callback: FetchCallback<()>) -> FetchTask {
+    let post = post_with_head("/logout")
+        .body(Nothing)
+        .unwrap();
+    FetchService::fetch(post, callback).unwrap()
+}
+
+/// Get a list of everywhere the user is logged in
+pub fn get_sessions(callback: FetchCallback<Vec<SessionInfo>>) -> FetchTask {
+    let get = get_with_head("/sessions")
+        .body(Nothing)
+        .unwrap();
+    FetchService::fetch(get, callback).unwrap()
+}
+
+/// Log the user out of one of their other sessions
+pub fn revoke_session(session_id: i32, callback: FetchCallback<()>) -> FetchTask {
+    let delete = Request::delete(format!("/sessions/{}", session_id))
+        .body(Nothing)
+        .unwrap();
+    FetchService::fetch(delete, callback).unwrap()
+}
+
+/// Makes a new secret link for calendar apps to read the user's tasks from.
+/// Any old link stops working
+pub fn new_calendar_token(callback: FetchCallback<CalendarToken>) -> FetchTask {
+    let post = post_with_head("/calendar")
+        .body(Nothing)
+        .unwrap();
+    FetchService::fetch(post, callback).unwrap()
+}
+
+/// Stops calendar apps from reading the user's tasks
+pub fn remove_calendar_token(callback: FetchCallback<()>) -> FetchTask {
+    let delete = delete_with_head("/calendar")
+        .body(Nothing)
+        .unwrap();
+    FetchService::fetch(delete, callback).unwrap()
+}
+
 /// Sends the user's creds to the backend, which responds with an error, or a jwt
 pub fn sign_in(user: NewUser, callback: FetchCallback<User>) -> FetchTask  {
     let login = post_with_head("/login")
@@ -92,12 +203,6 @@ pub fn get_done_tasks(callback: FetchCallback<Vec<Task>>) -> FetchTask {
     FetchService::fetch(get, callback).unwrap()
 }
 
-/// Get a list of all of the completed tasks
-pub fn undo_done_tasks(callback: FetchCallback<Vec<Task>>) -> FetchTask {
-    let get = post_with_head("/task/undo").body(Nothing).unwrap();
-    FetchService::fetch(get, callback).unwrap()
-}
-
 /// Commits to a new task
 pub fn commit_new_task(new_task: NewTask, callback: FetchCallback<Task>) -> FetchTask {
         let post = post_with_head("/task")
@@ -122,6 +227,15 @@ pub fn complete_task(task_id: i32, callback: FetchCallback<Task>) -> FetchTask {
         FetchService::fetch(post, callback).unwrap()
 }
 
+/// Undoes completing the task, taking back the points it was worth
+pub fn uncomplete_task(task_id: i32, callback: FetchCallback<Task>) -> FetchTask {
+    let post = post_with_head(&format!("/task/uncomplete/{}", task_id))
+        .body(Nothing)
+        .unwrap();
+    FetchService::fetch(post, callback).unwrap()
+}
+
+/// Archives a task, it can be restored or purged from the archive
 pub fn delete_task(task_id: i32, callback: FetchCallback<()>) -> FetchTask {
         let delete = Request::delete(format!("/task/{}", task_id))
             .body(Nothing)
@@ -129,6 +243,28 @@ pub fn delete_task(task_id: i32, callback: FetchCallback<()>) -> FetchTask {
         FetchService::fetch(delete, callback).unwrap()
 }
 
+/// Get the tasks that were deleted and the one-off tasks that were done
+pub fn get_archived_tasks(callback: FetchCallback<Vec<Task>>) -> FetchTask {
+    let get = get_with_head("/task/archived").body(Nothing).unwrap();
+    FetchService::fetch(get, callback).unwrap()
+}
+
+/// Bring an archived task back
+pub fn restore_task(task_id: i32, callback: FetchCallback<Task>) -> FetchTask {
+    let post = post_with_head(&format!("/task/restore/{}", task_id))
+        .body(Nothing)
+        .unwrap();
+    FetchService::fetch(post, callback).unwrap()
+}
+
+/// Delete an archived task and its history for good
+pub fn purge_task(task_id: i32, callback: FetchCallback<()>) -> FetchTask {
+    let delete = Request::delete(format!("/task/purge/{}", task_id))
+        .body(Nothing)
+        .unwrap();
+    FetchService::fetch(delete, callback).unwrap()
+}
+
 pub fn get_rewards(callback: FetchCallback<Vec<Reward>>) -> FetchTask {
     let get = Request::get("/reward")
         .body(Nothing)
@@ -158,9 +294,116 @@ pub fn update_reward(reward_id: i32, reward_edits: NewReward, callback: FetchCal
         FetchService::fetch(update, callback).unwrap()
 }
 
+/// Archives a reward, it can be restored or purged from the archive
 pub fn delete_reward(reward_id: i32, callback: FetchCallback<()>) -> FetchTask {
         let delete = Request::delete(format!("/reward/{}", reward_id))
             .body(Nothing)
             .unwrap();
         FetchService::fetch(delete, callback).unwrap()
+}
+
+/// Get the rewards that were deleted
+pub fn get_archived_rewards(callback: FetchCallback<Vec<Reward>>) -> FetchTask {
+    let get = Request::get("/reward/archived")
+        .body(Nothing)
+        .unwrap();
+    FetchService::fetch(get, callback).unwrap()
+}
+
+/// Bring an archived reward back
+pub fn restore_reward(reward_id: i32, callback: FetchCallback<Reward>) -> FetchTask {
+    let post = post_with_head(&format!("/reward/restore/{}", reward_id))
+        .body(Nothing)
+        .unwrap();
+    FetchService::fetch(post, callback).unwrap()
+}
+
+/// Delete an archived reward for good
+pub fn purge_reward(reward_id: i32, callback: FetchCallback<()>) -> FetchTask {
+    let delete = Request::delete(format!("/reward/purge/{}", reward_id))
+        .body(Nothing)
+        .unwrap();
+    FetchService::fetch(delete, callback).unwrap()
+}
+
+/// Get the rewards the user is saving for
+pub fn get_goals(callback: FetchCallback<Vec<Goal>>) -> FetchTask {
+    let get = get_with_head("/goal")
+        .body(Nothing)
+        .unwrap();
+    FetchService::fetch(get, callback).unwrap()
+}
+
+/// Pin a reward as a goal to save points for
+pub fn pin_goal(reward_id: i32, callback: FetchCallback<Goal>) -> FetchTask {
+    let new_goal = NewGoal{reward_id};
+    let post = post_with_head("/goal")
+        .body(Json(&new_goal))
+        .unwrap();
+    FetchService::fetch(post, callback).unwrap()
+}
+
+/// Set aside some of the user's points for a goal
+pub fn deposit_to_goal(goal_id: i32, pts: i32, callback: FetchCallback<Goal>) -> FetchTask {
+    let goal_deposit = GoalDeposit{pts};
+    let post = post_with_head(&format!("/goal/{}/deposit", goal_id))
+        .body(Json(&goal_deposit))
+        .unwrap();
+    FetchService::fetch(post, callback).unwrap()
+}
+
+/// Cancel a goal, the points saved for it are given back
+pub fn cancel_goal(goal_id: i32, callback: FetchCallback<i32>) -> FetchTask {
+    let delete = Request::delete(format!("/goal/{}", goal_id))
+        .body(Nothing)
+        .unwrap();
+    FetchService::fetch(delete, callback).unwrap()
+}
+
+/// Get the household the user is in, if any
+pub fn get_household(callback: FetchCallback<Option<Household>>) -> FetchTask {
+    let get = get_with_head("/household")
+        .body(Nothing)
+        .unwrap();
+    FetchService::fetch(get, callback).unwrap()
+}
+
+/// Start a new household with the user in it
+pub fn create_household(new_household: &NewHousehold, callback: FetchCallback<Household>) -> FetchTask {
+    let post = post_with_head("/household")
+        .body(Json(new_household))
+        .unwrap();
+    FetchService::fetch(post, callback).unwrap()
+}
+
+/// Join the household with the invite code
+pub fn join_household(join: &JoinHousehold, callback: FetchCallback<Household>) -> FetchTask {
+    let post = post_with_head("/household/join")
+        .body(Json(join))
+        .unwrap();
+    FetchService::fetch(post, callback).unwrap()
+}
+
+/// Leave the user's household, which stops sharing their tasks and rewards with it
+pub fn leave_household(callback: FetchCallback<()>) -> FetchTask {
+    let post = post_with_head("/household/leave")
+        .body(Nothing)
+        .unwrap();
+    FetchService::fetch(post, callback).unwrap()
+}
+
+/// Get a page of the history of where the user's points came from and went
+pub fn get_ledger(page: i64, callback: FetchCallback<LedgerPage>) -> FetchTask {
+    let get = get_with_head(&format!("/ledger?page={}", page))
+        .body(Nothing)
+        .unwrap();
+    FetchService::fetch(get, callback).unwrap()
+}
+
+/// Get the tasks the user completed from one day to another, including both
+pub fn get_history(from: NaiveDate, to: NaiveDate, callback: FetchCallback<Vec<TaskCompletion>>) -> FetchTask {
+    let get = get_with_head(&format!("/history?from={}&to={}", from, to))
+        .body(Nothing)
+        .unwrap();
+    FetchService::fetch(get, callback).unwrap()
 }
\ No newline at end of file
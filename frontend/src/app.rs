@@ -6,9 +6,9 @@ use crate::pages::*;
 use crate::data::*;
 use std::rc::Rc;
 use data::user::User;
-use crate::apis::{get_user, FetchResponse};
+use data::household::Household;
+use crate::apis::{get_user, set_time_zone, get_household, browser_time_zone, decode, FetchResponse};
 use yew::services::fetch::{FetchTask};
-use yew::format::{Json};
 use crate::components::Header;
 
 // TODO: get naming consistent here (probably w/out Page is better)
@@ -23,6 +23,22 @@ pub enum Route {
     Tasks,
     #[to = "/#rewards"]
     RewardsPage,
+    #[to = "/#points"]
+    PointsPage,
+    #[to = "/#history"]
+    HistoryPage,
+    #[to = "/#archived"]
+    ArchivedPage,
+    #[to = "/#sessions"]
+    SessionsPage,
+    #[to = "/#password"]
+    PasswordPage,
+    #[to = "/#calendar"]
+    CalendarPage,
+    #[to = "/#delete-account"]
+    DeleteAccountPage,
+    #[to = "/#household"]
+    HouseholdPage,
     #[to = "/"]
     HomePage,
 }
@@ -30,18 +46,25 @@ pub enum Route {
 pub struct State {
     store: Store,
     user: ItemPtr<Option<User>>,
+    /// Whether a user was signed in the last time the user changed
+    signed_in: bool,
     _user_callback: StoreListener<Option<User>>,
 }
 
 pub struct App {
     state: State,
     link: ComponentLink<Self>,
-    fetch_task: Option<FetchTask>
+    fetch_task: Option<FetchTask>,
+    household_fetch_task: Option<FetchTask>,
 }
 
 pub enum Msg {
     RequestAuth,
     ReceiveAuth(ItemPtr<Option<User>>),
+    /// Let the backend know the user's time zone so it knows when their day starts
+    SyncTimeZone(String),
+    /// Get the household the user is in, so shared tasks and rewards can be offered
+    FetchHousehold,
     Noop,
 }
 
@@ -65,10 +88,12 @@ impl Component for App {
             state: State {
                 store,
                 user: StoreItem::new_ptr(),
+                signed_in: false,
                 _user_callback,
             },
             link,
-            fetch_task: None
+            fetch_task: None,
+            household_fetch_task: None,
         }
     }
 
@@ -78,8 +103,8 @@ impl Component for App {
                 ConsoleService::log("request auth");
                 let store_clone = self.state.store.clone();
                 let callback = self.link.callback(move |response: FetchResponse<Option<User>>| {
-                    let ret = match response.into_parts() {
-                        (_, Json(Ok(Some(user)))) => {
+                    let ret = match decode(response) {
+                        Ok(Some(user)) => {
                             ConsoleService::log("got user back to app");
                             store_clone.act(StoreAction::StartSession(user));
                             Msg::Noop
@@ -95,9 +120,46 @@ impl Component for App {
             }
             Msg::ReceiveAuth(user) => {
                 ConsoleService::log("receive auth");
+                let stale_time_zone = match (&*user.borrow(), browser_time_zone()) {
+                    (Some(u), Some(time_zone)) if u.time_zone != time_zone => Some(time_zone),
+                    _ => None,
+                };
+                if let Some(time_zone) = stale_time_zone {
+                    self.link.send_message(Msg::SyncTimeZone(time_zone));
+                }
+                let signed_in = user.borrow().is_some();
+                if signed_in && !self.state.signed_in {
+                    self.link.send_message(Msg::FetchHousehold);
+                }
+                self.state.signed_in = signed_in;
                 self.state.user = user;
                 true
             }
+            Msg::SyncTimeZone(time_zone) => {
+                ConsoleService::log("sync time zone");
+                let store_clone = self.state.store.clone();
+                let callback = self.link.callback(move |response: FetchResponse<User>| {
+                    match decode(response) {
+                        Ok(user) => store_clone.act(StoreAction::StartSession(user)),
+                        Err(error) => ConsoleService::error(&format!("Failed to set the time zone: {}", error)),
+                    }
+                    Msg::Noop
+                });
+                self.fetch_task = Some(set_time_zone(&time_zone, callback));
+                false
+            }
+            Msg::FetchHousehold => {
+                let store_clone = self.state.store.clone();
+                let callback = self.link.callback(move |response: FetchResponse<Option<Household>>| {
+                    match decode(response) {
+                        Ok(household) => store_clone.act(StoreAction::SetHousehold(household)),
+                        Err(error) => ConsoleService::error(&format!("Failed to get the household: {}", error)),
+                    }
+                    Msg::Noop
+                });
+                self.household_fetch_task = Some(get_household(callback));
+                false
+            }
             Msg::Noop => false,
         }
     }
@@ -121,6 +183,14 @@ impl Component for App {
                 let main_page = match route {
                     Route::Tasks => html!{<TasksPage store={store.clone()} />},
                     Route::RewardsPage => html!{<RewardsPage store={store.clone()} />},
+                    Route::PointsPage => html!{<PointsPage />},
+                    Route::HistoryPage => html!{<HistoryPage />},
+                    Route::ArchivedPage => html!{<ArchivedPage />},
+                    Route::SessionsPage => html!{<SessionsPage store={store.clone()} />},
+                    Route::PasswordPage => html!{<PasswordPage store={store.clone()} />},
+                    Route::CalendarPage => html!{<CalendarPage store={store.clone()} />},
+                    Route::DeleteAccountPage => html!{<DeleteAccountPage store={store.clone()} />},
+                    Route::HouseholdPage => html!{<HouseholdPage store={store.clone()} />},
                     _ => html!{<Home />}
                 };
                 html! {<>
@@ -0,0 +1,10 @@
+use yew::prelude::*;
+use data::{error::FieldError, validate::error_for};
+
+/// Shows the error for the given field under it, if the field has one
+pub fn field_error(errors: &[FieldError], field: &str) -> Html {
+    match error_for(errors, field) {
+        Some(message) => html!{<div class="field-error">{message}</div>},
+        None => html!{<></>},
+    }
+}
\ No newline at end of file
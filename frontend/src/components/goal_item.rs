@@ -0,0 +1,182 @@
+use data::goal::Goal;
+use data::error::{ApiError, FieldError};
+use yew::prelude::*;
+use yew::services::{
+    fetch::FetchTask,
+    dialog::DialogService,
+};
+use crate::data::*;
+use crate::apis::{deposit_to_goal, cancel_goal, decode, FetchResponse};
+use crate::components::field_error;
+
+/// Shows how far along a goal is, with a way to set points aside for it
+pub struct GoalItem {
+    state: State,
+    props: Props,
+    link: ComponentLink<Self>,
+    fetch_action: Option<FetchTask>,
+}
+
+#[derive(Properties, Clone)]
+pub struct Props {
+    pub goal: Goal,
+    pub store: Store,
+    /// Called when the goal is cancelled, so the list of goals can be fetched again
+    pub on_cancelled: Callback<()>,
+}
+
+struct State {
+    /// The points to set aside the next time Save is clicked
+    deposit: i32,
+    /// What was wrong with the last deposit
+    errors: Vec<FieldError>,
+}
+
+pub enum Msg {
+    UpdateDeposit(String),
+    Deposit,
+    Deposited(Goal),
+    Cancel,
+    Cancelled(i32),
+    ShowErrors(Vec<FieldError>),
+}
+
+/// Sets the signed in user's points to the given total
+fn set_user_pts(store: &Store, pts: i32) {
+    store.session_user.update(|user_opt| {
+        match user_opt {
+            Some(user) if user.bspts != pts => {
+                user.bspts = pts;
+                true
+            }
+            _ => false
+        }
+    });
+}
+
+impl Component for GoalItem {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let state = State {
+            deposit: props.goal.remaining(),
+            errors: vec![],
+        };
+        Self { state, props, link, fetch_action: None }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::UpdateDeposit(pts_as_string) => {
+                if let Ok(pts) = pts_as_string.parse::<i32>() {
+                    self.state.deposit = pts;
+                }
+                false
+            }
+            Msg::Deposit => {
+                let callback = self.link.callback(|response: FetchResponse<Goal>| {
+                    match decode(response) {
+                        Ok(goal) => Msg::Deposited(goal),
+                        Err(ApiError::Validation{fields, ..}) => Msg::ShowErrors(fields),
+                        Err(error) => Msg::ShowErrors(vec![FieldError::new("pts", error.message())]),
+                    }
+                });
+                self.fetch_action = Some(deposit_to_goal(self.props.goal.id, self.state.deposit, callback));
+                true
+            }
+            Msg::Deposited(goal) => {
+                self.fetch_action = None;
+                let deposited = goal.saved - self.props.goal.saved;
+                self.props.store.session_user.update(move |user_opt| {
+                    match user_opt {
+                        Some(user) if deposited != 0 => {
+                            user.bspts -= deposited;
+                            true
+                        }
+                        _ => false
+                    }
+                });
+                self.state.deposit = goal.remaining();
+                self.state.errors = vec![];
+                self.props.goal = goal;
+                true
+            }
+            Msg::Cancel => {
+                if !DialogService::confirm(&format!(
+                    "Stop saving for {}? The {} pts set aside will be given back",
+                    self.props.goal.reward_name,
+                    self.props.goal.saved,
+                )) {
+                    return false;
+                }
+                let callback = self.link.callback(|response: FetchResponse<i32>| {
+                    match decode(response) {
+                        Ok(pts) => Msg::Cancelled(pts),
+                        Err(error) => Msg::ShowErrors(vec![FieldError::new("pts", error.message())]),
+                    }
+                });
+                self.fetch_action = Some(cancel_goal(self.props.goal.id, callback));
+                true
+            }
+            Msg::Cancelled(pts) => {
+                self.fetch_action = None;
+                set_user_pts(&self.props.store, pts);
+                self.props.on_cancelled.emit(());
+                true
+            }
+            Msg::ShowErrors(errors) => {
+                self.fetch_action = None;
+                self.state.errors = errors;
+                true
+            }
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.state.deposit = props.goal.remaining();
+        self.props = props;
+        true
+    }
+
+    fn view(&self) -> Html {
+        let goal = &self.props.goal;
+
+        let projection = match goal.projected_on {
+            _ if goal.remaining() == 0 => "Ready to take!".to_string(),
+            Some(day) => format!("At {:.1} pts a day, ready by {}", goal.daily_rate, day.format("%B %-d")),
+            None => "Earn some points to see when it'll be ready".to_string(),
+        };
+        let progress_style = format!("width: {}%", goal.percent_saved());
+
+        let edit_deposit = self.link.callback(|input: InputData| {Msg::UpdateDeposit(input.value)});
+        let deposit_html = if goal.remaining() == 0 {
+            html! {<></>}
+        } else if self.fetch_action.is_some() {
+            html! {<span class="button">{"Saving..."}</span>}
+        } else {
+            html! {<>
+                <input class="input" type="number" min="1" max={goal.remaining()} oninput={edit_deposit} value={self.state.deposit} />
+                <span class="button" onclick={self.link.callback(|_| {Msg::Deposit})}>{"Set aside"}</span>
+            </>}
+        };
+
+        html! {
+            <div class="goal">
+                <div class="goal-line">
+                    <span class="name">{&goal.reward_name}</span>
+                    <span class="flex-buffer" />
+                    <span class="text">{format!("{} / {} pts", goal.saved, goal.target)}</span>
+                </div>
+                <div class="progress"><div class="progress-fill" style={progress_style} /></div>
+                <div class="goal-line">
+                    <span class="sub-info">{projection}</span>
+                    <span class="flex-buffer" />
+                    {deposit_html}
+                    <span class="button delete" onclick={self.link.callback(|_| {Msg::Cancel})}>{"Cancel"}</span>
+                </div>
+                {field_error(&self.state.errors, "pts")}
+            </div>
+        }
+    }
+}
\ No newline at end of file
@@ -12,6 +12,8 @@ type Callbacks = Option<StoreListener<Option<User>>>;
 
 struct State {
     bspts: i32,
+    /// Whether the account menu is showing
+    menu_open: bool,
     callbacks: Callbacks,
 }
 
@@ -29,6 +31,7 @@ pub struct Header {
 
 pub enum Msg {
     SetPoints(i32),
+    ToggleMenu,
 }
 
 impl Component for Header {
@@ -40,6 +43,7 @@ impl Component for Header {
         Self {
             state: State {
                 bspts: 0,
+                menu_open: false,
                 callbacks: None,
             },
             props,
@@ -57,10 +61,16 @@ impl Component for Header {
                     true
                 }
             }
+            Msg::ToggleMenu => {
+                self.state.menu_open = !self.state.menu_open;
+                true
+            }
         }
     }
 
     fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        // Close the menu once one of its pages is picked
+        self.state.menu_open = false;
         self.props = props;
         true
     }
@@ -91,6 +101,21 @@ impl Component for Header {
             _ => ("", ""),
         };
         let now = Date::new_0();
+        let menu = if self.state.menu_open {
+            html! {
+                <div class="menu">
+                    <RouterAnchor<Route> route={Route::HistoryPage} >{"Task History"}</RouterAnchor<Route>>
+                    <RouterAnchor<Route> route={Route::ArchivedPage} >{"Archived"}</RouterAnchor<Route>>
+                    <RouterAnchor<Route> route={Route::HouseholdPage} >{"Household"}</RouterAnchor<Route>>
+                    <RouterAnchor<Route> route={Route::SessionsPage} >{"Where You're Logged In"}</RouterAnchor<Route>>
+                    <RouterAnchor<Route> route={Route::PasswordPage} >{"Change Password"}</RouterAnchor<Route>>
+                    <RouterAnchor<Route> route={Route::CalendarPage} >{"Calendar Feed"}</RouterAnchor<Route>>
+                    <RouterAnchor<Route> route={Route::DeleteAccountPage} >{"Delete Account"}</RouterAnchor<Route>>
+                </div>
+            }
+        } else {
+            html! {<></>}
+        };
         html! {
             <>
                 <div class="header">
@@ -99,10 +124,16 @@ impl Component for Header {
                             <span class="fa fa-3x fa-home" />
                         </RouterAnchor<Route>>
                         <span class="flex-buffer" />
-                        <span class="bspts">{format!("BsPts: {}", self.state.bspts)}</span>
+                        <RouterAnchor<Route> classes="bspts" route={Route::PointsPage} >
+                            {format!("BsPts: {}", self.state.bspts)}
+                        </RouterAnchor<Route>>
                         <span class="flex-buffer" />
                         <span class="show-time">{format!("{}h{}", now.get_hours(), now.get_minutes())}</span>
+                        <span class="home-link menu-toggle" onclick={self.link.callback(|_| {Msg::ToggleMenu})}>
+                            <span class="fa fa-3x fa-user" />
+                        </span>
                     </div>
+                    {menu}
                     <div class="line routes">
                         <RouterAnchor<Route> classes={tasks_class} route={Route::Tasks} >{"Tasks"}</RouterAnchor<Route>>
                         <RouterAnchor<Route> classes={rewards_class} route={Route::RewardsPage} >{"Rewards"}</RouterAnchor<Route>>
@@ -2,20 +2,26 @@ mod task_item;
 mod task_editor;
 mod reward_item;
 mod reward_editor;
+mod goal_item;
 mod icon_component;
 mod edit_result;
 mod popup;
 mod header;
 mod badge_field_header;
 mod icon_chooser;
+mod field_error;
+mod undo_toast;
 
 pub use task_item::TaskItem;
 pub use task_editor::TaskEditor;
 pub use edit_result::EditResult;
 pub use reward_item::RewardItem;
 pub use reward_editor::RewardEditor;
+pub use goal_item::GoalItem;
 pub use icon_component::IconComponent;
 pub use popup::Popup;
 pub use header::Header;
 pub use badge_field_header::badge_field_header;
-pub use icon_chooser::IconChooser;
\ No newline at end of file
+pub use icon_chooser::IconChooser;
+pub use field_error::field_error;
+pub use undo_toast::UndoToast;
\ No newline at end of file
@@ -3,12 +3,15 @@ use yew::services::{
     dialog::DialogService,
     console::{ConsoleService},
 };
-use yew::format::{Json};
-use crate::apis::{new_reward, update_reward, delete_reward, FetchResponse};
+use crate::apis::{new_reward, update_reward, delete_reward, decode, FetchResponse};
 use yew::services::fetch::{FetchTask};
 use yew::prelude::*;
-use crate::components::EditResult;
+use data::household::Household;
+use crate::data::*;
+use crate::components::{EditResult, field_error};
 use data::icon::{RewardIcon, RewardCategory};
+use data::error::{ApiError, FieldError};
+use data::validate::Validate;
 use crate::components::IconChooser;
 
 pub struct RewardEditor {
@@ -17,6 +20,7 @@ pub struct RewardEditor {
     link: ComponentLink<Self>,
     /// The current fetch action going on if any
     fetch_action: Option<FetchTask>,
+    _household_callback: Option<StoreListener<Option<Household>>>,
 }
 
 #[derive(Properties, Clone)]
@@ -24,6 +28,7 @@ pub struct Props {
     /// A reward to edit, or none to create a new reward
     pub reward_to_edit: Option<Reward>,
     pub on_done: Callback<EditResult<Reward>>,
+    pub store: Store,
 }
 
 /// THe mode the reward editor is in: create a new reward or edit and existing
@@ -36,6 +41,10 @@ enum Mode {
 struct State {
     mode: Mode,
     reward: NewReward,
+    /// What was wrong with the reward the last time it was saved
+    errors: Vec<FieldError>,
+    /// Whether the user is in a household the reward can be shared with
+    in_household: bool,
 }
 
 pub enum Msg {
@@ -43,11 +52,21 @@ pub enum Msg {
     UpdatePoints(String),
     UpdateDescription(String),
     UpdateIcon(RewardIcon),
+    UpdateCooldown(u32),
+    UpdateLimitPeriod(String),
+    UpdateLimitTimes(u32),
+    ToggleOverdraft,
+    UpdatePriceIncrement(u32),
+    UpdatePriceDecay(u32),
     SaveReward,
     ReturnReward(Reward),
+    ShowErrors(Vec<FieldError>),
     DeleteReward,
     RewardDeleted,
     CancelEdit,
+    ToggleShared,
+    ReceiveHousehold(ItemPtr<Option<Household>>),
+    Noop,
 }
 
 impl Component for RewardEditor {
@@ -63,6 +82,12 @@ impl Component for RewardEditor {
                     description: "".to_string(),
                     bspts: 0,
                     icon: RewardIcon::default(),
+                    cooldown_days: 0,
+                    limit: RedemptionLimit::None,
+                    allow_overdraft: false,
+                    price_increment: 0,
+                    price_decay: 0,
+                    shared: false,
                 }
             )}
             Some(reward) => {(
@@ -74,13 +99,17 @@ impl Component for RewardEditor {
             state : State{
                 mode,
                 reward: reward_to_edit,
+                errors: vec![],
+                in_household: false,
             },
             props: Props {
                 reward_to_edit: None,
                 on_done: properties.on_done,
+                store: properties.store,
             },
             link,
             fetch_action: None,
+            _household_callback: None,
         }
     }
 
@@ -104,30 +133,62 @@ impl Component for RewardEditor {
                 self.state.reward.icon = icon;
                 false
             }
+            Msg::UpdateCooldown(days) => {
+                self.state.reward.cooldown_days = days;
+                false
+            }
+            // Change between no limit and a daily or weekly limit
+            Msg::UpdateLimitPeriod(period) => {
+                // Start a new limit at once rather than the 0 times that would fail validation
+                let times = self.state.reward.limit.times().max(1);
+                self.state.reward.limit = match period.as_str() {
+                    "d" => RedemptionLimit::PerDay{times},
+                    "w" => RedemptionLimit::PerWeek{times},
+                    _ => RedemptionLimit::None,
+                };
+                true
+            }
+            Msg::UpdateLimitTimes(times) => {
+                self.state.reward.limit = match self.state.reward.limit {
+                    RedemptionLimit::None => RedemptionLimit::None,
+                    RedemptionLimit::PerDay{..} => RedemptionLimit::PerDay{times},
+                    RedemptionLimit::PerWeek{..} => RedemptionLimit::PerWeek{times},
+                };
+                false
+            }
+            Msg::ToggleOverdraft => {
+                self.state.reward.allow_overdraft = !self.state.reward.allow_overdraft;
+                true
+            }
+            Msg::UpdatePriceIncrement(pts) => {
+                self.state.reward.price_increment = pts;
+                false
+            }
+            Msg::UpdatePriceDecay(pts) => {
+                self.state.reward.price_decay = pts;
+                false
+            }
             Msg::SaveReward => {
+                // Don't bother the server with a reward it will only send back
+                self.state.errors = self.state.reward.field_errors();
+                if !self.state.errors.is_empty() {
+                    return true;
+                }
+                let reward_committed_callback = self.link.callback(|response: FetchResponse<Reward>| {
+                    match decode(response) {
+                        Ok(reward) => Msg::ReturnReward(reward),
+                        Err(ApiError::Validation{fields, ..}) => Msg::ShowErrors(fields),
+                        Err(error) => {
+                            ConsoleService::error(&format!("Failed to save reward: {}", error));
+                            Msg::CancelEdit
+                        }
+                    }
+                });
                 match &self.state.mode {
                     Mode::Create => {
-                        let reward_committed_callback = self.link.callback(|response: FetchResponse<Reward>| {
-                            if let (_, Json(Ok(reward))) = response.into_parts() {
-                                Msg::ReturnReward(reward)
-                            } else {
-                                // TODO: error
-                                ConsoleService::error("Failed to save reward");
-                                Msg::CancelEdit
-                            }
-                        });
                         self.fetch_action = Some(new_reward(&self.state.reward, reward_committed_callback));
                     }
                     Mode::Edit(reward_id) => {
-                        let reward_committed_callback = self.link.callback(|response: FetchResponse<Reward>| {
-                            if let (_, Json(Ok(reward))) = response.into_parts() {
-                                Msg::ReturnReward(reward)
-                            } else {
-                                // TODO: error
-                                ConsoleService::error("Failed to save reward");
-                                Msg::CancelEdit
-                            }
-                        });
                         self.fetch_action = Some(update_reward(*reward_id, self.state.reward.clone(), reward_committed_callback));
                     }
                 };
@@ -138,20 +199,25 @@ impl Component for RewardEditor {
                 self.props.on_done.emit(EditResult::Return(Box::new(reward)));
                 true
             }
+            Msg::ShowErrors(errors) => {
+                self.fetch_action = None;
+                self.state.errors = errors;
+                true
+            }
             Msg::DeleteReward => {
                 let should_delete = DialogService::confirm(format!(
-                    "Are you sure you want to destroy reward {}?",
+                    "Archive reward {}? It can be restored from the Archived page",
                     self.state.reward.name
                 ).as_str());
                 if should_delete {
                     if let Mode::Edit(id) = self.state.mode {
                         let after_reward_deleted = self.link.callback(|response: FetchResponse<()>| {
-                            if let (_, Json(Ok(()))) = response.into_parts() {
-                                Msg::RewardDeleted
-                            } else {
-                                // TODO: error, don't just leave w/out explanation
-                                ConsoleService::error("Failed to delete reward");
-                                Msg::CancelEdit
+                            match decode(response) {
+                                Ok(()) => Msg::RewardDeleted,
+                                Err(error) => {
+                                    ConsoleService::error(&format!("Failed to delete reward: {}", error));
+                                    Msg::CancelEdit
+                                }
                             }
                         });
                         let delete_reward = delete_reward(id ,after_reward_deleted);
@@ -171,6 +237,25 @@ impl Component for RewardEditor {
                 self.props.on_done.emit(EditResult::Cancel);
                 true
             }
+            Msg::ToggleShared => {
+                self.state.reward.shared = !self.state.reward.shared;
+                true
+            }
+            Msg::ReceiveHousehold(household) => {
+                self.state.in_household = household.borrow().is_some();
+                true
+            }
+            Msg::Noop => false,
+        }
+    }
+
+    fn rendered(&mut self, first_render: bool) {
+        if first_render {
+            let household_callback = self.props.store.household.subscribe(
+                self.link.callback(Msg::ReceiveHousehold),
+                true
+            );
+            self._household_callback = Some(household_callback);
         }
     }
 
@@ -190,6 +275,136 @@ impl Component for RewardEditor {
         let edit_desc = self.link.callback(|input: InputData| {Msg::UpdateDescription(input.value)});
         let on_save = self.link.callback(|_| {Msg::SaveReward});
         let on_cancel = self.link.callback(|_| {Msg::CancelEdit});
+        let errors = &self.state.errors;
+
+        let edit_cooldown = self.link.callback(|input: InputData| {
+            match input.value.parse::<u32>() {
+                Ok(days) => Msg::UpdateCooldown(days),
+                Err(_) => Msg::Noop
+            }
+        });
+        let cooldown_input = html! {
+            <div>
+                <span class="text">{"Wait "}</span>
+                <input
+                    class="input"
+                    type="number"
+                    min="0"
+                    max={MAX_COOLDOWN_DAYS}
+                    oninput={edit_cooldown}
+                    value={self.state.reward.cooldown_days}
+                />
+                <span class="text">{" days before taking it again"}</span>
+                {field_error(errors, "cooldown_days")}
+            </div>
+        };
+
+        let limit = &self.state.reward.limit;
+        let edit_limit_period = self.link.callback(|input: ChangeData| {
+            match input {
+                ChangeData::Select(select) => Msg::UpdateLimitPeriod(select.value()),
+                _ => Msg::Noop
+            }
+        });
+        let limit_times_input = if let RedemptionLimit::None = limit {
+            html!{<></>}
+        } else {
+            let edit_limit_times = self.link.callback(|input: InputData| {
+                match input.value.parse::<u32>() {
+                    Ok(times) => Msg::UpdateLimitTimes(times),
+                    Err(_) => Msg::Noop
+                }
+            });
+            html!{
+                <>
+                    <input
+                        class="input"
+                        type="number"
+                        min="1"
+                        max={MAX_LIMIT_TIMES}
+                        oninput={edit_limit_times}
+                        value={limit.times()}
+                    />
+                    <span class="text">{" times"}</span>
+                    {field_error(errors, "limit.times")}
+                </>
+            }
+        };
+        let limit_selector = html! {
+            <div>
+                <span class="text">{"Take it "}</span>
+                <select onchange={edit_limit_period}>
+                    <option selected={*limit == RedemptionLimit::None} value="n">{"any number of times"}</option>
+                    <option selected={matches!(limit, RedemptionLimit::PerDay{..})} value="d">{"each day at most"}</option>
+                    <option selected={matches!(limit, RedemptionLimit::PerWeek{..})} value="w">{"each week at most"}</option>
+                </select>
+                {limit_times_input}
+            </div>
+        };
+
+        let edit_price_increment = self.link.callback(|input: InputData| {
+            match input.value.parse::<u32>() {
+                Ok(pts) => Msg::UpdatePriceIncrement(pts),
+                Err(_) => Msg::Noop
+            }
+        });
+        let edit_price_decay = self.link.callback(|input: InputData| {
+            match input.value.parse::<u32>() {
+                Ok(pts) => Msg::UpdatePriceDecay(pts),
+                Err(_) => Msg::Noop
+            }
+        });
+        let pricing_inputs = html! {
+            <div>
+                <span class="text">{"Costs "}</span>
+                <input
+                    class="input"
+                    type="number"
+                    min="0"
+                    max={MAX_PRICE_INCREMENT}
+                    oninput={edit_price_increment}
+                    value={self.state.reward.price_increment}
+                />
+                <span class="text">{" pts more each time it's taken, wearing off by "}</span>
+                <input
+                    class="input"
+                    type="number"
+                    min="0"
+                    max={MAX_PRICE_DECAY}
+                    oninput={edit_price_decay}
+                    value={self.state.reward.price_decay}
+                />
+                <span class="text">{" pts a day"}</span>
+                {field_error(errors, "price_increment")}
+                {field_error(errors, "price_decay")}
+            </div>
+        };
+
+        let toggle_overdraft = self.link.callback(|_| {Msg::ToggleOverdraft});
+        let overdraft_toggle = html! {
+            <div>
+                <label class="text">
+                    <input type="checkbox" checked={self.state.reward.allow_overdraft} onclick={toggle_overdraft} />
+                    {"Can be taken without enough BS Pts."}
+                </label>
+            </div>
+        };
+
+        // Only offer sharing to members of a household, or so a shared reward can be taken back
+        let share_toggle = if self.state.in_household || self.state.reward.shared {
+            let toggle_shared = self.link.callback(|_| {Msg::ToggleShared});
+            html! {
+                <div>
+                    <label class="text">
+                        <input type="checkbox" checked={self.state.reward.shared} onclick={toggle_shared} />
+                        {"Share with your household"}
+                    </label>
+                    {field_error(errors, "shared")}
+                </div>
+            }
+        } else {
+            html! {<></>}
+        };
 
         let delete_this_reward = if let Mode::Create = self.state.mode {
             // Don't allow destroying a reward that doesn't exist
@@ -199,7 +414,7 @@ impl Component for RewardEditor {
 
             html! { <div class="badge-line">
                 <span class="flex-buffer" />
-                <a class="delete" onclick={on_destroy}>{"Delete this reward"}</a>
+                <a class="delete" onclick={on_destroy}>{"Archive this reward"}</a>
             </div>}
         };
 
@@ -214,12 +429,19 @@ impl Component for RewardEditor {
                         placeholder="Reward Name"
                         value={self.state.reward.name.clone()}
                     />
+                    {field_error(errors, "name")}
                 </div>
                 <div>
                     <span class="text">{"Costs "}</span>
                     <input class="input" type="number" oninput={edit_bspts} value={self.state.reward.bspts} />
                     <span class="text">{" BS Pts."}</span>
+                    {field_error(errors, "bspts")}
                 </div>
+                {pricing_inputs}
+                {cooldown_input}
+                {limit_selector}
+                {overdraft_toggle}
+                {share_toggle}
                 <div><IconChooser<RewardIcon, RewardCategory>
                 icon={Some(self.state.reward.icon.clone())}
                     on_change={self.link.callback(|icon: Box<RewardIcon>| {Msg::UpdateIcon(*icon)})}
@@ -230,7 +452,7 @@ impl Component for RewardEditor {
                     oninput={edit_desc}
                     placeholder="Optionally describe the reward"
                     value={self.state.reward.description.clone()}
-                /></div>
+                />{field_error(errors, "description")}</div>
                 <div class="button-line">
                     <span class="cancel button" onclick={on_cancel}>{"Cancel"}</span>
                     <span class="flex-buffer"></span>
@@ -1,14 +1,16 @@
 use data::reward::Reward;
 use data::icon::*;
+use data::error::ApiError;
+use chrono::Duration;
+use std::convert::TryFrom;
 use yew::prelude::*;
-use yew::format::{Json};
 use crate::components::{Popup, RewardEditor, EditResult, IconComponent};
 use yew::services::{
     fetch::FetchTask,
     console::ConsoleService,
 };
 use crate::data::*;
-use crate::apis::{do_reward, FetchResponse};
+use crate::apis::{do_reward, browser_today, decode, FetchResponse};
 
 pub struct RewardItem {
     state: State,
@@ -21,6 +23,14 @@ pub struct RewardItem {
 pub struct Props {
     pub reward: Box<Reward>,
     pub store: Store,
+    /// Called when the reward's cooldown, limit or cost stops it from being taken
+    pub on_refused: Callback<ApiError>,
+    /// Whether the user is saving points for this reward
+    pub is_goal: bool,
+    /// Called with the reward's id to start saving for it
+    pub on_pin: Callback<i32>,
+    /// Called after the reward is taken, which uses up any goal for it
+    pub on_taken: Callback<()>,
 }
 
 pub struct State {
@@ -34,6 +44,10 @@ pub enum Msg {
     Update(Box<Reward>),
     CancelEdit,
     DestroySelf,
+    /// The reward was taken, so count it as taken today
+    Taken,
+    /// The reward's rules didn't allow it to be taken
+    Refused(ApiError),
     FetchDone,
 }
 
@@ -53,8 +67,8 @@ impl Component for RewardItem {
             Msg::TakeReward => {
                 let store = self.props.store.clone();
                 let callback = self.link.callback(move |response: FetchResponse<i32>| {
-                    match response.into_parts() {
-                        (_, Json(Ok(pts))) => {
+                    match decode(response) {
+                        Ok(pts) => {
                             store.session_user.update(|user_opt| {
                                 match user_opt {
                                     Some(user) => {
@@ -67,10 +81,16 @@ impl Component for RewardItem {
                                     None => false
                                 }
                             });
+                            Msg::Taken
+                        }
+                        Err(error @ ApiError::CoolingDown(_))
+                        | Err(error @ ApiError::LimitReached(_))
+                        | Err(error @ ApiError::InsufficientPoints(_)) => Msg::Refused(error),
+                        Err(error) => {
+                            ConsoleService::error(&format!("Could not take the reward: {}", error));
+                            Msg::FetchDone
                         }
-                        _ => ConsoleService::error("Could not mark task as done")
                     }
-                    Msg::FetchDone
                 });
                 self.fetch_action = Some(do_reward(self.props.reward.id, callback));
                 false
@@ -93,6 +113,24 @@ impl Component for RewardItem {
                 self.props.store.act(StoreAction::DeleteReward(self.props.reward.id));
                 true
             }
+            Msg::Taken => {
+                self.fetch_action = None;
+                let today = browser_today();
+                self.props.reward.last_redeemed = Some(today);
+                self.props.reward.redeemed_today += 1;
+                self.props.reward.current_price = self.props.reward.current_price
+                    .saturating_add(i32::try_from(self.props.reward.price_increment).unwrap_or(i32::MAX));
+                if self.props.reward.cooldown_days > 0 {
+                    self.props.reward.available_on = Some(today + Duration::days(self.props.reward.cooldown_days as i64));
+                }
+                self.props.on_taken.emit(());
+                true
+            }
+            Msg::Refused(error) => {
+                self.fetch_action = None;
+                self.props.on_refused.emit(error);
+                true
+            }
             Msg::FetchDone => {
                 self.fetch_action = None;
                 true
@@ -108,13 +146,28 @@ impl Component for RewardItem {
     fn view(&self) -> Html {
         let reward = &self.props.reward;
 
-        let pts_desc = match reward.bspts {
+        let pts_desc = match reward.current_price {
            1 => "1 pt".to_string(),
            pts => format!("{} pts", pts),
         };
+        let pts_desc = if reward.current_price != reward.bspts {
+            format!("{} (usually {})", pts_desc, reward.bspts)
+        } else {
+            pts_desc
+        };
+
+        let taken_desc = match (reward.available_on, reward.redeemed_today, reward.last_redeemed) {
+            (Some(available_on), _, _) => format!("Available again {}", available_on.format("%B %-d")),
+            (None, 0, Some(last)) => format!("Last taken {}", last.format("%B %-d")),
+            (None, 0, None) => "".to_string(),
+            (None, 1, _) => "Taken once today".to_string(),
+            (None, times, _) => format!("Taken {} times today", times),
+        };
 
         let click_edit = self.link.callback(|_| {Msg::EditReward});
         let click_done = self.link.callback(|_| {Msg::TakeReward});
+        let reward_id = reward.id;
+        let click_pin = self.props.on_pin.reform(move |_| reward_id);
 
         let badge_class = format!("badge {}-theme", self.props.reward.icon.get_color().to_string());
         let edit_class = format!("edit button {}-theme", self.props.reward.icon.get_color().to_string());
@@ -129,9 +182,20 @@ impl Component for RewardItem {
                 <div class="description">
                     <div class="name">{&reward.name}</div>
                     <div class="info">{pts_desc}</div>
+                    <div class="sub-info">{taken_desc}</div>
+                    {if reward.household_id.is_some() {
+                        html!{<div class="sub-info shared">{"Shared with your household"}</div>}
+                    } else {
+                        html!{<></>}
+                    }}
                 </div>
                 <div class="buttons">
-                    <div class={edit_class} onclick={click_edit}>{"Edit"}</div>
+                    <div class={edit_class.clone()} onclick={click_edit}>{"Edit"}</div>
+                    {if self.props.is_goal {
+                        html! {<></>}
+                    } else {
+                        html! {<div class={edit_class} title="Set points aside for this reward" onclick={click_pin}>{"Save"}</div>}
+                    }}
                     <div class={done_class} onclick={click_done}>{"Take"}</div>
                 </div>
                 {
@@ -149,6 +213,7 @@ impl Component for RewardItem {
                                 <RewardEditor
                                     reward_to_edit={Some(*self.props.reward.clone())}
                                     on_done={on_done}
+                                    store={self.props.store.clone()}
                                 />
                             </Popup>
                         }
@@ -3,12 +3,22 @@ use yew::services::{
     dialog::DialogService,
     console::{ConsoleService},
 };
-use yew::format::{Json};
-use crate::apis::{commit_new_task, update_task, delete_task, FetchResponse};
+use crate::apis::{commit_new_task, update_task, delete_task, browser_today, decode, FetchResponse};
 use yew::services::fetch::{FetchTask};
 use yew::prelude::*;
-use crate::components::{EditResult, IconChooser};
+use data::household::Household;
+use crate::data::*;
+use crate::components::{EditResult, IconChooser, field_error};
 use data::icon::{TaskIcon, TaskCategory};
+use data::error::{ApiError, FieldError};
+use data::validate::Validate;
+use chrono::NaiveDate;
+
+const WEEKDAY_NAMES: [&str; 7] = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"];
+const MONTH_NAMES: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June",
+    "July", "August", "September", "October", "November", "December",
+];
 
 pub struct TaskEditor {
     state: State,
@@ -16,6 +26,7 @@ pub struct TaskEditor {
     link: ComponentLink<Self>,
     /// The current fetch action going on if any
     fetch_action: Option<FetchTask>,
+    _household_callback: Option<StoreListener<Option<Household>>>,
 }
 
 #[derive(Properties, Clone)]
@@ -23,6 +34,7 @@ pub struct Props {
     /// A task to edit, or none to create a new task
     pub task_to_edit: Option<Task>,
     pub on_done: Callback<EditResult<Task>>,
+    pub store: Store,
 }
 
 /// THe mode the task editor is in: create a new task or edit and existing
@@ -35,6 +47,10 @@ pub enum Mode {
 pub struct State {
     pub mode: Mode,
     task: NewTask,
+    /// What was wrong with the task the last time it was saved
+    errors: Vec<FieldError>,
+    /// Whether the user is in a household the task can be shared with
+    in_household: bool,
 }
 
 pub enum Msg {
@@ -44,15 +60,44 @@ pub enum Msg {
     UpdateFrequencyUnit(String),
     UpdateFrequencyEvery(u32),
     UpdateFrequencyBy(u32),
+    UpdateFrequencyWeek(u32),
+    UpdateFrequencyMonth(u32),
+    UpdateFrequencyDue(NaiveDate),
+    ToggleWeekday(u32),
+    UpdatePenaltyPolicy(String),
+    UpdatePenaltyPoints(u32),
+    UpdateStreakBonusPolicy(String),
+    UpdateStreakBonusAmount(u32),
     UpdateIcon(TaskIcon),
     SaveTask,
     ReturnTask(Task),
+    ShowErrors(Vec<FieldError>),
     DeleteTask,
     TaskDeleted,
     CancelEdit,
+    ToggleShared,
+    ReceiveHousehold(ItemPtr<Option<Household>>),
     Noop,
 }
 
+impl TaskEditor {
+    /// A dropdown to pick the weekday of the task's frequency
+    fn weekday_selector(&self, weekday: u32) -> Html {
+        let edit_by = self.link.callback(|input: ChangeData| {
+            match input {
+                ChangeData::Select(select) => {
+                    Msg::UpdateFrequencyBy(select.selected_index() as u32)
+                },
+                _ => panic!("can't get change data value")
+            }
+        });
+        let options = WEEKDAY_NAMES.iter().enumerate().map(|(day, name)| {
+            html!{<option selected={weekday == day as u32}>{name}</option>}
+        }).collect::<Html>();
+        html!{<select onchange={edit_by}>{options}</select>}
+    }
+}
+
 impl Component for TaskEditor {
     type Message = Msg;
     type Properties = Props;
@@ -67,6 +112,10 @@ impl Component for TaskEditor {
                     bspts: 0,
                     frequency: TaskInterval::Days{every: 1},
                     icon: TaskIcon::default(),
+                    penalty: PenaltyPolicy::None,
+                    streak_bonus: StreakBonus::None,
+                    rrule: None,
+                    shared: false,
                 }
             )}
             Some(task) => {(
@@ -78,13 +127,17 @@ impl Component for TaskEditor {
             state : State{
                 mode,
                 task: task_to_edit,
+                errors: vec![],
+                in_household: false,
             },
             props: Props {
                 task_to_edit: None,
                 on_done: properties.on_done,
+                store: properties.store,
             },
             link,
             fetch_action: None,
+            _household_callback: None,
         }
     }
 
@@ -104,19 +157,18 @@ impl Component for TaskEditor {
                 self.state.task.description = desc;
                 false
             }
-            // Change between days, weeks, months
+            // Change between days, weeks, months, years etc.
             Msg::UpdateFrequencyUnit(time_unit) => {
-                let current_freq = &self.state.task.frequency;
-                let (every, by) = match current_freq {
-                    TaskInterval::Days{every} => (every, &(0 as u32)),
-                    TaskInterval::Weeks{every, weekday} => (every, weekday),
-                    TaskInterval::Months{every, day_of_month} => (every, day_of_month),
-                };
+                let every = self.state.task.frequency.every();
                 let new_freq = match time_unit.as_str() {
-                    "d" => TaskInterval::Days{every: *every},
-                    "w" => TaskInterval::Weeks{every: *every, weekday: *by},
-                    "m" => TaskInterval::Months{every: *every, day_of_month: *by},
-                    _ => panic!("Invalid time unit for task frequency"),
+                    "d" => TaskInterval::Days{every},
+                    "w" => TaskInterval::Weeks{every, weekday: 0},
+                    "dw" => TaskInterval::DaysOfWeek{every, weekdays: vec![0]},
+                    "m" => TaskInterval::Months{every, day_of_month: 1},
+                    "mw" => TaskInterval::MonthWeekday{every, week: 1, weekday: 0},
+                    "y" => TaskInterval::Years{every, month: 1, day: 1},
+                    "o" => TaskInterval::Once{due: browser_today()},
+                    _ => return false,
                 };
                 self.state.task.frequency = new_freq;
                 true
@@ -134,6 +186,21 @@ impl Component for TaskEditor {
                         every: new_every,
                         day_of_month: *day_of_month
                     },
+                    TaskInterval::DaysOfWeek{every:_, weekdays} => TaskInterval::DaysOfWeek{
+                        every: new_every,
+                        weekdays: weekdays.clone(),
+                    },
+                    TaskInterval::MonthWeekday{every:_, week, weekday} => TaskInterval::MonthWeekday{
+                        every: new_every,
+                        week: *week,
+                        weekday: *weekday,
+                    },
+                    TaskInterval::Years{every:_, month, day} => TaskInterval::Years{
+                        every: new_every,
+                        month: *month,
+                        day: *day,
+                    },
+                    TaskInterval::Once{due} => TaskInterval::Once{due: *due},
                 };
                 self.state.task.frequency = new_freq;
                 false
@@ -151,8 +218,93 @@ impl Component for TaskEditor {
                         every: *every,
                         day_of_month: new_by
                     },
+                    TaskInterval::DaysOfWeek{every, weekdays} => TaskInterval::DaysOfWeek{
+                        every: *every,
+                        weekdays: weekdays.clone(),
+                    },
+                    TaskInterval::MonthWeekday{every, week, weekday:_} => TaskInterval::MonthWeekday{
+                        every: *every,
+                        week: *week,
+                        weekday: new_by,
+                    },
+                    TaskInterval::Years{every, month, day:_} => TaskInterval::Years{
+                        every: *every,
+                        month: *month,
+                        day: new_by,
+                    },
+                    TaskInterval::Once{due} => TaskInterval::Once{due: *due},
                 };
                 self.state.task.frequency = new_freq;
+                // The last day of the month checkbox may have changed
+                true
+            }
+            Msg::UpdateFrequencyWeek(new_week) => {
+                if let TaskInterval::MonthWeekday{week, ..} = &mut self.state.task.frequency {
+                    *week = new_week;
+                }
+                false
+            }
+            Msg::UpdateFrequencyMonth(new_month) => {
+                if let TaskInterval::Years{month, ..} = &mut self.state.task.frequency {
+                    *month = new_month;
+                }
+                false
+            }
+            Msg::UpdateFrequencyDue(new_due) => {
+                if let TaskInterval::Once{due} = &mut self.state.task.frequency {
+                    *due = new_due;
+                }
+                false
+            }
+            Msg::ToggleWeekday(weekday) => {
+                if let TaskInterval::DaysOfWeek{weekdays, ..} = &mut self.state.task.frequency {
+                    if weekdays.contains(&weekday) {
+                        weekdays.retain(|day| *day != weekday);
+                    } else {
+                        weekdays.push(weekday);
+                        weekdays.sort_unstable();
+                    }
+                }
+                true
+            }
+            // Change between the kinds of penalty
+            Msg::UpdatePenaltyPolicy(policy) => {
+                let pts = self.state.task.penalty.pts();
+                let new_penalty = match policy.as_str() {
+                    "f" => PenaltyPolicy::Flat{pts},
+                    "l" => PenaltyPolicy::Linear{pts},
+                    "e" => PenaltyPolicy::Escalating{pts},
+                    _ => PenaltyPolicy::None,
+                };
+                self.state.task.penalty = new_penalty;
+                true
+            }
+            Msg::UpdatePenaltyPoints(pts) => {
+                let new_penalty = match self.state.task.penalty {
+                    PenaltyPolicy::None => PenaltyPolicy::None,
+                    PenaltyPolicy::Flat{pts:_} => PenaltyPolicy::Flat{pts},
+                    PenaltyPolicy::Linear{pts:_} => PenaltyPolicy::Linear{pts},
+                    PenaltyPolicy::Escalating{pts:_} => PenaltyPolicy::Escalating{pts},
+                };
+                self.state.task.penalty = new_penalty;
+                false
+            }
+            // Change between the kinds of streak bonus
+            Msg::UpdateStreakBonusPolicy(policy) => {
+                let amount = self.state.task.streak_bonus.amount();
+                self.state.task.streak_bonus = match policy.as_str() {
+                    "f" => StreakBonus::Flat{pts: amount},
+                    "m" => StreakBonus::Multiplier{percent: amount},
+                    _ => StreakBonus::None,
+                };
+                true
+            }
+            Msg::UpdateStreakBonusAmount(amount) => {
+                self.state.task.streak_bonus = match self.state.task.streak_bonus {
+                    StreakBonus::None => StreakBonus::None,
+                    StreakBonus::Flat{..} => StreakBonus::Flat{pts: amount},
+                    StreakBonus::Multiplier{..} => StreakBonus::Multiplier{percent: amount},
+                };
                 false
             }
             Msg::UpdateIcon(icon) => {
@@ -162,29 +314,26 @@ impl Component for TaskEditor {
                 false
             }
             Msg::SaveTask => {
+                // Don't bother the server with a task it will only send back
+                self.state.errors = self.state.task.field_errors();
+                if !self.state.errors.is_empty() {
+                    return true;
+                }
+                let task_committed_callback = self.link.callback(|response: FetchResponse<Task>| {
+                    match decode(response) {
+                        Ok(task) => Msg::ReturnTask(task),
+                        Err(ApiError::Validation{fields, ..}) => Msg::ShowErrors(fields),
+                        Err(error) => {
+                            ConsoleService::error(&format!("Failed to save task: {}", error));
+                            Msg::CancelEdit
+                        }
+                    }
+                });
                 match &self.state.mode {
                     Mode::Create => {
-                        let task_committed_callback = self.link.callback(|response: FetchResponse<Task>| {
-                            if let (_, Json(Ok(task))) = response.into_parts() {
-                                Msg::ReturnTask(task)
-                            } else {
-                                // TODO: error
-                                ConsoleService::error("Failed to save task");
-                                Msg::CancelEdit
-                            }
-                        });
                         self.fetch_action = Some(commit_new_task(self.state.task.clone(), task_committed_callback));
                     }
                     Mode::Edit(task_id) => {
-                        let task_committed_callback = self.link.callback(|response: FetchResponse<Task>| {
-                            if let (_, Json(Ok(task))) = response.into_parts() {
-                                Msg::ReturnTask(task)
-                            } else {
-                                // TODO: error
-                                ConsoleService::error("Failed to save task");
-                                Msg::CancelEdit
-                            }
-                        });
                         ConsoleService::log(&format!("save icon: {:#?}", &self.state.task.icon));
                         self.fetch_action = Some(update_task(*task_id, self.state.task.clone(), task_committed_callback));
                     }
@@ -195,20 +344,25 @@ impl Component for TaskEditor {
                 self.props.on_done.emit(EditResult::<Task>::Return(Box::new(task)));
                 true
             }
+            Msg::ShowErrors(errors) => {
+                self.fetch_action = None;
+                self.state.errors = errors;
+                true
+            }
             Msg::DeleteTask => {
                 let should_delete = DialogService::confirm(format!(
-                    "Are you sure you want to destroy task {}?",
+                    "Archive task {}? It can be restored from the Archived page",
                     self.state.task.name
                 ).as_str());
                 if should_delete {
                     if let Mode::Edit(id) = self.state.mode {
                         let after_task_deleted = self.link.callback(|response: FetchResponse<()>| {
-                            if let (_, Json(Ok(()))) = response.into_parts() {
-                                Msg::TaskDeleted
-                            } else {
-                                // TODO: error, don't just leave w/out explanation
-                                ConsoleService::error("Failed to delete task");
-                                Msg::CancelEdit
+                            match decode(response) {
+                                Ok(()) => Msg::TaskDeleted,
+                                Err(error) => {
+                                    ConsoleService::error(&format!("Failed to delete task: {}", error));
+                                    Msg::CancelEdit
+                                }
                             }
                         });
                         let delete_task = delete_task(id ,after_task_deleted);
@@ -228,12 +382,30 @@ impl Component for TaskEditor {
                 self.props.on_done.emit(EditResult::<Task>::Cancel);
                 true
             }
+            Msg::ToggleShared => {
+                self.state.task.shared = !self.state.task.shared;
+                true
+            }
+            Msg::ReceiveHousehold(household) => {
+                self.state.in_household = household.borrow().is_some();
+                true
+            }
             Msg::Noop => {
                 false
             }
         }
     }
 
+    fn rendered(&mut self, first_render: bool) {
+        if first_render {
+            let household_callback = self.props.store.household.subscribe(
+                self.link.callback(Msg::ReceiveHousehold),
+                true
+            );
+            self._household_callback = Some(household_callback);
+        }
+    }
+
     fn change(&mut self, _props: Self::Properties) -> ShouldRender {
         false
     }
@@ -258,36 +430,19 @@ impl Component for TaskEditor {
         let on_cancel = self.link.callback(|_| {Msg::CancelEdit});
 
         let freq = &self.state.task.frequency;
+        let errors = &self.state.errors;
 
         let by_when_selector = match freq {
             TaskInterval::Days{every:_} => html!{<></>},
             TaskInterval::Weeks{every:_, weekday} => {
-                ConsoleService::log("WEEK!");
-                let edit_by = self.link.callback(|input: ChangeData| {
-                    match input {
-                        ChangeData::Select(select) => {
-                            Msg::UpdateFrequencyBy(select.selected_index() as u32)
-                        },
-                        _ => panic!("can't get change data value")
-                    }
-                });
                 html!{
                     <>
                         <span class="text">{" on "}</span>
-                        <select onchange={edit_by}>
-                            <option selected={*weekday==0}>{"Monday"}</option>
-                            <option selected={*weekday==1}>{"Tuesday"}</option>
-                            <option selected={*weekday==2}>{"Wednesday"}</option>
-                            <option selected={*weekday==3}>{"Thursday"}</option>
-                            <option selected={*weekday==4}>{"Friday"}</option>
-                            <option selected={*weekday==5}>{"Saturday"}</option>
-                            <option selected={*weekday==6}>{"Sunday"}</option>
-                        </select>
+                        {self.weekday_selector(*weekday)}
                     </>
                 }
             },
             TaskInterval::Months{every:_, day_of_month} => {
-                ConsoleService::log("MONTH!");
                 let edit_by = self.link.callback(|input: InputData| {
                     match input.value.parse::<u32>() {
                         Ok(day_of_month) => {
@@ -296,6 +451,10 @@ impl Component for TaskEditor {
                         Err(_) => Msg::Noop
                     }
                 });
+                let is_last_day = *day_of_month == LAST_DAY_OF_MONTH;
+                let toggle_last_day = self.link.callback(move |_| {
+                    Msg::UpdateFrequencyBy(if is_last_day { 1 } else { LAST_DAY_OF_MONTH })
+                });
                 html!{
                     <>
                         <span class="text">{" on the "}</span>
@@ -303,41 +462,275 @@ impl Component for TaskEditor {
                             class="input"
                             type="number"
                             min="1"
-                            max="28"
+                            max="31"
                             oninput={edit_by}
                             value={day_of_month}
                         />
+                        <span class="text">{" of the month, or "}</span>
+                        <label class="text">
+                            <input type="checkbox" checked={is_last_day} onclick={toggle_last_day} />
+                            {"the last day"}
+                        </label>
+                    </>
+                }
+            },
+            TaskInterval::DaysOfWeek{every:_, weekdays} => {
+                let weekday_toggles = WEEKDAY_NAMES.iter().enumerate().map(|(weekday, name)| {
+                    let weekday = weekday as u32;
+                    let toggle = self.link.callback(move |_| Msg::ToggleWeekday(weekday));
+                    html!{
+                        <label class="text weekday-toggle">
+                            <input type="checkbox" checked={weekdays.contains(&weekday)} onclick={toggle} />
+                            {&name[..3]}
+                        </label>
+                    }
+                }).collect::<Html>();
+                html!{
+                    <>
+                        <span class="text">{" on "}</span>
+                        {weekday_toggles}
+                    </>
+                }
+            },
+            TaskInterval::MonthWeekday{every:_, week, weekday} => {
+                let edit_week = self.link.callback(|input: ChangeData| {
+                    match input {
+                        ChangeData::Select(select) => match select.value().parse::<u32>() {
+                            Ok(week) => Msg::UpdateFrequencyWeek(week),
+                            Err(_) => Msg::Noop,
+                        },
+                        _ => Msg::Noop
+                    }
+                });
+                html!{
+                    <>
+                        <span class="text">{" on the "}</span>
+                        <select onchange={edit_week}>
+                            <option selected={*week==1} value="1">{"first"}</option>
+                            <option selected={*week==2} value="2">{"second"}</option>
+                            <option selected={*week==3} value="3">{"third"}</option>
+                            <option selected={*week==4} value="4">{"fourth"}</option>
+                            <option selected={*week==LAST_WEEK} value={LAST_WEEK}>{"last"}</option>
+                        </select>
+                        {self.weekday_selector(*weekday)}
                         <span class="text">{" of the month"}</span>
                     </>
                 }
             },
+            TaskInterval::Years{every:_, month, day} => {
+                let edit_month = self.link.callback(|input: ChangeData| {
+                    match input {
+                        ChangeData::Select(select) => Msg::UpdateFrequencyMonth(select.selected_index() as u32 + 1),
+                        _ => Msg::Noop
+                    }
+                });
+                let edit_day = self.link.callback(|input: InputData| {
+                    match input.value.parse::<u32>() {
+                        Ok(day) => Msg::UpdateFrequencyBy(day),
+                        Err(_) => Msg::Noop
+                    }
+                });
+                let month_options = MONTH_NAMES.iter().enumerate().map(|(month0, name)| {
+                    html!{<option selected={*month == month0 as u32 + 1}>{name}</option>}
+                }).collect::<Html>();
+                html!{
+                    <>
+                        <span class="text">{" on "}</span>
+                        <select onchange={edit_month}>{month_options}</select>
+                        <input
+                            class="input"
+                            type="number"
+                            min="1"
+                            max="31"
+                            oninput={edit_day}
+                            value={day}
+                        />
+                    </>
+                }
+            },
+            TaskInterval::Once{due} => {
+                let edit_due = self.link.callback(|input: InputData| {
+                    match NaiveDate::parse_from_str(&input.value, "%Y-%m-%d") {
+                        Ok(due) => Msg::UpdateFrequencyDue(due),
+                        Err(_) => Msg::Noop
+                    }
+                });
+                html!{
+                    <>
+                        <span class="text">{" by "}</span>
+                        <input
+                            class="input"
+                            type="date"
+                            oninput={edit_due}
+                            value={due.format("%Y-%m-%d").to_string()}
+                        />
+                    </>
+                }
+            },
         };
 
-
         let edit_time_unit = self.link.callback(|input: ChangeData| {
             match input {
                 ChangeData::Select(select) => Msg::UpdateFrequencyUnit(select.value()),
                 _ => panic!("can't get change data value")
             }
         });
+        let time_unit = match freq {
+            TaskInterval::Days{..} => "d",
+            TaskInterval::Weeks{..} => "w",
+            TaskInterval::DaysOfWeek{..} => "dw",
+            TaskInterval::Months{..} => "m",
+            TaskInterval::MonthWeekday{..} => "mw",
+            TaskInterval::Years{..} => "y",
+            TaskInterval::Once{..} => "o",
+        };
+        // One-off tasks aren't done every so often, so there's nothing to count
+        let every_input = if freq.repeats() {
+            html! {
+                <>
+                    <span class="text">{"Do every "}</span>
+                    <input
+                        class="input"
+                        type="number"
+                        oninput={edit_every}
+                        value={self.state.task.frequency.every()}
+                    />
+                </>
+            }
+        } else {
+            html! {<span class="text">{"Do "}</span>}
+        };
         let frequency_selector = html! {
             <div>
-                <span class="text">{"Do every "}</span>
-                <input
-                    class="input"
-                    type="number"
-                    oninput={edit_every}
-                    value={self.state.task.frequency.every()}
-                />
+                {every_input}
                 <select onchange={edit_time_unit}>
-                    <option selected={self.state.task.frequency.in_days()} value="d">{"Days"}</option>
-                    <option selected={self.state.task.frequency.in_weeks()} value="w">{"Weeks"}</option>
-                    <option selected={self.state.task.frequency.in_months()} value="m">{"Months"}</option>
+                    <option selected={time_unit == "d"} value="d">{"Days"}</option>
+                    <option selected={time_unit == "w"} value="w">{"Weeks"}</option>
+                    <option selected={time_unit == "dw"} value="dw">{"Weeks on several days"}</option>
+                    <option selected={time_unit == "m"} value="m">{"Months"}</option>
+                    <option selected={time_unit == "mw"} value="mw">{"Months on a weekday"}</option>
+                    <option selected={time_unit == "y"} value="y">{"Years"}</option>
+                    <option selected={time_unit == "o"} value="o">{"Once"}</option>
                 </select>
                 {by_when_selector}
+                {field_error(errors, "frequency.every")}
+                {field_error(errors, "frequency.weekday")}
+                {field_error(errors, "frequency.weekdays")}
+                {field_error(errors, "frequency.week")}
+                {field_error(errors, "frequency.day_of_month")}
+                {field_error(errors, "frequency.month")}
+                {field_error(errors, "frequency.day")}
+                {field_error(errors, "frequency.due")}
             </div>
         };
 
+        let penalty = &self.state.task.penalty;
+        let edit_penalty_policy = self.link.callback(|input: ChangeData| {
+            match input {
+                ChangeData::Select(select) => Msg::UpdatePenaltyPolicy(select.value()),
+                _ => Msg::Noop
+            }
+        });
+        let penalty_pts_input = if let PenaltyPolicy::None = penalty {
+            html!{<></>}
+        } else {
+            let edit_penalty_pts = self.link.callback(|input: InputData| {
+                match input.value.parse::<u32>() {
+                    Ok(pts) => Msg::UpdatePenaltyPoints(pts),
+                    Err(_) => Msg::Noop
+                }
+            });
+            html!{
+                <>
+                    <input
+                        class="input"
+                        type="number"
+                        min="0"
+                        max={PenaltyPolicy::MAX_PTS}
+                        oninput={edit_penalty_pts}
+                        value={penalty.pts()}
+                    />
+                    <span class="text">{" pts"}</span>
+                </>
+            }
+        };
+        let penalty_selector = html! {
+            <div>
+                <span class="text">{"If missed lose "}</span>
+                <select onchange={edit_penalty_policy}>
+                    <option selected={*penalty == PenaltyPolicy::None} value="n">{"nothing"}</option>
+                    <option selected={matches!(penalty, PenaltyPolicy::Flat{..})} value="f">{"the same"}</option>
+                    <option selected={matches!(penalty, PenaltyPolicy::Linear{..})} value="l">{"a growing"}</option>
+                    <option selected={matches!(penalty, PenaltyPolicy::Escalating{..})} value="e">{"a doubling"}</option>
+                </select>
+                {penalty_pts_input}
+                {field_error(errors, "penalty")}
+            </div>
+        };
+
+        let streak_bonus = &self.state.task.streak_bonus;
+        let edit_streak_bonus_policy = self.link.callback(|input: ChangeData| {
+            match input {
+                ChangeData::Select(select) => Msg::UpdateStreakBonusPolicy(select.value()),
+                _ => Msg::Noop
+            }
+        });
+        let streak_bonus_input = if let StreakBonus::None = streak_bonus {
+            html!{<></>}
+        } else {
+            let edit_streak_bonus_amount = self.link.callback(|input: InputData| {
+                match input.value.parse::<u32>() {
+                    Ok(amount) => Msg::UpdateStreakBonusAmount(amount),
+                    Err(_) => Msg::Noop
+                }
+            });
+            let unit = if let StreakBonus::Multiplier{..} = streak_bonus {
+                " % more each time"
+            } else {
+                " pts"
+            };
+            html!{
+                <>
+                    <input
+                        class="input"
+                        type="number"
+                        min="0"
+                        oninput={edit_streak_bonus_amount}
+                        value={streak_bonus.amount()}
+                    />
+                    <span class="text">{unit}</span>
+                </>
+            }
+        };
+        let streak_bonus_selector = html! {
+            <div>
+                <span class="text">{"If done on time in a row earn "}</span>
+                <select onchange={edit_streak_bonus_policy}>
+                    <option selected={*streak_bonus == StreakBonus::None} value="n">{"nothing extra"}</option>
+                    <option selected={matches!(streak_bonus, StreakBonus::Flat{..})} value="f">{"the same extra"}</option>
+                    <option selected={matches!(streak_bonus, StreakBonus::Multiplier{..})} value="m">{"a growing share"}</option>
+                </select>
+                {streak_bonus_input}
+                {field_error(errors, "streak_bonus")}
+            </div>
+        };
+
+        // Only offer sharing to members of a household, or so a shared task can be taken back
+        let share_toggle = if self.state.in_household || self.state.task.shared {
+            let toggle_shared = self.link.callback(|_| {Msg::ToggleShared});
+            html! {
+                <div>
+                    <label class="text">
+                        <input type="checkbox" checked={self.state.task.shared} onclick={toggle_shared} />
+                        {"Share with your household"}
+                    </label>
+                    {field_error(errors, "shared")}
+                </div>
+            }
+        } else {
+            html! {<></>}
+        };
+
         let delete_this_task = if let Mode::Create = self.state.mode {
             // Don't allow destroying a task that doesn't exist
             html! { <></> }
@@ -347,7 +740,7 @@ impl Component for TaskEditor {
             html! {
                 <div class="badge-line">
                     <span class="flex-buffer" />
-                    <a class="delete" onclick={on_destroy}>{"Delete this task"}</a>
+                    <a class="delete" onclick={on_destroy}>{"Archive this task"}</a>
                 </div>
             }
         };
@@ -363,13 +756,18 @@ impl Component for TaskEditor {
                         placeholder="Task Name"
                         value={self.state.task.name.clone()}
                     />
+                    {field_error(errors, "name")}
                 </div>
                 <div>
                     <span class="text">{"Is worth "}</span>
                     <input class="input" type="number" oninput={edit_bspts} value={self.state.task.bspts} />
                     <span class="text">{" bs points"}</span>
+                    {field_error(errors, "bspts")}
                 </div>
                 {frequency_selector}
+                {penalty_selector}
+                {streak_bonus_selector}
+                {share_toggle}
                 <div><IconChooser<TaskIcon, TaskCategory>
                     icon={Some(self.state.task.icon.clone())}
                     on_change={self.link.callback(|icon: Box<TaskIcon>| {Msg::UpdateIcon(*icon)})}
@@ -380,7 +778,7 @@ impl Component for TaskEditor {
                     oninput={edit_desc}
                     placeholder="Optionally describe the task"
                     value={self.state.task.description.clone()}
-                /></div>
+                />{field_error(errors, "description")}</div>
                 <div class="button-line">
                     <span class="cancel button" onclick={on_cancel}>{"Cancel"}</span>
                     <span class="flex-buffer"></span>
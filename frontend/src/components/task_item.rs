@@ -1,8 +1,7 @@
 use data::task::Task;
 use yew::prelude::*;
-use yew::format::{Json};
 use crate::components::{Popup, TaskEditor, EditResult, IconComponent};
-use crate::apis::{complete_task, FetchResponse};
+use crate::apis::{complete_task, decode, FetchResponse};
 use yew::services::fetch::{FetchTask};
 use yew::services::ConsoleService;
 use crate::data::*;
@@ -54,13 +53,12 @@ impl Component for TaskItem {
             }
             Msg::CompleteTask => {
                 let callback = self.link.callback(|response: FetchResponse<Task>| {
-                    match response.into_parts() {
-                        (_, Json(Ok(completed_task))) => {
+                    match decode(response) {
+                        Ok(completed_task) => {
                             Msg::TaskIsCompleted(completed_task)
                         }
-                        (_, _) => {
-                            // TODO: Real error handling
-                            ConsoleService::error("Could not mark task complete");
+                        Err(error) => {
+                            ConsoleService::error(&format!("Could not mark task complete: {}", error));
                             Msg::CancelEdit
                         }
                     }
@@ -80,21 +78,8 @@ impl Component for TaskItem {
                 //         false
                 //     }
                 // });
-                let id = completed_task.id;
-                let remove_task = move |tasks: &mut TaskList| {
-                    tasks.remove(id).is_some()
-                };
-                if self.props.store.todo_tasks.update(remove_task) {
-                    self.props.store.done_tasks.update(move |tasks: &mut TaskList| {
-                        tasks.push(Box::new(completed_task));
-                        true
-                    });
-                    true
-                } else {
-                    let err_msg = format!("Could not find task {} to complete", id);
-                    ConsoleService::error(&err_msg);
-                    false
-                }
+                self.props.store.act(StoreAction::CompleteTask(completed_task));
+                true
             }
             Msg::Update(task) => {
                 self.state.edit_popup = false;
@@ -155,7 +140,32 @@ impl Component for TaskItem {
            // Not for a long time, just show iso 8601 format
            _ => task.next_reset.format("%F").to_string(),
         };
-        let do_by = format!("Do by {}", do_by_description);
+        // Only one-off tasks are left past-due for longer than a day
+        let do_by = if task.days_to_next_reset < -1 {
+            format!("Overdue since {}", task.next_reset.format("%B %-d"))
+        } else {
+            format!("Do by {}", do_by_description)
+        };
+
+        let lost_desc = if task.pts_lost > 0 {
+            let next_penalty = task.penalty.penalty_for_miss((task.misses + 1) as u32);
+            let lost = format!("{} pts lost", task.pts_lost);
+            if next_penalty > 0 {
+                format!("{}, missing again costs {}", lost, next_penalty)
+            } else {
+                lost
+            }
+        } else {
+            "".to_string()
+        };
+
+        let streak_desc = if task.streak > 1 {
+            format!("{} in a row, best {}", task.streak, task.best_streak)
+        } else if task.best_streak > 1 {
+            format!("Best streak {}", task.best_streak)
+        } else {
+            "".to_string()
+        };
 
         let click_edit = self.link.callback(|_| {Msg::EditTask});
         let click_done = self.link.callback(|_| {Msg::CompleteTask});
@@ -176,10 +186,17 @@ impl Component for TaskItem {
                         html!{<>
                             <div class="info">{pts_desc}</div>
                             <div class="sub-info">{do_by}</div>
+                            <div class="sub-info lost">{lost_desc}</div>
                         </>}
                     } else {
                         html!{<></>}
                     }}
+                    <div class="sub-info streak">{streak_desc}</div>
+                    {if task.household_id.is_some() {
+                        html!{<div class="sub-info shared">{"Shared with your household"}</div>}
+                    } else {
+                        html!{<></>}
+                    }}
                 </div>
                 <div class="buttons">
                     <div class={edit_class} onclick={click_edit}>{"Edit"}</div>
@@ -205,6 +222,7 @@ impl Component for TaskItem {
                                 <TaskEditor
                                     task_to_edit={Some((*self.props.task).clone())}
                                     on_done={on_done}
+                                    store={self.props.store.clone()}
                                 />
                             </Popup>
                         }
@@ -0,0 +1,144 @@
+use yew::prelude::*;
+use yew::services::fetch::{FetchTask};
+use yew::services::timeout::{TimeoutService, TimeoutTask};
+use std::time::Duration;
+use data::task::Task;
+use data::error::ApiError;
+use crate::apis::{uncomplete_task, sign_out_frontend, decode, FetchResponse};
+use crate::data::*;
+
+/// How long the toast is shown after a task is completed
+const SHOW_FOR: Duration = Duration::from_secs(8);
+
+#[derive(Properties, Clone)]
+pub struct Props {
+    pub store: Store,
+}
+
+/// Shows a message after a task is completed, with a button to undo it
+/// in case the done button was hit by mistake
+pub struct UndoToast {
+    props: Props,
+    link: ComponentLink<Self>,
+    /// The task that was just completed, none if the toast is hidden
+    task: Option<Task>,
+    error_message: Option<String>,
+    _completed_callback: Option<StoreListener<Option<Task>>>,
+    hide_timeout: Option<TimeoutTask>,
+    fetch_task: Option<FetchTask>,
+}
+
+pub enum Msg {
+    ReceiveCompleted(ItemPtr<Option<Task>>),
+    Undo,
+    Undone(Task),
+    Dismiss,
+    HandleError(ApiError),
+}
+
+impl Component for UndoToast {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Self {
+            props,
+            link,
+            task: None,
+            error_message: None,
+            _completed_callback: None,
+            hide_timeout: None,
+            fetch_task: None,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::ReceiveCompleted(task) => {
+                self.task = task.borrow().clone();
+                self.error_message = None;
+                self.hide_timeout = if self.task.is_some() {
+                    Some(TimeoutService::spawn(SHOW_FOR, self.link.callback(|_| Msg::Dismiss)))
+                } else {
+                    None
+                };
+                true
+            }
+            Msg::Undo => {
+                let task_id = match &self.task {
+                    Some(task) => task.id,
+                    None => return false,
+                };
+                let callback = self.link.callback(|response: FetchResponse<Task>| {
+                    match decode(response) {
+                        Ok(task) => Msg::Undone(task),
+                        Err(error) => Msg::HandleError(error),
+                    }
+                });
+                // Keep the toast up until the undo is finished
+                self.hide_timeout = None;
+                self.fetch_task = Some(uncomplete_task(task_id, callback));
+                true
+            }
+            Msg::Undone(task) => {
+                self.fetch_task = None;
+                self.props.store.act(StoreAction::UncompleteTask(task));
+                true
+            }
+            Msg::Dismiss => {
+                self.hide_timeout = None;
+                self.props.store.last_completed.set(None);
+                true
+            }
+            Msg::HandleError(error) => {
+                self.fetch_task = None;
+                if let ApiError::Unauthorized(_) = error {
+                    sign_out_frontend();
+                } else {
+                    self.error_message = Some(format!("Could not undo: {}", error));
+                    self.hide_timeout = Some(TimeoutService::spawn(SHOW_FOR, self.link.callback(|_| Msg::Dismiss)));
+                }
+                true
+            }
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props = props;
+        false
+    }
+
+    fn rendered(&mut self, first_render: bool) {
+        if first_render {
+            let completed_callback = self.props.store.last_completed.subscribe(
+                self.link.callback(Msg::ReceiveCompleted),
+                false
+            );
+            self._completed_callback = Some(completed_callback);
+        }
+    }
+
+    fn view(&self) -> Html {
+        let task = match &self.task {
+            Some(task) => task,
+            None => return html! {<></>},
+        };
+        let message = match &self.error_message {
+            Some(msg) => msg.clone(),
+            None => format!("Completed {}", task.name),
+        };
+        let undo_button = if self.fetch_task.is_some() {
+            html! {<span class="button">{"Undoing..."}</span>}
+        } else {
+            html! {<span class="button" onclick={self.link.callback(|_| {Msg::Undo})}>{"Undo"}</span>}
+        };
+
+        html! {
+            <div class="toast">
+                <span class="message">{message}</span>
+                {undo_button}
+                <span class="button" onclick={self.link.callback(|_| {Msg::Dismiss})}>{"Dismiss"}</span>
+            </div>
+        }
+    }
+}
\ No newline at end of file
@@ -3,6 +3,7 @@ use data::{
     user::User,
     task::Task,
     reward::Reward,
+    household::Household,
 };
 use std::rc::Rc;
 use std::cell::{Cell};
@@ -17,22 +18,31 @@ pub struct UnwrappedStore {
     pub session_user: StoreItem<Option<User>>,
     pub todo_tasks: StoreItem<TaskList>,
     pub done_tasks: StoreItem<TaskList>,
+    /// The task that was just completed, which can still be undone
+    pub last_completed: StoreItem<Option<Task>>,
     pub rewards: StoreItem<VecDeque<Reward>>,
+    /// The household the user is in, which tasks and rewards can be shared with
+    pub household: StoreItem<Option<Household>>,
 }
 
 /// The actions that the store can provide
 pub enum StoreAction {
     /// Adds the user to the table
     StartSession(User),
-    // Ends the session, logging out
-    // EndSession,
+    /// Ends the session, logging out
+    EndSession,
     /// Takes a vec of tasks and stores it
     SetTasks{tasks: Vec<Task>, are_done: bool},
     /// Set the specified task as complete
+    CompleteTask(Task),
+    /// Move the specified task back to todo after its completion was undone
+    UncompleteTask(Task),
     /// Delete the task with the specified id
     DeleteTask(i32),
     SetRewards(Vec<Reward>),
     DeleteReward(i32),
+    /// Sets the household the user is in, none once they leave it
+    SetHousehold(Option<Household>),
 }
 
 impl UnwrappedStore {
@@ -42,7 +52,9 @@ impl UnwrappedStore {
             session_user: StoreItem::default(),
             todo_tasks: StoreItem::default(),
             done_tasks: StoreItem::default(),
+            last_completed: StoreItem::default(),
             rewards: StoreItem::default(),
+            household: StoreItem::default(),
         }
     }
 
@@ -52,9 +64,11 @@ impl UnwrappedStore {
                 ConsoleService::log("Starting Session");
                 self.session_user.set(Some(user))
             }
-            // StoreAction::EndSession => {
-            //     self.session_user.set(None)
-            // }
+            StoreAction::EndSession => {
+                ConsoleService::log("Ending Session");
+                self.household.set(None);
+                self.session_user.set(None)
+            }
             StoreAction::SetTasks{tasks, are_done} => {
                 let task_list = TaskList::from_vec(tasks);
                 if are_done {
@@ -65,6 +79,43 @@ impl UnwrappedStore {
                     self.todo_tasks.set(task_list)
                 }
             }
+            StoreAction::CompleteTask(task) => {
+                let task_id = task.id;
+                let remove_task = move |tasks: &mut TaskList| {
+                    tasks.remove(task_id).is_some()
+                };
+                if self.todo_tasks.update(remove_task) {
+                    // One-off tasks are archived when they're done rather than listed
+                    if task.archived_at.is_none() {
+                        let done_task = task.clone();
+                        self.done_tasks.update(move |tasks: &mut TaskList| {
+                            tasks.push(Box::new(done_task));
+                            true
+                        });
+                    }
+                    self.last_completed.set(Some(task));
+                } else {
+                    let err_msg = format!("Could not find task {} to complete", task_id);
+                    ConsoleService::error(&err_msg);
+                }
+            }
+            StoreAction::UncompleteTask(task) => {
+                let task_id = task.id;
+                let remove_task = move |tasks: &mut TaskList| {
+                    tasks.remove(task_id).is_some()
+                };
+                // Archived one-off tasks weren't in the done list
+                if self.done_tasks.update(remove_task) || !task.frequency.repeats() {
+                    self.todo_tasks.update(move |tasks: &mut TaskList| {
+                        tasks.push(Box::new(task));
+                        true
+                    });
+                } else {
+                    let err_msg = format!("Could not find task {} to uncomplete", task_id);
+                    ConsoleService::error(&err_msg);
+                }
+                self.last_completed.set(None);
+            }
             StoreAction::DeleteTask(task_id) => {
                 let remove_task = move |tasks: &mut TaskList| {
                     tasks.remove(task_id).is_some()
@@ -100,6 +151,21 @@ impl UnwrappedStore {
                     ConsoleService::error(&err_msg);
                 }
             }
+            StoreAction::SetHousehold(household) => {
+                ConsoleService::log("Setting household");
+                // Keep the user's household in step so it's right if the user is sent again
+                let household_id = household.as_ref().map(|h| h.id);
+                self.session_user.update(move |user_opt| {
+                    match user_opt {
+                        Some(user) if user.household_id != household_id => {
+                            user.household_id = household_id;
+                            true
+                        }
+                        _ => false
+                    }
+                });
+                self.household.set(household)
+            }
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,234 @@
+use yew::prelude::*;
+use data::{task::Task, reward::Reward};
+use chrono::NaiveDateTime;
+use crate::apis::{
+    get_archived_tasks, restore_task, purge_task,
+    get_archived_rewards, restore_reward, purge_reward,
+    sign_out_frontend, decode, FetchResponse,
+};
+use crate::components::badge_field_header;
+use yew::services::{
+    fetch::FetchTask,
+    console::ConsoleService,
+    dialog::DialogService,
+};
+use data::error::ApiError;
+
+struct State {
+    /// The archived tasks, none until they're fetched
+    tasks: Option<Vec<Task>>,
+    /// The archived rewards, none until they're fetched
+    rewards: Option<Vec<Reward>>,
+    error_message: Option<String>,
+}
+
+/// Shows the tasks and rewards that were archived, which can be restored or deleted for good
+pub struct ArchivedPage {
+    state: State,
+    link: ComponentLink<Self>,
+    fetch_tasks: Option<FetchTask>,
+    fetch_rewards: Option<FetchTask>,
+}
+
+pub enum Msg {
+    FetchTasks,
+    ReceiveTasks(Vec<Task>),
+    RestoreTask(i32),
+    PurgeTask(i32),
+    FetchRewards,
+    ReceiveRewards(Vec<Reward>),
+    RestoreReward(i32),
+    PurgeReward(i32),
+    HandleError{msg: String, error: ApiError},
+}
+
+/// One archived task or reward, with buttons to restore or purge it
+fn archived_item(name: &str, archived_at: Option<NaiveDateTime>, restore: Callback<MouseEvent>, purge: Callback<MouseEvent>) -> Html {
+    let archived_on = archived_at.map(|at| format!("Archived {}", at.format("%F"))).unwrap_or_default();
+    html! {
+        <div class="archived-item">
+            <span class="name">{name}</span>
+            <span class="date">{archived_on}</span>
+            <span class="flex-buffer" />
+            <span class="button" onclick={restore}>{"Restore"}</span>
+            <span class="button delete" onclick={purge}>{"Delete Forever"}</span>
+        </div>
+    }
+}
+
+impl Component for ArchivedPage {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
+        ConsoleService::info("Creating archive");
+
+        link.send_message(Msg::FetchTasks);
+        link.send_message(Msg::FetchRewards);
+
+        Self {
+            state: State {
+                tasks: None,
+                rewards: None,
+                error_message: None,
+            },
+            link,
+            fetch_tasks: None,
+            fetch_rewards: None,
+        }
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        match message {
+            Msg::FetchTasks => {
+                let callback = self.link.callback(|response: FetchResponse<Vec<Task>>| {
+                    match decode(response) {
+                        Ok(tasks) => Msg::ReceiveTasks(tasks),
+                        Err(error) => Msg::HandleError{
+                            msg: "Failed to get archived tasks".to_string(),
+                            error,
+                        },
+                    }
+                });
+                self.fetch_tasks = Some(get_archived_tasks(callback));
+                false
+            }
+            Msg::ReceiveTasks(tasks) => {
+                self.fetch_tasks = None;
+                self.state.tasks = Some(tasks);
+                true
+            }
+            Msg::RestoreTask(task_id) => {
+                let callback = self.link.callback(|response: FetchResponse<Task>| {
+                    match decode(response) {
+                        Ok(_) => Msg::FetchTasks,
+                        Err(error) => Msg::HandleError{
+                            msg: "Failed to restore the task".to_string(),
+                            error,
+                        },
+                    }
+                });
+                self.fetch_tasks = Some(restore_task(task_id, callback));
+                false
+            }
+            Msg::PurgeTask(task_id) => {
+                if !DialogService::confirm("Delete this task and its history forever?") {
+                    return false;
+                }
+                let callback = self.link.callback(|response: FetchResponse<()>| {
+                    match decode(response) {
+                        Ok(()) => Msg::FetchTasks,
+                        Err(error) => Msg::HandleError{
+                            msg: "Failed to delete the task".to_string(),
+                            error,
+                        },
+                    }
+                });
+                self.fetch_tasks = Some(purge_task(task_id, callback));
+                false
+            }
+            Msg::FetchRewards => {
+                let callback = self.link.callback(|response: FetchResponse<Vec<Reward>>| {
+                    match decode(response) {
+                        Ok(rewards) => Msg::ReceiveRewards(rewards),
+                        Err(error) => Msg::HandleError{
+                            msg: "Failed to get archived rewards".to_string(),
+                            error,
+                        },
+                    }
+                });
+                self.fetch_rewards = Some(get_archived_rewards(callback));
+                false
+            }
+            Msg::ReceiveRewards(rewards) => {
+                self.fetch_rewards = None;
+                self.state.rewards = Some(rewards);
+                true
+            }
+            Msg::RestoreReward(reward_id) => {
+                let callback = self.link.callback(|response: FetchResponse<Reward>| {
+                    match decode(response) {
+                        Ok(_) => Msg::FetchRewards,
+                        Err(error) => Msg::HandleError{
+                            msg: "Failed to restore the reward".to_string(),
+                            error,
+                        },
+                    }
+                });
+                self.fetch_rewards = Some(restore_reward(reward_id, callback));
+                false
+            }
+            Msg::PurgeReward(reward_id) => {
+                if !DialogService::confirm("Delete this reward forever?") {
+                    return false;
+                }
+                let callback = self.link.callback(|response: FetchResponse<()>| {
+                    match decode(response) {
+                        Ok(()) => Msg::FetchRewards,
+                        Err(error) => Msg::HandleError{
+                            msg: "Failed to delete the reward".to_string(),
+                            error,
+                        },
+                    }
+                });
+                self.fetch_rewards = Some(purge_reward(reward_id, callback));
+                false
+            }
+            Msg::HandleError{msg, error} => {
+                self.fetch_tasks = None;
+                self.fetch_rewards = None;
+                if let ApiError::Unauthorized(_) = error {
+                    sign_out_frontend();
+                } else {
+                    self.state.error_message = Some(format!("{}: {}", msg, error));
+                }
+                true
+            }
+        }
+    }
+
+    fn change(&mut self, _: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn view(&self) -> Html {
+        if let Some(msg) = &self.state.error_message {
+            return html! {<span>{msg}</span>}
+        }
+
+        let tasks_html: Html = match &self.state.tasks {
+            None => html! {<span>{"Waiting for tasks to be fetched"}</span>},
+            Some(tasks) if tasks.is_empty() => html! {<span>{"No tasks have been archived"}</span>},
+            Some(tasks) => tasks.iter().map(|task: &Task| {
+                let task_id = task.id;
+                archived_item(
+                    &task.name,
+                    task.archived_at,
+                    self.link.callback(move |_| {Msg::RestoreTask(task_id)}),
+                    self.link.callback(move |_| {Msg::PurgeTask(task_id)}),
+                )
+            }).collect(),
+        };
+
+        let rewards_html: Html = match &self.state.rewards {
+            None => html! {<span>{"Waiting for rewards to be fetched"}</span>},
+            Some(rewards) if rewards.is_empty() => html! {<span>{"No rewards have been archived"}</span>},
+            Some(rewards) => rewards.iter().map(|reward: &Reward| {
+                let reward_id = reward.id;
+                archived_item(
+                    &reward.name,
+                    reward.archived_at,
+                    self.link.callback(move |_| {Msg::RestoreReward(reward_id)}),
+                    self.link.callback(move |_| {Msg::PurgeReward(reward_id)}),
+                )
+            }).collect(),
+        };
+
+        html! {<>
+            {badge_field_header("Archived tasks")}
+            <div class="archived">{tasks_html}</div>
+            {badge_field_header("Archived rewards")}
+            <div class="archived">{rewards_html}</div>
+        </>}
+    }
+}
\ No newline at end of file
@@ -0,0 +1,153 @@
+use yew::prelude::*;
+use yew::services::fetch::{FetchTask};
+use yew::utils::window;
+use data::user::CalendarToken;
+use data::error::ApiError;
+use crate::apis::{new_calendar_token, remove_calendar_token, sign_out_frontend, decode, FetchResponse};
+use crate::data::*;
+
+struct State {
+    /// The feed's address, which can only be shown right after the link is made
+    feed_url: Option<String>,
+    waiting: bool,
+    removed: bool,
+    error_message: Option<String>,
+}
+
+#[derive(Properties, Clone)]
+pub struct Props {
+    pub store: Store,
+}
+
+/// Lets the user make a secret link to their tasks for calendar apps, or turn it off
+pub struct CalendarPage {
+    state: State,
+    props: Props,
+    link: ComponentLink<Self>,
+    fetch_task: Option<FetchTask>,
+}
+
+pub enum Msg {
+    MakeLink,
+    ReceiveToken(CalendarToken),
+    RemoveLink,
+    Removed,
+    HandleError{msg: String, error: ApiError},
+}
+
+impl Component for CalendarPage {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Self {
+            state: State {
+                feed_url: None,
+                waiting: false,
+                removed: false,
+                error_message: None,
+            },
+            props,
+            link,
+            fetch_task: None,
+        }
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        match message {
+            Msg::MakeLink => {
+                let callback = self.link.callback(|response: FetchResponse<CalendarToken>| {
+                    match decode(response) {
+                        Ok(token) => Msg::ReceiveToken(token),
+                        Err(error) => Msg::HandleError{
+                            msg: "Failed to make a calendar link".to_string(),
+                            error,
+                        },
+                    }
+                });
+                self.fetch_task = Some(new_calendar_token(callback));
+                self.state.waiting = true;
+                true
+            }
+            Msg::ReceiveToken(CalendarToken {token}) => {
+                self.fetch_task = None;
+                self.state.waiting = false;
+                self.state.removed = false;
+                self.state.error_message = None;
+                let origin = window().location().origin().unwrap_or_default();
+                self.state.feed_url = Some(format!("{}/calendar/{}.ics", origin, token));
+                true
+            }
+            Msg::RemoveLink => {
+                let callback = self.link.callback(|response: FetchResponse<()>| {
+                    match decode(response) {
+                        Ok(()) => Msg::Removed,
+                        Err(error) => Msg::HandleError{
+                            msg: "Failed to turn off the calendar link".to_string(),
+                            error,
+                        },
+                    }
+                });
+                self.fetch_task = Some(remove_calendar_token(callback));
+                self.state.waiting = true;
+                true
+            }
+            Msg::Removed => {
+                self.fetch_task = None;
+                self.state.waiting = false;
+                self.state.removed = true;
+                self.state.feed_url = None;
+                self.state.error_message = None;
+                true
+            }
+            Msg::HandleError{msg, error} => {
+                self.fetch_task = None;
+                self.state.waiting = false;
+                if let ApiError::Unauthorized(_) = error {
+                    sign_out_frontend();
+                } else {
+                    self.state.error_message = Some(format!("{}: {}", msg, error));
+                }
+                true
+            }
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props = props;
+        false
+    }
+
+    fn view(&self) -> Html {
+        let message = match (&self.state.error_message, &self.state.feed_url, self.state.removed) {
+            (Some(msg), _, _) => html! {<span>{msg}</span>},
+            (None, Some(url), _) => html! {<>
+                <span>{"Add this address to your calendar app. Keep it secret, anyone with it can see your tasks"}</span>
+                <input class="input" type="text" readonly=true value={url} />
+            </>},
+            (None, None, true) => html! {<span>{"Your calendar link has been turned off"}</span>},
+            (None, None, false) => html! {
+                <span>{"Get a link that calendar apps can use to show when your tasks are due. Making a new link turns off the old one"}</span>
+            },
+        };
+
+        let buttons = if self.state.waiting {
+            html! {<span class="save button">{"Waiting..."}</span>}
+        } else {
+            html! {<>
+                <span class="button" onclick={self.link.callback(|_| {Msg::RemoveLink})}>{"Turn Off Link"}</span>
+                <span class="save button" onclick={self.link.callback(|_| {Msg::MakeLink})}>{"Make New Link"}</span>
+            </>}
+        };
+
+        html! {
+            <div class="form no-auth-page">
+                {message}
+                <div class="button-line">
+                    <span class="flex-buffer"></span>
+                    {buttons}
+                </div>
+            </div>
+        }
+    }
+}
\ No newline at end of file
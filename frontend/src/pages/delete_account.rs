@@ -0,0 +1,119 @@
+use yew::prelude::*;
+use yew::services::fetch::{FetchTask};
+use data::user::*;
+use data::error::ApiError;
+use crate::apis::{delete_account, sign_out_frontend, decode, FetchResponse};
+use crate::data::*;
+
+struct State {
+    deletion: AccountDeletion,
+    deleting: bool,
+    error_message: Option<String>,
+}
+
+#[derive(Properties, Clone)]
+pub struct Props {
+    pub store: Store,
+}
+
+/// Lets the user delete their account, after they confirm their password
+pub struct DeleteAccountPage {
+    state: State,
+    props: Props,
+    link: ComponentLink<Self>,
+    fetch_task: Option<FetchTask>,
+}
+
+pub enum Msg {
+    DeleteAccount,
+    Deleted,
+    TryAgain(String),
+    UpdatePassword(String),
+}
+
+impl Component for DeleteAccountPage {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Self {
+            state: State {
+                deletion: AccountDeletion {
+                    password: "".to_string(),
+                },
+                deleting: false,
+                error_message: None,
+            },
+            props,
+            link,
+            fetch_task: None,
+        }
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        match message {
+            Msg::DeleteAccount => {
+                let callback = self.link.callback(|response: FetchResponse<()>| {
+                    match decode(response) {
+                        Ok(()) => Msg::Deleted,
+                        Err(ApiError::Forbidden(_)) => {
+                            Msg::TryAgain("Incorrect Password".to_string())
+                        }
+                        Err(_) => Msg::TryAgain("There was some problem, please try again".to_string()),
+                    }
+                });
+                self.fetch_task = Some(delete_account(&self.state.deletion, callback));
+                self.state.deleting = true;
+                true
+            }
+            Msg::Deleted => {
+                self.fetch_task = None;
+                self.props.store.act(StoreAction::EndSession);
+                sign_out_frontend();
+                false
+            }
+            Msg::TryAgain(error) => {
+                self.fetch_task = None;
+                self.state.error_message = Some(error);
+                self.state.deleting = false;
+                true
+            }
+            Msg::UpdatePassword(password) => {
+                self.state.deletion.password = password;
+                false
+            }
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props = props;
+        false
+    }
+
+    fn view(&self) -> Html {
+        let error_message = match &self.state.error_message {
+            Some(msg) => html! {<span>{msg}</span>},
+            None => html! {<></>},
+        };
+
+        let edit_pw = self.link.callback(|input: InputData| {Msg::UpdatePassword(input.value)});
+        let on_delete = self.link.callback(|_| {Msg::DeleteAccount});
+        let delete_button = if self.state.deleting {
+            html! {<span class="save button">{"Deleting..."}</span>}
+        } else {
+            html! {<span class="save button" onclick={on_delete}>{"Delete Account"}</span>}
+        };
+
+        html! {
+            <div class="form no-auth-page">
+                <span>{"This deletes all of your tasks, rewards and points for good"}</span>
+                {error_message}
+                <input placeholder="Password" class="input" type="password" oninput={edit_pw} />
+                <div class="button-line">
+                    <span class="flex-buffer"></span>
+                    {delete_button}
+                </div>
+            </div>
+        }
+    }
+}
\ No newline at end of file
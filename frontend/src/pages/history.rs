@@ -0,0 +1,161 @@
+use yew::prelude::*;
+use data::task::TaskCompletion;
+use crate::apis::{get_history, browser_today, sign_out_frontend, decode, FetchResponse};
+use crate::components::badge_field_header;
+use yew::services::{
+    fetch::FetchTask,
+    console::ConsoleService,
+};
+use data::error::ApiError;
+use chrono::{NaiveDate, Duration};
+
+/// How many days of history are shown at once
+const DAYS_PER_PAGE: i64 = 30;
+
+struct State {
+    /// The last day shown, the first is DAYS_PER_PAGE - 1 days before it
+    to: NaiveDate,
+    /// The tasks completed in the days shown, none until they're fetched
+    completions: Option<Vec<TaskCompletion>>,
+    error_message: Option<String>,
+}
+
+/// Shows the tasks the user completed, a month at a time
+pub struct HistoryPage {
+    state: State,
+    link: ComponentLink<Self>,
+    fetch_history: Option<FetchTask>,
+}
+
+pub enum Msg {
+    FetchHistory(NaiveDate),
+    ReceiveHistory(Vec<TaskCompletion>),
+    HandleError{msg: String, error: ApiError},
+}
+
+fn first_day(to: NaiveDate) -> NaiveDate {
+    to.checked_sub_signed(Duration::days(DAYS_PER_PAGE - 1)).unwrap_or(NaiveDate::MIN)
+}
+
+impl Component for HistoryPage {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
+        ConsoleService::info("Creating history");
+
+        let to = browser_today();
+        link.send_message(Msg::FetchHistory(to));
+
+        Self {
+            state: State {
+                to,
+                completions: None,
+                error_message: None,
+            },
+            link,
+            fetch_history: None,
+        }
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        match message {
+            Msg::FetchHistory(to) => {
+                let callback = self.link.callback(|response: FetchResponse<Vec<TaskCompletion>>| {
+                    match decode(response) {
+                        Ok(completions) => Msg::ReceiveHistory(completions),
+                        Err(error) => Msg::HandleError{
+                            msg: "Failed to get task history".to_string(),
+                            error,
+                        },
+                    }
+                });
+                self.state.to = to;
+                self.fetch_history = Some(get_history(first_day(to), to, callback));
+                false
+            }
+            Msg::ReceiveHistory(completions) => {
+                self.fetch_history = None;
+                self.state.completions = Some(completions);
+                true
+            }
+            Msg::HandleError{msg, error} => {
+                self.fetch_history = None;
+                if let ApiError::Unauthorized(_) = error {
+                    sign_out_frontend();
+                } else {
+                    self.state.error_message = Some(format!("{}: {}", msg, error));
+                }
+                true
+            }
+        }
+    }
+
+    fn change(&mut self, _: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn view(&self) -> Html {
+        if let Some(msg) = &self.state.error_message {
+            return html! {<span>{msg}</span>}
+        }
+
+        let completions = match &self.state.completions {
+            Some(completions) => completions,
+            None => return html! {<span>{"Waiting for history to be fetched"}</span>},
+        };
+
+        let completions_html: Html = if completions.is_empty() {
+            html! {<span>{"No tasks were completed in these days"}</span>}
+        } else {
+            completions.iter().map(|completion: &TaskCompletion| {
+                let late = if completion.on_time {
+                    html! {<></>}
+                } else {
+                    html! {<span class="late">{"Late"}</span>}
+                };
+                let bonus = if completion.bonus > 0 {
+                    html! {<span class="bonus">{format!("{:+} for a streak of {}", completion.bonus, completion.streak)}</span>}
+                } else {
+                    html! {<></>}
+                };
+                html! {
+                    <div class="ledger-entry">
+                        <span class="date">{completion.completed_on.format("%F").to_string()}</span>
+                        <span class="source">{&completion.task_name}</span>
+                        {late}
+                        <span class="flex-buffer" />
+                        {bonus}
+                        <span class="amount credit">{format!("{:+}", completion.bspts)}</span>
+                    </div>
+                }
+            }).collect()
+        };
+
+        let to = self.state.to;
+        let from = first_day(to);
+        let today = browser_today();
+        let newer_button = if to < today {
+            let newer_to = (to + Duration::days(DAYS_PER_PAGE)).min(today);
+            html! {<span class="button" onclick={self.link.callback(move |_| {Msg::FetchHistory(newer_to)})}>{"Newer"}</span>}
+        } else {
+            html! {<></>}
+        };
+        let older_button = match from.pred_opt() {
+            Some(older_to) => html! {
+                <span class="button" onclick={self.link.callback(move |_| {Msg::FetchHistory(older_to)})}>{"Older"}</span>
+            },
+            None => html! {<></>},
+        };
+
+        html! {<>
+            {badge_field_header(&format!("Tasks done from {} to {}", from.format("%F"), to.format("%F")))}
+            <div class="ledger">{completions_html}</div>
+            <div class="button-line">
+                {newer_button}
+                <span class="flex-buffer"></span>
+                {older_button}
+            </div>
+        </>}
+    }
+}
\ No newline at end of file
@@ -0,0 +1,264 @@
+use yew::prelude::*;
+use yew::services::{
+    fetch::FetchTask,
+    dialog::DialogService,
+};
+use data::household::*;
+use data::error::{ApiError, FieldError};
+use crate::apis::{create_household, join_household, leave_household, sign_out_frontend, decode, FetchResponse};
+use crate::components::field_error;
+use crate::data::*;
+
+struct State {
+    household: ItemPtr<Option<Household>>,
+    /// The name of the household to start
+    name: String,
+    /// The invite code of the household to join
+    invite_code: String,
+    waiting: bool,
+    errors: Vec<FieldError>,
+    error_message: Option<String>,
+}
+
+#[derive(Properties, Clone)]
+pub struct Props {
+    pub store: Store,
+}
+
+/// Lets the user start or join a household to share tasks and rewards with, or leave the one they're in
+pub struct HouseholdPage {
+    state: State,
+    props: Props,
+    link: ComponentLink<Self>,
+    fetch_task: Option<FetchTask>,
+    _household_callback: Option<StoreListener<Option<Household>>>,
+}
+
+pub enum Msg {
+    ReceiveHousehold(ItemPtr<Option<Household>>),
+    UpdateName(String),
+    UpdateInviteCode(String),
+    Create,
+    Join,
+    Joined(Household),
+    Leave,
+    Left,
+    HandleError{msg: String, error: ApiError},
+}
+
+impl Component for HouseholdPage {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Self {
+            state: State {
+                household: StoreItem::new_ptr(),
+                name: "".to_string(),
+                invite_code: "".to_string(),
+                waiting: false,
+                errors: vec![],
+                error_message: None,
+            },
+            props,
+            link,
+            fetch_task: None,
+            _household_callback: None,
+        }
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        match message {
+            Msg::ReceiveHousehold(household) => {
+                self.state.household = household;
+                true
+            }
+            Msg::UpdateName(name) => {
+                self.state.name = name;
+                false
+            }
+            Msg::UpdateInviteCode(invite_code) => {
+                self.state.invite_code = invite_code;
+                false
+            }
+            Msg::Create => {
+                let callback = self.link.callback(|response: FetchResponse<Household>| {
+                    match decode(response) {
+                        Ok(household) => Msg::Joined(household),
+                        Err(error) => Msg::HandleError{
+                            msg: "Failed to start a household".to_string(),
+                            error,
+                        },
+                    }
+                });
+                let new_household = NewHousehold {name: self.state.name.clone()};
+                self.fetch_task = Some(create_household(&new_household, callback));
+                self.state.waiting = true;
+                true
+            }
+            Msg::Join => {
+                let callback = self.link.callback(|response: FetchResponse<Household>| {
+                    match decode(response) {
+                        Ok(household) => Msg::Joined(household),
+                        Err(error) => Msg::HandleError{
+                            msg: "Failed to join the household".to_string(),
+                            error,
+                        },
+                    }
+                });
+                let join = JoinHousehold {invite_code: self.state.invite_code.clone()};
+                self.fetch_task = Some(join_household(&join, callback));
+                self.state.waiting = true;
+                true
+            }
+            Msg::Joined(household) => {
+                self.fetch_task = None;
+                self.state.waiting = false;
+                self.state.errors = vec![];
+                self.state.error_message = None;
+                self.props.store.act(StoreAction::SetHousehold(Some(household)));
+                true
+            }
+            Msg::Leave => {
+                if !DialogService::confirm(
+                    "Leave the household? Your tasks and rewards will stop being shared with it"
+                ) {
+                    return false;
+                }
+                let callback = self.link.callback(|response: FetchResponse<()>| {
+                    match decode(response) {
+                        Ok(()) => Msg::Left,
+                        Err(error) => Msg::HandleError{
+                            msg: "Failed to leave the household".to_string(),
+                            error,
+                        },
+                    }
+                });
+                self.fetch_task = Some(leave_household(callback));
+                self.state.waiting = true;
+                true
+            }
+            Msg::Left => {
+                self.fetch_task = None;
+                self.state.waiting = false;
+                self.state.error_message = None;
+                self.props.store.act(StoreAction::SetHousehold(None));
+                true
+            }
+            Msg::HandleError{msg, error} => {
+                self.fetch_task = None;
+                self.state.waiting = false;
+                match error {
+                    ApiError::Unauthorized(_) => sign_out_frontend(),
+                    ApiError::Validation{fields, ..} => {
+                        self.state.errors = fields;
+                        self.state.error_message = None;
+                    }
+                    error => {
+                        self.state.errors = vec![];
+                        self.state.error_message = Some(format!("{}: {}", msg, error.message()));
+                    }
+                }
+                true
+            }
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props = props;
+        false
+    }
+
+    fn rendered(&mut self, first_render: bool) {
+        if first_render {
+            let household_callback = self.props.store.household.subscribe(
+                self.link.callback(Msg::ReceiveHousehold),
+                true
+            );
+            self._household_callback = Some(household_callback);
+        }
+    }
+
+    fn view(&self) -> Html {
+        let message = match &self.state.error_message {
+            Some(msg) => html! {<span>{msg}</span>},
+            None => html! {<></>},
+        };
+
+        let household_html = match &*self.state.household.borrow() {
+            Some(household) => self.view_household(household),
+            None => self.view_join(),
+        };
+
+        html! {
+            <div class="form no-auth-page household">
+                {message}
+                {household_html}
+            </div>
+        }
+    }
+}
+
+impl HouseholdPage {
+    /// Shows the household the user is in, with who else is in it
+    fn view_household(&self, household: &Household) -> Html {
+        let members: Html = household.members.iter().map(|member| {
+            html! {
+                <div class="member">
+                    <span>{&member.uname}</span>
+                    <span class="flex-buffer"></span>
+                    <span class="sub-info">{format!("{} pts", member.bspts)}</span>
+                </div>
+            }
+        }).collect();
+
+        let leave_button = if self.state.waiting {
+            html! {<span class="button">{"Waiting..."}</span>}
+        } else {
+            html! {<span class="button delete" onclick={self.link.callback(|_| {Msg::Leave})}>{"Leave Household"}</span>}
+        };
+
+        html! {<>
+            <span class="name">{&household.name}</span>
+            <span>{"Give others this code so they can join"}</span>
+            <input class="input invite-code" type="text" readonly=true value={&household.invite_code} />
+            <div class="members">{members}</div>
+            <div class="button-line">
+                <span class="flex-buffer"></span>
+                {leave_button}
+            </div>
+        </>}
+    }
+
+    /// Lets the user start a household or join one with an invite code
+    fn view_join(&self) -> Html {
+        let edit_name = self.link.callback(|input: InputData| {Msg::UpdateName(input.value)});
+        let edit_code = self.link.callback(|input: InputData| {Msg::UpdateInviteCode(input.value)});
+        let buttons = if self.state.waiting {
+            (html! {<span class="save button">{"Waiting..."}</span>}, html! {<></>})
+        } else {
+            (
+                html! {<span class="save button" onclick={self.link.callback(|_| {Msg::Create})}>{"Start Household"}</span>},
+                html! {<span class="save button" onclick={self.link.callback(|_| {Msg::Join})}>{"Join Household"}</span>},
+            )
+        };
+        let (create_button, join_button) = buttons;
+
+        html! {<>
+            <span>{"Start a household to share tasks and rewards with the people you live with"}</span>
+            <input placeholder="Household Name" class="input" type="text" oninput={edit_name} value={&self.state.name} />
+            {field_error(&self.state.errors, "name")}
+            <div class="button-line">
+                <span class="flex-buffer"></span>
+                {create_button}
+            </div>
+            <span>{"Or join one with the invite code you were given"}</span>
+            <input placeholder="Invite Code" class="input" type="text" oninput={edit_code} value={&self.state.invite_code} />
+            {field_error(&self.state.errors, "invite_code")}
+            <div class="button-line">
+                <span class="flex-buffer"></span>
+                {join_button}
+            </div>
+        </>}
+    }
+}
\ No newline at end of file
@@ -4,10 +4,26 @@ mod signin;
 mod signup;
 mod tasks;
 mod rewards;
+mod points;
+mod sessions;
+mod password;
+mod delete_account;
+mod calendar;
+mod history;
+mod archived;
+mod household;
 
 pub use home::{Home};
 pub use signin::SignIn;
 pub use signup::SignUp;
 pub use no_auth::AuthOptions;
 pub use tasks::TasksPage;
-pub use rewards::RewardsPage;
\ No newline at end of file
+pub use rewards::RewardsPage;
+pub use points::PointsPage;
+pub use sessions::SessionsPage;
+pub use password::PasswordPage;
+pub use delete_account::DeleteAccountPage;
+pub use calendar::CalendarPage;
+pub use history::HistoryPage;
+pub use archived::ArchivedPage;
+pub use household::HouseholdPage;
\ No newline at end of file
@@ -0,0 +1,150 @@
+use yew::prelude::*;
+use yew::services::fetch::{FetchTask};
+use data::user::*;
+use data::error::ApiError;
+use crate::apis::{change_password, sign_out_frontend, decode, FetchResponse};
+use crate::data::*;
+
+struct State {
+    change: PasswordChange,
+    /// The new password typed a second time, to catch typos
+    confirm_password: String,
+    saving: bool,
+    saved: bool,
+    error_message: Option<String>,
+}
+
+#[derive(Properties, Clone)]
+pub struct Props {
+    pub store: Store,
+}
+
+/// Lets the user change their password. Doing so logs them out everywhere else
+pub struct PasswordPage {
+    state: State,
+    props: Props,
+    link: ComponentLink<Self>,
+    fetch_task: Option<FetchTask>,
+}
+
+pub enum Msg {
+    ChangePassword,
+    Saved(User),
+    TryAgain(String),
+    UpdateCurrentPassword(String),
+    UpdateNewPassword(String),
+    UpdateConfirmPassword(String),
+}
+
+impl Component for PasswordPage {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Self {
+            state: State {
+                change: PasswordChange {
+                    current_password: "".to_string(),
+                    new_password: "".to_string(),
+                },
+                confirm_password: "".to_string(),
+                saving: false,
+                saved: false,
+                error_message: None,
+            },
+            props,
+            link,
+            fetch_task: None,
+        }
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        match message {
+            Msg::ChangePassword => {
+                if self.state.change.new_password != self.state.confirm_password {
+                    self.link.send_message(Msg::TryAgain("The new passwords don't match".to_string()));
+                    return false;
+                }
+                let callback = self.link.callback(|response: FetchResponse<User>| {
+                    match decode(response) {
+                        Ok(user) => Msg::Saved(user),
+                        Err(ApiError::Forbidden(_)) => {
+                            Msg::TryAgain("Incorrect Password".to_string())
+                        }
+                        Err(ApiError::Unauthorized(_)) => {
+                            sign_out_frontend();
+                            Msg::TryAgain("You've been logged out".to_string())
+                        }
+                        Err(error @ ApiError::Validation{..}) => Msg::TryAgain(error.to_string()),
+                        Err(_) => Msg::TryAgain("There was some problem, please try again".to_string()),
+                    }
+                });
+                self.fetch_task = Some(change_password(&self.state.change, callback));
+                self.state.saving = true;
+                true
+            }
+            Msg::Saved(user) => {
+                self.fetch_task = None;
+                self.state.saving = false;
+                self.state.saved = true;
+                self.state.error_message = None;
+                self.props.store.act(StoreAction::StartSession(user));
+                true
+            }
+            Msg::TryAgain(error) => {
+                self.fetch_task = None;
+                self.state.error_message = Some(error);
+                self.state.saving = false;
+                true
+            }
+            Msg::UpdateCurrentPassword(password) => {
+                self.state.change.current_password = password;
+                false
+            }
+            Msg::UpdateNewPassword(password) => {
+                self.state.change.new_password = password;
+                false
+            }
+            Msg::UpdateConfirmPassword(password) => {
+                self.state.confirm_password = password;
+                false
+            }
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props = props;
+        false
+    }
+
+    fn view(&self) -> Html {
+        let message = match (&self.state.error_message, self.state.saved) {
+            (Some(msg), _) => html! {<span>{msg}</span>},
+            (None, true) => html! {<span>{"Your password was changed, and you've been logged out everywhere else"}</span>},
+            (None, false) => html! {<></>},
+        };
+
+        let edit_current = self.link.callback(|input: InputData| {Msg::UpdateCurrentPassword(input.value)});
+        let edit_new = self.link.callback(|input: InputData| {Msg::UpdateNewPassword(input.value)});
+        let edit_confirm = self.link.callback(|input: InputData| {Msg::UpdateConfirmPassword(input.value)});
+        let on_save = self.link.callback(|_| {Msg::ChangePassword});
+        let save_button = if self.state.saving {
+            html! {<span class="save button">{"Saving..."}</span>}
+        } else {
+            html! {<span class="save button" onclick={on_save}>{"Change Password"}</span>}
+        };
+
+        html! {
+            <div class="form no-auth-page">
+                {message}
+                <input placeholder="Current Password" class="input" type="password" oninput={edit_current} />
+                <input placeholder="New Password" class="input" type="password" oninput={edit_new} />
+                <input placeholder="New Password Again" class="input" type="password" oninput={edit_confirm} />
+                <div class="button-line">
+                    <span class="flex-buffer"></span>
+                    {save_button}
+                </div>
+            </div>
+        }
+    }
+}
\ No newline at end of file
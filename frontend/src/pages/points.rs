@@ -0,0 +1,145 @@
+use yew::prelude::*;
+use data::ledger::*;
+use crate::apis::{get_ledger, sign_out_frontend, decode, FetchResponse};
+use crate::components::badge_field_header;
+use yew::services::{
+    fetch::FetchTask,
+    console::ConsoleService,
+};
+use data::error::ApiError;
+
+struct State {
+    /// The page of the ledger currently shown, none until it's fetched
+    ledger: Option<LedgerPage>,
+    error_message: Option<String>,
+}
+
+/// Shows the history of where the user's points came from and went
+pub struct PointsPage {
+    state: State,
+    link: ComponentLink<Self>,
+    fetch_ledger: Option<FetchTask>,
+}
+
+pub enum Msg {
+    FetchPage(i64),
+    ReceivePage(LedgerPage),
+    HandleError{msg: String, error: ApiError},
+}
+
+/// Describe where an entry's points came from
+fn describe_source(source: LedgerSource) -> &'static str {
+    match source {
+        LedgerSource::TaskCompletion => "Completed a task",
+        LedgerSource::RewardRedemption => "Took a reward",
+        LedgerSource::StreakBonus => "Kept up a streak",
+        LedgerSource::CompletionUndone => "Undid a task",
+        LedgerSource::Penalty => "Missed a task",
+        LedgerSource::Adjustment => "Adjustment",
+        LedgerSource::GoalDeposit => "Saved for a goal",
+        LedgerSource::GoalRelease => "Released from a goal",
+    }
+}
+
+impl Component for PointsPage {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
+        ConsoleService::info("Creating points");
+
+        link.send_message(Msg::FetchPage(0));
+
+        Self {
+            state: State {
+                ledger: None,
+                error_message: None,
+            },
+            link,
+            fetch_ledger: None,
+        }
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        match message {
+            Msg::FetchPage(page) => {
+                let callback = self.link.callback(|response: FetchResponse<LedgerPage>| {
+                    match decode(response) {
+                        Ok(ledger) => Msg::ReceivePage(ledger),
+                        Err(error) => Msg::HandleError{
+                            msg: "Failed to get points history".to_string(),
+                            error,
+                        },
+                    }
+                });
+                self.fetch_ledger = Some(get_ledger(page, callback));
+                false
+            }
+            Msg::ReceivePage(ledger) => {
+                self.fetch_ledger = None;
+                self.state.ledger = Some(ledger);
+                true
+            }
+            Msg::HandleError{msg, error} => {
+                self.fetch_ledger = None;
+                if let ApiError::Unauthorized(_) = error {
+                    sign_out_frontend();
+                } else {
+                    self.state.error_message = Some(format!("{}: {}", msg, error));
+                }
+                true
+            }
+        }
+    }
+
+    fn change(&mut self, _: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn view(&self) -> Html {
+        if let Some(msg) = &self.state.error_message {
+            return html! {<span>{msg}</span>}
+        }
+
+        let ledger = match &self.state.ledger {
+            Some(ledger) => ledger,
+            None => return html! {<span>{"Waiting for points to be fetched"}</span>},
+        };
+
+        let entries_html: Html = ledger.entries.iter().map(|entry: &LedgerEntry| {
+            let amount_class = if entry.amount < 0 {"amount debit"} else {"amount credit"};
+            html! {
+                <div class="ledger-entry">
+                    <span class="date">{entry.created_at.format("%F %H:%M").to_string()}</span>
+                    <span class="source">{describe_source(entry.source)}</span>
+                    <span class="flex-buffer" />
+                    <span class={amount_class}>{format!("{:+}", entry.amount)}</span>
+                </div>
+            }
+        }).collect();
+
+        let page = ledger.page;
+        let has_previous = page > 0;
+        let has_next = (page + 1) * ledger.per_page < ledger.total;
+        let previous_button = if has_previous {
+            html! {<span class="button" onclick={self.link.callback(move |_| {Msg::FetchPage(page - 1)})}>{"Newer"}</span>}
+        } else {
+            html! {<></>}
+        };
+        let next_button = if has_next {
+            html! {<span class="button" onclick={self.link.callback(move |_| {Msg::FetchPage(page + 1)})}>{"Older"}</span>}
+        } else {
+            html! {<></>}
+        };
+
+        html! {<>
+            {badge_field_header("Where your points went")}
+            <div class="ledger">{entries_html}</div>
+            <div class="button-line">
+                {previous_button}
+                <span class="flex-buffer"></span>
+                {next_button}
+            </div>
+        </>}
+    }
+}
\ No newline at end of file
@@ -1,13 +1,13 @@
 use yew::prelude::*;
 use data::reward::Reward;
-use crate::apis::{get_rewards, sign_out_frontend, FetchResponse};
+use data::goal::Goal;
+use crate::apis::{get_rewards, get_goals, pin_goal, sign_out_frontend, decode, FetchResponse};
 use crate::components::*;
-use yew::format::{Json};
 use yew::services::{
     fetch::FetchTask,
     console::ConsoleService,
 };
-use http::status::StatusCode;
+use data::error::{ApiError, ErrorCode};
 use crate::data::*;
 use std::collections::VecDeque;
 
@@ -16,8 +16,12 @@ type StoreCallbacks = Option<StoreListener<VecDeque<Reward>>>;
 struct State {
     /// The rewards currently available
     rewards: ItemPtr<VecDeque<Reward>>,
+    /// The rewards the user is saving for
+    goals: Vec<Goal>,
     edit_popup: bool,
     error_message: Option<String>,
+    /// Why the last reward couldn't be taken, shown until it's dismissed
+    refusal: Option<ApiError>,
     store_callbacks: StoreCallbacks,
 }
 
@@ -31,6 +35,7 @@ pub struct RewardsPage {
     props: Props,
     link: ComponentLink<Self>,
     fetch_rewards: Option<FetchTask>,
+    fetch_goals: Option<FetchTask>,
 }
 
 pub enum Msg {
@@ -39,7 +44,14 @@ pub enum Msg {
     OpenRewardCreationComponent,
     NewRewardAdded(Box<Reward>),
     CancelCreateReward,
-    HandleError{msg: String, code: Option<StatusCode>},
+    FetchGoals,
+    ReceiveGoals(Vec<Goal>),
+    /// Start saving for the reward with the given id
+    PinGoal(i32),
+    HandleError{msg: String, error: ApiError},
+    /// A reward's rules stopped it from being taken
+    ShowRefusal(ApiError),
+    DismissRefusal,
     Noop,
 }
 
@@ -52,17 +64,21 @@ impl Component for RewardsPage {
 
         // Get the ball rolling on getting the rewards
         link.send_message(Msg::FetchRewards);
+        link.send_message(Msg::FetchGoals);
 
         Self {
             state: State {
                 rewards: StoreItem::new_ptr(),
+                goals: vec![],
                 edit_popup: false,
                 error_message: None,
+                refusal: None,
                 store_callbacks: None,
             },
             props,
             link,
             fetch_rewards: None,
+            fetch_goals: None,
         }
     }
 
@@ -73,15 +89,15 @@ impl Component for RewardsPage {
                 ConsoleService::info("Getting rewards");
                 let store_clone = self.props.store.clone();
                 let callback = self.link.callback(move |response: FetchResponse<Vec<Reward>>| {
-                    match response.into_parts() {
-                        (_, Json(Ok(rewards))) => {
+                    match decode(response) {
+                        Ok(rewards) => {
                             store_clone.act(StoreAction::SetRewards(rewards.into()));
                             Msg::Noop
                         }
-                        (parts, _) => {
+                        Err(error) => {
                             Msg::HandleError{
                                 msg: "Failed to get rewards".to_string(),
-                                code: Some(parts.status),
+                                error,
                             }
                         }
                     }
@@ -111,14 +127,53 @@ impl Component for RewardsPage {
                 self.state.edit_popup = false;
                 true
             }
-            Msg::HandleError{msg, code} => {
-                if let Some(StatusCode::UNAUTHORIZED) = code {
+            Msg::FetchGoals => {
+                let callback = self.link.callback(|response: FetchResponse<Vec<Goal>>| {
+                    match decode(response) {
+                        Ok(goals) => Msg::ReceiveGoals(goals),
+                        Err(error) => Msg::HandleError{
+                            msg: "Failed to get goals".to_string(),
+                            error,
+                        },
+                    }
+                });
+                self.fetch_goals = Some(get_goals(callback));
+                false
+            }
+            Msg::ReceiveGoals(goals) => {
+                self.fetch_goals = None;
+                self.state.goals = goals;
+                true
+            }
+            Msg::PinGoal(reward_id) => {
+                let callback = self.link.callback(|response: FetchResponse<Goal>| {
+                    match decode(response) {
+                        Ok(_) => Msg::FetchGoals,
+                        Err(error) => Msg::HandleError{
+                            msg: "Failed to save for the reward".to_string(),
+                            error,
+                        },
+                    }
+                });
+                self.fetch_goals = Some(pin_goal(reward_id, callback));
+                false
+            }
+            Msg::HandleError{msg, error} => {
+                if let ApiError::Unauthorized(_) = error {
                     sign_out_frontend();
                 } else {
-                    self.state.error_message = Some(msg);
+                    self.state.error_message = Some(format!("{}: {}", msg, error));
                 }
                 true
             }
+            Msg::ShowRefusal(error) => {
+                self.state.refusal = Some(error);
+                true
+            }
+            Msg::DismissRefusal => {
+                self.state.refusal = None;
+                true
+            }
             Msg::Noop => false,
         }
     }
@@ -148,6 +203,9 @@ impl Component for RewardsPage {
             }
         }
 
+        let on_refused = self.link.callback(Msg::ShowRefusal);
+        let on_pin = self.link.callback(Msg::PinGoal);
+        let refetch_goals = self.link.callback(|_| Msg::FetchGoals);
         let rewards_html: Html = self.state.rewards.borrow().iter().map(|reward: &Reward| {
                 html!{
                 <RewardItem
@@ -155,6 +213,10 @@ impl Component for RewardsPage {
                     // the rewards in boxes that can get passed around from the get go
                     reward={Box::new(reward.clone())}
                     store={self.props.store.clone()}
+                    on_refused={on_refused.clone()}
+                    is_goal={self.state.goals.iter().any(|goal| goal.reward_id == reward.id)}
+                    on_pin={on_pin.clone()}
+                    on_taken={refetch_goals.clone()}
                 />
             }
         }).collect();
@@ -170,7 +232,7 @@ impl Component for RewardsPage {
 
             html! {
                 <Popup>
-                    <RewardEditor reward_to_edit={None} on_done={on_done} />
+                    <RewardEditor reward_to_edit={None} on_done={on_done} store={self.props.store.clone()} />
                 </Popup>
             }
         } else {
@@ -184,10 +246,48 @@ impl Component for RewardsPage {
             }
         };
 
+        let goals_html = if self.state.goals.is_empty() {
+            html! {<></>}
+        } else {
+            let goal_items: Html = self.state.goals.iter().map(|goal: &Goal| {
+                html! {
+                    <GoalItem
+                        goal={goal.clone()}
+                        store={self.props.store.clone()}
+                        on_cancelled={refetch_goals.clone()}
+                    />
+                }
+            }).collect();
+            html! {<>
+                {badge_field_header("Saving for")}
+                <div class="goals">{goal_items}</div>
+            </>}
+        };
+
+        let refusal_html = match &self.state.refusal {
+            Some(error) => {
+                let heading = match error.code() {
+                    ErrorCode::CoolingDown => "Not yet",
+                    ErrorCode::LimitReached => "Limit reached",
+                    ErrorCode::InsufficientPoints => "Not enough points",
+                    _ => "Can't take that",
+                };
+                html! {
+                    <div class="toast">
+                        <span class="message"><strong>{heading}</strong>{" "}{error.message()}</span>
+                        <span class="button" onclick={self.link.callback(|_| {Msg::DismissRefusal})}>{"Dismiss"}</span>
+                    </div>
+                }
+            }
+            None => html! {<></>},
+        };
+
         html! {
             <>
                 <div>{new_reward_html}</div>
+                {goals_html}
                 <div class="badge-field">{rewards_html}</div>
+                {refusal_html}
             </>
         }
     }
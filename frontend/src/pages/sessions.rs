@@ -0,0 +1,167 @@
+use yew::prelude::*;
+use data::session::SessionInfo;
+use crate::apis::{get_sessions, revoke_session, sign_out, sign_out_frontend, decode, FetchResponse};
+use crate::components::badge_field_header;
+use crate::data::*;
+use yew::services::{
+    fetch::FetchTask,
+    console::ConsoleService,
+};
+use data::error::ApiError;
+
+struct State {
+    /// Everywhere the user is logged in, none until it's fetched
+    sessions: Option<Vec<SessionInfo>>,
+    error_message: Option<String>,
+}
+
+#[derive(Properties, Clone)]
+pub struct Props {
+    pub store: Store,
+}
+
+/// Shows everywhere the user is logged in, and lets them log out
+/// of this device or any of the others
+pub struct SessionsPage {
+    state: State,
+    props: Props,
+    link: ComponentLink<Self>,
+    fetch_task: Option<FetchTask>,
+}
+
+pub enum Msg {
+    FetchSessions,
+    ReceiveSessions(Vec<SessionInfo>),
+    Revoke(i32),
+    SignOut,
+    SignedOut,
+    HandleError{msg: String, error: ApiError},
+}
+
+impl Component for SessionsPage {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        ConsoleService::info("Creating sessions");
+
+        link.send_message(Msg::FetchSessions);
+
+        Self {
+            state: State {
+                sessions: None,
+                error_message: None,
+            },
+            props,
+            link,
+            fetch_task: None,
+        }
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        match message {
+            Msg::FetchSessions => {
+                let callback = self.link.callback(|response: FetchResponse<Vec<SessionInfo>>| {
+                    match decode(response) {
+                        Ok(sessions) => Msg::ReceiveSessions(sessions),
+                        Err(error) => Msg::HandleError{
+                            msg: "Failed to get where you're logged in".to_string(),
+                            error,
+                        },
+                    }
+                });
+                self.fetch_task = Some(get_sessions(callback));
+                false
+            }
+            Msg::ReceiveSessions(sessions) => {
+                self.fetch_task = None;
+                self.state.sessions = Some(sessions);
+                true
+            }
+            Msg::Revoke(session_id) => {
+                let callback = self.link.callback(|response: FetchResponse<()>| {
+                    match decode(response) {
+                        Ok(()) => Msg::FetchSessions,
+                        Err(error) => Msg::HandleError{
+                            msg: "Failed to log out of that device".to_string(),
+                            error,
+                        },
+                    }
+                });
+                self.fetch_task = Some(revoke_session(session_id, callback));
+                false
+            }
+            Msg::SignOut => {
+                let callback = self.link.callback(|response: FetchResponse<()>| {
+                    match decode(response) {
+                        Ok(()) => Msg::SignedOut,
+                        Err(error) => Msg::HandleError{
+                            msg: "Failed to sign out".to_string(),
+                            error,
+                        },
+                    }
+                });
+                self.fetch_task = Some(sign_out(callback));
+                false
+            }
+            Msg::SignedOut => {
+                self.fetch_task = None;
+                self.props.store.act(StoreAction::EndSession);
+                sign_out_frontend();
+                false
+            }
+            Msg::HandleError{msg, error} => {
+                self.fetch_task = None;
+                if let ApiError::Unauthorized(_) = error {
+                    sign_out_frontend();
+                } else {
+                    self.state.error_message = Some(format!("{}: {}", msg, error));
+                }
+                true
+            }
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props = props;
+        false
+    }
+
+    fn view(&self) -> Html {
+        if let Some(msg) = &self.state.error_message {
+            return html! {<span>{msg}</span>}
+        }
+
+        let sessions = match &self.state.sessions {
+            Some(sessions) => sessions,
+            None => return html! {<span>{"Waiting for sessions to be fetched"}</span>},
+        };
+
+        let sessions_html: Html = sessions.iter().map(|session: &SessionInfo| {
+            let session_id = session.id;
+            let action = if session.current {
+                html! {<span class="current">{"This device"}</span>}
+            } else {
+                html! {
+                    <span class="button" onclick={self.link.callback(move |_| {Msg::Revoke(session_id)})}>
+                        {"Log Out"}
+                    </span>
+                }
+            };
+            html! {
+                <div class="session">
+                    <span class="date">{format!("Logged in {}", session.created_at.format("%F %H:%M"))}</span>
+                    <span class="date">{format!("Last used {}", session.last_seen.format("%F %H:%M"))}</span>
+                    <span class="flex-buffer" />
+                    {action}
+                </div>
+            }
+        }).collect();
+
+        html! {<>
+            {badge_field_header("Where you're logged in")}
+            <div class="sessions">{sessions_html}</div>
+            <div class="top button" onclick={self.link.callback(|_| {Msg::SignOut})}>{"Sign Out"}</div>
+        </>}
+    }
+}
\ No newline at end of file
@@ -1,9 +1,8 @@
 use yew::prelude::*;
 use yew::services::fetch::{FetchTask};
 use data::user::*;
-use yew::format::{Json};
-use crate::apis::{sign_in, FetchResponse};
-use http::status::StatusCode;
+use crate::apis::{sign_in, decode, FetchResponse};
+use data::error::ApiError;
 use crate::data::*;
 
 struct State {
@@ -56,17 +55,17 @@ impl Component for SignIn {
         match message {
             Msg::LoginUser => {
                 let callback = self.link.callback(|jwt_response: FetchResponse<User>| {
-                    match jwt_response.into_parts() {
-                        (head, _) if head.status == StatusCode::NOT_FOUND => {
+                    match decode(jwt_response) {
+                        Ok(user) => {
+                            Msg::SaveUser(user)
+                        }
+                        Err(ApiError::NotFound(_)) => {
                             Msg::TryAgain("No user was found with that username".to_string())
                         }
-                        (head, _) if head.status == StatusCode::UNAUTHORIZED => {
+                        Err(ApiError::Unauthorized(_)) => {
                             Msg::TryAgain("Incorrect Password".to_string())
                         }
-                        (_, Json(Ok(user))) => {
-                            Msg::SaveUser(user)
-                        }
-                        _ => {
+                        Err(_) => {
                             Msg::TryAgain("There was some problem, please try again".to_string())
                         }
                     }
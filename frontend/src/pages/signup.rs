@@ -1,14 +1,18 @@
 use yew::prelude::*;
 use yew::services::fetch::{FetchTask};
 use data::user::*;
-use yew::format::{Json};
-use crate::apis::{sign_up, FetchResponse};
+use data::error::{ApiError, FieldError};
+use data::validate::Validate;
+use crate::apis::{sign_up, decode, FetchResponse};
+use crate::components::field_error;
 use crate::data::*;
 
 struct State {
     new_user: NewUser,
     saving: bool,
     error_message: Option<String>,
+    /// What was wrong with the user the last time they tried to sign up
+    errors: Vec<FieldError>,
 }
 
 #[derive(Properties, Clone)]
@@ -27,6 +31,7 @@ pub enum Msg {
     CreateNewUser,
     SaveUser(User),
     TryAgain(String),
+    ShowErrors(Vec<FieldError>),
     UpdateUname(String),
     UpdatePassword(String),
 }
@@ -44,6 +49,7 @@ impl Component for SignUp {
                 },
                 saving: false,
                 error_message: None,
+                errors: vec![],
             },
             props,
             link,
@@ -54,11 +60,18 @@ impl Component for SignUp {
     fn update(&mut self, message: Self::Message) -> ShouldRender {
         match message {
             Msg::CreateNewUser => {
+                self.state.errors = self.state.new_user.field_errors();
+                if !self.state.errors.is_empty() {
+                    return true;
+                }
                 let callback = self.link.callback(|jwt_response: FetchResponse<User>| {
-                    if let (_, Json(Ok(user))) = jwt_response.into_parts() {
-                        Msg::SaveUser(user)
-                    } else {
-                        Msg::TryAgain("There was an issue creating that user".to_string())
+                    match decode(jwt_response) {
+                        Ok(user) => Msg::SaveUser(user),
+                        Err(ApiError::Conflict(_)) => {
+                            Msg::TryAgain("There's already a user with that username".to_string())
+                        }
+                        Err(ApiError::Validation{fields, ..}) => Msg::ShowErrors(fields),
+                        Err(_) => Msg::TryAgain("There was an issue creating that user".to_string()),
                     }
                 });
                 let fetch_task = sign_up(self.state.new_user.clone(), callback);
@@ -75,6 +88,11 @@ impl Component for SignUp {
                 self.state.saving = false;
                 true
             },
+            Msg::ShowErrors(errors) => {
+                self.state.errors = errors;
+                self.state.saving = false;
+                true
+            },
             Msg::UpdateUname(uname) => {
                 self.state.new_user.uname = uname;
                 true
@@ -106,7 +124,9 @@ impl Component for SignUp {
             <div class="form no-auth-page">
                 {error_message}
                 <input placeholder="User Name" class="input" type="text" oninput={edit_uname} />
+                {field_error(&self.state.errors, "uname")}
                 <input placeholder="Password" class="input" type="password" oninput={edit_pw} />
+                {field_error(&self.state.errors, "password")}
                 <div class="button-line">
                     <span class="flex-buffer"></span>
                     <span class="save button" onclick={on_sign_up}>{"Sign Up"}</span>
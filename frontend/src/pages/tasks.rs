@@ -1,13 +1,12 @@
 use yew::prelude::*;
 use data::task::{Task};
-use crate::apis::{get_todo_tasks, get_done_tasks, sign_out_frontend, undo_done_tasks, FetchResponse};
+use crate::apis::{get_todo_tasks, get_done_tasks, sign_out_frontend, decode, FetchResponse};
 use crate::components::*;
-use yew::format::{Json};
 use yew::services::{
     fetch::FetchTask,
     console::ConsoleService,
 };
-use http::status::StatusCode;
+use data::error::ApiError;
 use crate::data::*;
 
 type Callbacks = Option<(
@@ -41,13 +40,12 @@ pub enum Msg {
     /// Do nothing
     NoOp,
     FetchTodoTasks,
-    FetchResetableTasksFromDoneTasks,
     FetchDoneTasks,
     ReceiveTasks{tasks: ItemPtr<TaskList>, are_done: bool},
     OpenTaskCreationComponent,
     NewTaskCommitted(Box<Task>),
     CancelCreateTask,
-    HandleError{msg: String, code: Option<StatusCode>},
+    HandleError{msg: String, error: ApiError},
 }
 
 impl Component for TasksPage {
@@ -57,7 +55,8 @@ impl Component for TasksPage {
     fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
         ConsoleService::info("Creating tasks");
 
-        // Get the ball rolling on getting the tasks
+        // Get the ball rolling on getting the tasks. The server rolls them
+        // over to the new day on its own, so they can be shown as they are
         link.send_message(Msg::FetchTodoTasks);
 
         Self {
@@ -81,44 +80,20 @@ impl Component for TasksPage {
                 ConsoleService::info("Getting todo tasks");
                 let store_clone = self.props.store.clone();
                 let callback = self.link.callback(move |response: FetchResponse<Vec<Task>>| {
-                    match response.into_parts() {
-                        (_, Json(Ok(tasks))) => {
+                    match decode(response) {
+                        Ok(tasks) => {
                             store_clone.act(StoreAction::SetTasks{tasks: tasks, are_done: false});
-                            Msg::FetchResetableTasksFromDoneTasks
-                        }
-                        (parts, _) => {
-                            Msg::HandleError{
-                                msg: "Failed to get tasks".to_string(),
-                                code: Some(parts.status),
-                            }
-                        }
-                    }
-                });
-                let fetch_task = get_todo_tasks(callback);
-                self.fetch_tasks = Some(fetch_task);
-                false
-            }
-            Msg::FetchResetableTasksFromDoneTasks => {
-                ConsoleService::info("Getting todo tasks");
-                let store_clone = self.props.store.clone();
-                let callback = self.link.callback(move |response: FetchResponse<Vec<Task>>| {
-                    match response.into_parts() {
-                        (_, Json(Ok(tasks))) => {
-                            store_clone.todo_tasks.update(move |todo_tasks| {
-                                todo_tasks.push_vec(&tasks);
-                                true
-                            });
                             Msg::FetchDoneTasks
                         }
-                        (parts, _) => {
+                        Err(error) => {
                             Msg::HandleError{
                                 msg: "Failed to get tasks".to_string(),
-                                code: Some(parts.status),
+                                error,
                             }
                         }
                     }
                 });
-                let fetch_task = undo_done_tasks(callback);
+                let fetch_task = get_todo_tasks(callback);
                 self.fetch_tasks = Some(fetch_task);
                 false
             }
@@ -126,18 +101,18 @@ impl Component for TasksPage {
                 ConsoleService::info("Getting done tasks");
                 let store_clone = self.props.store.clone();
                 let callback = self.link.callback(move |response: FetchResponse<Vec<Task>>| {
-                    match response.into_parts() {
-                        (_, Json(Ok(tasks))) => {
+                    match decode(response) {
+                        Ok(tasks) => {
                             store_clone.act(StoreAction::SetTasks{
                                 tasks,
                                 are_done: true,
                             });
                             Msg::NoOp
                         }
-                        (parts, _) => {
+                        Err(error) => {
                             Msg::HandleError{
                                 msg: "Failed to get tasks".to_string(),
-                                code: Some(parts.status),
+                                error,
                             }
                         }
                     }
@@ -170,11 +145,11 @@ impl Component for TasksPage {
                 self.state.edit_popup = false;
                 true
             }
-            Msg::HandleError{msg, code} => {
-                if let Some(StatusCode::UNAUTHORIZED) = code {
+            Msg::HandleError{msg, error} => {
+                if let ApiError::Unauthorized(_) = error {
                     sign_out_frontend();
                 } else {
-                    self.state.error_message = Some(msg);
+                    self.state.error_message = Some(format!("{}: {}", msg, error));
                 }
                 true
             }
@@ -249,7 +224,7 @@ impl Component for TasksPage {
 
             html! {
                 <Popup>
-                    <TaskEditor task_to_edit={None} on_done={on_done} />
+                    <TaskEditor task_to_edit={None} on_done={on_done} store={self.props.store.clone()} />
                 </Popup>
             }
         } else {
@@ -267,6 +242,7 @@ impl Component for TasksPage {
             <div>{new_task_html}</div>
             {todo_tasks_html}
             {done_tasks_html}
+            <UndoToast store={self.props.store.clone()} />
         </>}
     }
 }
\ No newline at end of file